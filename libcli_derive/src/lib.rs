@@ -0,0 +1,211 @@
+//! # Derive macro for libcli
+//! Implements `libcli::args::Options` for a struct with named fields
+//! Each field becomes an option, see the documentation of the Options trait
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, LitChar, LitStr, Type};
+
+#[proc_macro_derive(Options, attributes(option))]
+pub fn derive_options(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+// How the values of a field are read from the config
+enum Kind {
+    Switch,
+    Optional,
+    Many,
+    Single,
+}
+
+// The settings of a field from its type, doc comment and #[option(..)] attribute
+struct FieldOptions {
+    name: String,
+    abrev: Option<LitChar>,
+    desc: String,
+    policy: Option<Expr>,
+    default: Option<LitStr>,
+    required: bool,
+    positional: bool,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "Options can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Options can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut specs = Vec::new();
+    let mut reads = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("Named field without a name");
+        let options = field_options(field)?;
+        let (kind, inner) = kind(&field.ty);
+
+        let default_policy = match kind {
+            Kind::Switch => quote!(::libcli::args::OptionPolicy::Exact(0)),
+            Kind::Many => quote!(::libcli::args::OptionPolicy::AtLeast(0)),
+            Kind::Optional | Kind::Single => quote!(::libcli::args::OptionPolicy::Exact(1)),
+        };
+        let policy = match &options.policy {
+            Some(policy) => quote!({
+                #[allow(unused_imports)]
+                use ::libcli::args::OptionPolicy::*;
+                #policy
+            }),
+            None => default_policy,
+        };
+
+        let name = &options.name;
+        let desc = &options.desc;
+        let required =
+            options.required || (matches!(kind, Kind::Single) && options.default.is_none());
+        let abrev = match &options.abrev {
+            Some(abrev) => quote!(#abrev),
+            None => quote!('\0'),
+        };
+
+        let mut spec = if options.positional {
+            quote!(::libcli::args::OptionSpec::positional(#name, #desc, #required, #policy))
+        } else {
+            quote!(::libcli::args::OptionSpec::new(#abrev, #name, #desc, #required, #policy))
+        };
+        if let Some(default) = &options.default {
+            spec = quote!(#spec.default_values(&[#default]));
+        }
+        specs.push(spec);
+
+        reads.push(match kind {
+            Kind::Switch => quote!(#ident: config.option(#name).is_some()),
+            Kind::Optional => quote!(#ident: config.get::<#inner>(#name)?),
+            Kind::Many => quote!(#ident: config.get_many::<#inner>(#name)?),
+            Kind::Single => quote!(
+                #ident: config.get::<#inner>(#name)?.ok_or_else(|| {
+                    ::libcli::args::ParseError::MissingRequired {
+                        name: ::std::string::String::from(#name),
+                    }
+                })?
+            ),
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::libcli::args::Options for #ident #ty_generics #where_clause {
+            fn specs() -> ::std::vec::Vec<::libcli::args::OptionSpec> {
+                vec![#(#specs),*]
+            }
+
+            fn from_config(
+                config: &::libcli::args::Config,
+            ) -> ::std::result::Result<Self, ::libcli::args::ParseError> {
+                Ok(Self {
+                    #(#reads),*
+                })
+            }
+        }
+    })
+}
+
+// Reads the doc comment and #[option(..)] attribute of a field
+fn field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let ident = field.ident.as_ref().expect("Named field without a name");
+    let mut options = FieldOptions {
+        name: ident.to_string().trim_start_matches("r#").replace('_', "-"),
+        abrev: None,
+        desc: String::new(),
+        policy: None,
+        default: None,
+        required: false,
+        positional: false,
+    };
+
+    let mut doc = Vec::new();
+    for attr in &field.attrs {
+        if attr.path().is_ident("doc") {
+            if let syn::Meta::NameValue(meta) = &attr.meta {
+                if let Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(line),
+                    ..
+                }) = &meta.value
+                {
+                    doc.push(line.value().trim().to_string());
+                }
+            }
+        } else if attr.path().is_ident("option") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("abrev") {
+                    options.abrev = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("name") {
+                    options.name = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("desc") {
+                    options.desc = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("policy") {
+                    options.policy = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("default") {
+                    options.default = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("required") {
+                    options.required = true;
+                } else if meta.path.is_ident("positional") {
+                    options.positional = true;
+                } else {
+                    return Err(meta.error("unknown option attribute"));
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    if options.desc.is_empty() {
+        options.desc = doc.join("\n");
+    }
+
+    Ok(options)
+}
+
+// Determines the kind of a field and the type its values are parsed to
+fn kind(ty: &Type) -> (Kind, &Type) {
+    if let Type::Path(path) = ty {
+        let segment = match path.path.segments.last() {
+            Some(segment) => segment,
+            None => return (Kind::Single, ty),
+        };
+
+        if segment.ident == "bool" {
+            return (Kind::Switch, ty);
+        }
+
+        if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+            if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                if segment.ident == "Option" {
+                    return (Kind::Optional, inner);
+                }
+                if segment.ident == "Vec" {
+                    return (Kind::Many, inner);
+                }
+            }
+        }
+    }
+
+    (Kind::Single, ty)
+}
@@ -2,11 +2,42 @@
 //! Parses and generates configuration from supplied arguments and option specifications
 //! Can also generate usage strings
 
-use std::collections::HashMap;
+use crate::input::{BoxedInput, Input};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Derives Options for a struct, see the Options trait
+#[cfg(feature = "derive")]
+pub use libcli_derive::Options;
+
+mod app;
+mod completion;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "json")]
+mod describe;
+mod help;
+#[cfg(any(feature = "toml", feature = "json"))]
+mod load;
+mod messages;
+mod render;
+
+pub use app::{App, Handler};
+pub use help::{Help, DEFAULT_HELP_TEMPLATE};
+pub use messages::{English, HelpText, Messages};
+
+/// The name of the spec taking the unnamed arguments given before any option, see OptionSpec::new<br>
+/// Prefer OptionSpec::positional to give the arguments a name of your choice, e.g; "FILES"
+pub const UNNAMED: &str = "(unnamed)";
 
 /// Determines how the number of supplied values should match an argument
 /// 0 val indicates a switch like argument
-
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OptionPolicy {
     /// The args's supplied values should match exactly
     Exact(usize),
@@ -14,312 +45,4188 @@ pub enum OptionPolicy {
     AtLeast(usize),
     /// The option's supplied values should be at most n
     AtMost(usize),
+    /// The option's supplied values should be between min and max, inclusive
+    Range(usize, usize),
     /// Finalize will collect all remaining arguments to the value of the option, regardless of if it contains further options
     Finalize(),
-    /// Same as Finalize but will not cause Err on missing required option, useful for overriding options like --help
+    /// Same as Finalize but will not cause Err on missing required option, useful for overriding options like --help<br>
+    /// See OptionSpec::exclusive for an option which also rejects the other options
     FinalizeIgnore(),
 }
 
+impl OptionPolicy {
+    // The least number of values accepted
+    fn min(&self) -> usize {
+        match *self {
+            OptionPolicy::Exact(n) | OptionPolicy::AtLeast(n) | OptionPolicy::Range(n, _) => n,
+            _ => 0,
+        }
+    }
+
+    // The most number of values accepted, None if unbounded
+    fn max(&self) -> Option<usize> {
+        match *self {
+            OptionPolicy::Exact(n) | OptionPolicy::AtMost(n) | OptionPolicy::Range(_, n) => Some(n),
+            _ => None,
+        }
+    }
+
+    // Renders the accepted values as placeholders, e.g; `<VALUE>`, `[VALUE]` or `<VALUE>...`
+    // With required at least one value is shown as needed
+    fn placeholder(&self, name: &str, required: bool) -> String {
+        let min = match required {
+            true => self.min().max(1),
+            false => self.min(),
+        };
+        match (min, self.max()) {
+            (_, Some(0)) => String::new(),
+            (min, Some(max)) if min == max && max <= 3 => {
+                vec![format!("<{}>", name); max].join(" ")
+            }
+            (0, Some(1)) => format!("[{}]", name),
+            (0, _) => format!("[{}]...", name),
+            _ => format!("<{}>...", name),
+        }
+    }
+}
+
+impl std::fmt::Display for OptionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionPolicy::Exact(n) => write!(f, "exactly {}", n),
+            OptionPolicy::AtLeast(n) => write!(f, "at least {}", n),
+            OptionPolicy::AtMost(n) => write!(f, "at most {}", n),
+            OptionPolicy::Range(min, max) => write!(f, "{} to {}", min, max),
+            OptionPolicy::Finalize() | OptionPolicy::FinalizeIgnore() => {
+                write!(f, "all remaining")
+            }
+        }
+    }
+}
+
+/// Determines what happens when an option is given more than once
+pub enum DuplicatePolicy {
+    /// Parsing fails, except for switches which can always be repeated
+    Error,
+    /// The values of the last occurrence replace the earlier ones
+    LastWins,
+    /// The values of every occurrence are appended
+    Append,
+}
+
+/// Determines what happens when a key is given more than once to a key=value option
+pub enum DuplicateKeyPolicy {
+    /// Parsing fails
+    Error,
+    /// The value of the first occurrence is kept
+    FirstWins,
+    /// The value of the last occurrence replaces the earlier ones
+    LastWins,
+}
+
+/// Describes what the values of an option are, letting shell completions suggest them<br>
+/// E.g; a shell completes paths for an option with ValueHint::FilePath
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueHint {
+    /// Nothing is known about the values
+    #[default]
+    Unknown,
+    /// A path to a file or a directory
+    AnyPath,
+    /// A path to a file
+    FilePath,
+    /// A path to a directory
+    DirPath,
+    /// The name of an executable on the PATH
+    CommandName,
+    /// A host name, e.g; from the known hosts
+    Hostname,
+    /// The name of a user on the system
+    Username,
+    /// A URL
+    Url,
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl ValueHint {
+    // The snake case name of the hint in a described or loaded spec
+    fn name(self) -> &'static str {
+        match self {
+            ValueHint::Unknown => "unknown",
+            ValueHint::AnyPath => "any_path",
+            ValueHint::FilePath => "file_path",
+            ValueHint::DirPath => "dir_path",
+            ValueHint::CommandName => "command_name",
+            ValueHint::Hostname => "hostname",
+            ValueHint::Username => "username",
+            ValueHint::Url => "url",
+        }
+    }
+
+    // The hint with the snake case name, None if there is none
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            ValueHint::Unknown,
+            ValueHint::AnyPath,
+            ValueHint::FilePath,
+            ValueHint::DirPath,
+            ValueHint::CommandName,
+            ValueHint::Hostname,
+            ValueHint::Username,
+            ValueHint::Url,
+        ]
+        .iter()
+        .copied()
+        .find(|hint| hint.name() == name)
+    }
+}
+
+/// The reason parsing the arguments failed<br>
+/// The Display implementation gives a message which can be shown to the user<br>
+/// The errors caused by a command line argument carry its index and text, see ParseError::span
+/// ## Example
+/// ```
+/// # use libcli::args::{Config, OptionPolicy, OptionSpec, ParseError};
+/// # let specs = [
+/// #     OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0)),
+/// #     OptionSpec::new('o', "output", "Output file", true, OptionPolicy::Exact(1)),
+/// # ];
+/// match Config::new(&["./test"], &specs) {
+///     Err(ParseError::MissingRequired { name }) => assert_eq!(name, "output"),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The specs contain no positional spec to collect the arguments before any option<br>
+    /// No longer returned, positional arguments given without a positional spec fail with UnexpectedArgument
+    #[deprecated(note = "never returned, match ParseError::UnexpectedArgument instead")]
+    NoUnnamedSpec,
+    /// An option which isn't specified, e.g; `--ouput` or `-x`, with the most similar option name if any
+    UnknownOption {
+        token: String,
+        suggestion: Option<String>,
+        span: Option<ArgSpan>,
+    },
+    /// An abbreviated full name matches several options, see ConfigBuilder::prefix_matching
+    AmbiguousOption {
+        token: String,
+        candidates: Vec<String>,
+        span: Option<ArgSpan>,
+    },
+    /// A positional argument was given but the specs contain no positional spec
+    UnexpectedArgument {
+        token: String,
+        span: Option<ArgSpan>,
+    },
+    /// A value was attached to an option which takes none, e.g; `--no-color=1`
+    UnexpectedValue { name: String, span: Option<ArgSpan> },
+    /// The number of values doesn't match the OptionPolicy of the option
+    WrongValueCount {
+        name: String,
+        count: usize,
+        expected: OptionPolicy,
+        span: Option<ArgSpan>,
+    },
+    /// A value was rejected by the choices, the key=value format, a validator or parsing it to a type
+    InvalidValue {
+        name: String,
+        value: String,
+        reason: String,
+        span: Option<ArgSpan>,
+    },
+    /// An option with DuplicatePolicy::Error was given more than once
+    DuplicateOption { name: String, span: Option<ArgSpan> },
+    /// A key was given more than once to an option with DuplicateKeyPolicy::Error
+    DuplicateKey { name: String, key: String },
+    /// A required option wasn't given
+    MissingRequired { name: String },
+    /// An option wasn't given even though none of the options it is required unless were given
+    RequiredUnless { name: String, unless: Vec<String> },
+    /// An option wasn't given even though an option which makes it required was given
+    RequiredIf { name: String, given: String },
+    /// An option was given without an option it requires
+    MissingDependency { name: String, requires: String },
+    /// An exclusive option was given together with another option or argument
+    ExclusiveOption { name: String, other: String },
+    /// A response file or config file couldn't be read
+    File { path: String, reason: String },
+    /// The specs are inconsistent, see validate_specs
+    InvalidSpec(SpecError),
+    /// Any other failure, e.g; from Options::from_config or Config::deserialize
+    Custom(String),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[allow(deprecated)]
+            ParseError::NoUnnamedSpec => write!(f, "No specification for unnamed arguments found"),
+            ParseError::UnknownOption {
+                token,
+                suggestion: Some(similar),
+                ..
+            } => write!(f, "Invalid option {}, did you mean '--{}'?", token, similar),
+            ParseError::UnknownOption { token, .. } => write!(f, "Invalid option {}", token),
+            ParseError::AmbiguousOption {
+                token, candidates, ..
+            } => {
+                let names: Vec<_> = candidates
+                    .iter()
+                    .map(|candidate| format!("'--{}'", candidate))
+                    .collect();
+                write!(
+                    f,
+                    "Ambiguous option {}, could be {}",
+                    token,
+                    names.join(", ")
+                )
+            }
+            ParseError::UnexpectedArgument { token, .. } => {
+                write!(f, "Unexpected argument '{}'", token)
+            }
+            ParseError::UnexpectedValue { name, .. } => {
+                write!(f, "Option '--{}' takes no values", name)
+            }
+            ParseError::WrongValueCount {
+                name,
+                count,
+                expected,
+                ..
+            } => write!(
+                f,
+                "{} values supplied for {}, expected {}",
+                count,
+                subject(name),
+                expected
+            ),
+            ParseError::InvalidValue {
+                name,
+                value,
+                reason,
+                ..
+            } => write!(
+                f,
+                "Invalid value '{}' for {}: {}",
+                value,
+                subject(name),
+                reason
+            ),
+            ParseError::DuplicateOption { name, .. } => write!(f, "Duplicate option '{}'", name),
+            ParseError::DuplicateKey { name, key } => {
+                write!(f, "Duplicate key '{}' for option '{}'", key, name)
+            }
+            ParseError::MissingRequired { name } => {
+                write!(f, "Missing required {}", subject(name))
+            }
+            ParseError::RequiredUnless { name, unless } => write!(
+                f,
+                "Missing required option '{}', required unless '{}' is given",
+                name,
+                unless.join("' or '")
+            ),
+            ParseError::RequiredIf { name, given } => write!(
+                f,
+                "Missing required option '{}', required when '{}' is given",
+                name, given
+            ),
+            ParseError::MissingDependency { name, requires } => {
+                write!(f, "Option '{}' requires option '{}'", name, requires)
+            }
+            ParseError::ExclusiveOption { name, other } => write!(
+                f,
+                "Option '{}' must be given alone, found '{}'",
+                name, other
+            ),
+            ParseError::File { path, reason } => {
+                write!(f, "Failed to read '{}': {}", path, reason)
+            }
+            ParseError::InvalidSpec(err) => write!(f, "Invalid specification, {}", err),
+            ParseError::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Refers to an option in an error message, the unnamed spec is an implementation detail not shown to users
+fn subject(name: &str) -> String {
+    match name {
+        UNNAMED => String::from("arguments"),
+        name => format!("option '{}'", name),
+    }
+}
+
+impl ParseError {
+    /// Returns the command line argument the error was caused by
+    /// Returns None if the error isn't caused by a single argument, e.g; a missing option or an invalid environment variable
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// # let specs = [
+    /// #     OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0)),
+    /// #     OptionSpec::new('v', "verbose", "Verbose output", false, OptionPolicy::Exact(0)),
+    /// # ];
+    /// let args = ["./test", "-v", "--verbsoe"];
+    /// let err = Config::new(&args, &specs).err().unwrap();
+    /// let span = err.span().unwrap();
+    /// assert_eq!((span.index, span.token.as_str()), (2, "--verbsoe"));
+    ///
+    /// let offset: usize = args[..span.index].iter().map(|arg| arg.len() + 1).sum();
+    /// println!("{}\n{}{}", args.join(" "), " ".repeat(offset), "^".repeat(span.token.len()));
+    /// ```
+    pub fn span(&self) -> Option<&ArgSpan> {
+        match self {
+            ParseError::UnknownOption { span, .. }
+            | ParseError::AmbiguousOption { span, .. }
+            | ParseError::UnexpectedArgument { span, .. }
+            | ParseError::UnexpectedValue { span, .. }
+            | ParseError::WrongValueCount { span, .. }
+            | ParseError::InvalidValue { span, .. }
+            | ParseError::DuplicateOption { span, .. } => span.as_ref(),
+            _ => None,
+        }
+    }
+
+    // Attaches the argument at start unless the error already refers to one
+    // The value rejected by InvalidValue is searched for in the arguments from start, e.g; `-o value`
+    fn locate(mut self, args: &[String], start: usize, offset: usize) -> Self {
+        let found = match &self {
+            ParseError::InvalidValue { value, .. }
+            | ParseError::UnexpectedArgument { token: value, .. } => args
+                .iter()
+                .skip(start)
+                .position(|arg| arg == value)
+                .or_else(|| {
+                    args.iter()
+                        .skip(start)
+                        .position(|arg| arg.contains(value.as_str()))
+                })
+                .map_or(start, |position| start + position),
+            _ => start,
+        };
+
+        let slot = match &mut self {
+            ParseError::UnknownOption { span, .. }
+            | ParseError::AmbiguousOption { span, .. }
+            | ParseError::UnexpectedArgument { span, .. }
+            | ParseError::UnexpectedValue { span, .. }
+            | ParseError::WrongValueCount { span, .. }
+            | ParseError::InvalidValue { span, .. }
+            | ParseError::DuplicateOption { span, .. } => span,
+            _ => return self,
+        };
+        if let (None, Some(token)) = (&slot, args.get(found)) {
+            *slot = Some(ArgSpan {
+                index: offset + found,
+                token: token.clone(),
+            });
+        }
+        self
+    }
+}
+
+/// A command line argument which caused a ParseError
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgSpan {
+    /// The index of the argument, the command is at index 0 and the name of a subcommand at its position
+    /// With response files, the index refers to the arguments after expanding them
+    pub index: usize,
+    /// The argument as given, e.g; `-vx` for the unknown option `-x`
+    pub token: String,
+}
+
+/// A mistake in a list of OptionSpecs, see validate_specs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecError {
+    /// Two specs share a name or an alias
+    DuplicateName { name: String },
+    /// Two specs share an abbreviation
+    DuplicateAbrev { abrev: char },
+    /// A Range policy with a minimum above its maximum
+    InvalidPolicy { name: String, policy: OptionPolicy },
+    /// The number of default values isn't accepted by the policy of the spec
+    InvalidDefaults {
+        name: String,
+        count: usize,
+        policy: OptionPolicy,
+    },
+}
+
+impl std::fmt::Display for SpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpecError::DuplicateName { name } => {
+                write!(f, "the name '{}' is used by more than one option", name)
+            }
+            SpecError::DuplicateAbrev { abrev } => {
+                write!(
+                    f,
+                    "the abbreviation '-{}' is used by more than one option",
+                    abrev
+                )
+            }
+            SpecError::InvalidPolicy { name, policy } => {
+                write!(f, "option '{}' accepts {} values", name, policy)
+            }
+            SpecError::InvalidDefaults {
+                name,
+                count,
+                policy,
+            } => write!(
+                f,
+                "option '{}' has {} default values, expected {}",
+                name, count, policy
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// Checks a list of specs for mistakes, e.g; two options with the same name or abbreviation<br>
+/// The later spec would otherwise silently shadow the earlier one<br>
+/// Parsing calls this for the specs of every command and fails with ParseError::InvalidSpec
+/// ## Example
+/// ```
+/// # use libcli::args::{self, OptionPolicy, OptionSpec, SpecError};
+/// let specs = [
+///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+///     OptionSpec::new('o', "optimize", "Optimizes the output", false, OptionPolicy::Exact(0)),
+/// ];
+///
+/// assert_eq!(args::validate_specs(&specs), Err(SpecError::DuplicateAbrev { abrev: 'o' }));
+/// ```
+pub fn validate_specs(specs: &[OptionSpec]) -> Result<(), SpecError> {
+    let mut names = HashSet::new();
+    let mut abrevs = HashSet::new();
+
+    for spec in specs {
+        for name in std::iter::once(&spec.name).chain(spec.aliases.iter().map(|(alias, _)| alias)) {
+            if !names.insert(name.as_ref()) {
+                return Err(SpecError::DuplicateName {
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        if let Some(abrev) = spec.abrev.filter(|abrev| !abrevs.insert(*abrev)) {
+            return Err(SpecError::DuplicateAbrev { abrev });
+        }
+
+        if let OptionPolicy::Range(min, max) = spec.policy {
+            if min > max {
+                return Err(SpecError::InvalidPolicy {
+                    name: spec.name.to_string(),
+                    policy: spec.policy,
+                });
+            }
+        }
+
+        let count = spec.defaults.len();
+        if count > 0 && (count < spec.policy.min() || !spec.accepts(count)) {
+            return Err(SpecError::InvalidDefaults {
+                name: spec.name.to_string(),
+                count,
+                policy: spec.policy,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// The environment variable an option is mapped to with a prefix, e.g; `MYAPP_DRY_RUN` for `dry-run`
+fn env_var(prefix: &str, name: &str) -> String {
+    format!("{}_{}", prefix, name.to_uppercase().replace('-', "_"))
+}
+
+// Collects the variables read by the options of the commands and their subcommands
+// An option with its own variable, see OptionSpec::env, isn't read from the prefixed one
+fn known_env_vars(
+    prefix: &str,
+    specs: &[OptionSpec],
+    commands: &[Command],
+    known: &mut HashSet<String>,
+) {
+    known.extend(specs.iter().filter_map(|spec| match &spec.env {
+        Some(var) => Some(var.to_string()),
+        None if !spec.positional => Some(env_var(prefix, &spec.name)),
+        None => None,
+    }));
+    for cmd in commands {
+        known_env_vars(prefix, &cmd.specs, &cmd.subcommands, known);
+    }
+}
+
+// Validates the specs of the commands and their subcommands
+fn validate_commands(specs: &[OptionSpec], commands: &[Command]) -> Result<(), SpecError> {
+    validate_specs(specs)?;
+    commands
+        .iter()
+        .try_for_each(|cmd| validate_commands(&cmd.specs, &cmd.subcommands))
+}
+
 /// Specifies an option that can be given in the command line<br>
 /// Later supplied to config::Config::new()<br>
-/// abrev: The abreviation of name, e.g; 'r' or 'c'
-/// name: The name/trigger for the option, e.g; "recursive" or "clean", an a name of "(unnamed)" specifies the first unnamed arguments before any option is given<br>
+/// abrev: The abreviation of name, e.g; 'r' or 'c', '\0' for an option without abbreviation, see also OptionSpec::long
+/// name: The name/trigger for the option, e.g; "recursive" or "clean", a name of "(unnamed)", see UNNAMED, specifies the first unnamed arguments before any option is given, see also OptionSpec::positional<br>
 /// desc: a short description printed with --help<br>
 /// required: specifies if the option is required or optional<br>
 /// policy: an enum containing the number of values and how they're enforced<br>
+/// The names and descriptions are either `&'static str` or `String`, allowing specs to be built at runtime, e.g; from a list of plugins<br>
 /// ## Example
 /// ```
 /// let spec = libcli::args::OptionSpec::new('r', "recursive", "Reads all files recursively in a directory", false, libcli::args::OptionPolicy::Exact(0));
 /// ```
 pub struct OptionSpec {
-    abrev: char,
-    name: &'static str,
-    desc: &'static str,
+    abrev: Option<char>,
+    name: Cow<'static, str>,
+    long: bool,
+    positional: bool,
+    trailing: bool,
+    desc: Cow<'static, str>,
     required: bool,
     policy: OptionPolicy,
+    aliases: Vec<(Cow<'static, str>, bool)>,
+    duplicates: DuplicatePolicy,
+    choices: Vec<Cow<'static, str>>,
+    defaults: Vec<Cow<'static, str>>,
+    requires: Vec<Cow<'static, str>>,
+    required_unless: Vec<Cow<'static, str>>,
+    required_if: Vec<Cow<'static, str>>,
+    env: Option<Cow<'static, str>>,
+    validators: Vec<Validator>,
+    callbacks: Vec<RefCell<Callback>>,
+    negatable: bool,
+    hidden: bool,
+    deprecated: Option<Cow<'static, str>>,
+    global: bool,
+    exclusive: bool,
+    hyphen_values: bool,
+    delimiter: Option<char>,
+    key_value: Option<DuplicateKeyPolicy>,
+    missing: Vec<Cow<'static, str>>,
+    section: Option<Cow<'static, str>>,
+    value_hint: ValueHint,
+    value_name: Option<Cow<'static, str>>,
+    long_desc: Option<Cow<'static, str>>,
+    display_order: Option<usize>,
+}
+
+/// A function checking a single value of an option
+/// Returns Err(reason) if the value isn't accepted
+pub type Validator = Box<dyn Fn(&str) -> Result<(), String>>;
+
+/// A function called with the values of an option when it is parsed, see OptionSpec::on_parse
+pub type Callback = Box<dyn FnMut(&[String])>;
+
+/// A validator accepting a path to an existing file, see OptionSpec::with_validator
+/// ## Example
+/// ```
+/// # use libcli::args::{self, OptionPolicy, OptionSpec};
+/// let spec = OptionSpec::new('c', "config", "Config file", false, OptionPolicy::Exact(1))
+///     .with_validator(args::exists_file());
+/// ```
+pub fn exists_file() -> impl Fn(&str) -> Result<(), String> {
+    |value| match Path::new(value).metadata() {
+        Ok(metadata) if metadata.is_file() => Ok(()),
+        Ok(_) => Err(format!("'{}' is not a file", value)),
+        Err(e) => Err(format!("file '{}' can't be accessed, {}", value, e)),
+    }
+}
+
+/// A validator accepting a path to an existing directory, see OptionSpec::with_validator
+pub fn exists_dir() -> impl Fn(&str) -> Result<(), String> {
+    |value| match Path::new(value).metadata() {
+        Ok(metadata) if metadata.is_dir() => Ok(()),
+        Ok(_) => Err(format!("'{}' is not a directory", value)),
+        Err(e) => Err(format!("directory '{}' can't be accessed, {}", value, e)),
+    }
+}
+
+/// A validator accepting a path a file can be created at, e.g; an output file<br>
+/// The parent directory has to exist and the path can't be an existing directory
+pub fn creatable_path() -> impl Fn(&str) -> Result<(), String> {
+    |value| {
+        let path = Path::new(value);
+        if path.is_dir() {
+            return Err(format!("'{}' is a directory", value));
+        }
+
+        match path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            Some(parent) if !parent.is_dir() => {
+                Err(format!("directory '{}' does not exist", parent.display()))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl OptionSpec {
     pub fn new(
         abrev: char,
-        name: &'static str,
-        desc: &'static str,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
         required: bool,
         policy: OptionPolicy,
     ) -> Self {
+        let name = name.into();
         Self {
-            abrev,
+            abrev: if abrev == '\0' { None } else { Some(abrev) },
+            long: name != UNNAMED,
+            positional: name == UNNAMED,
             name,
-            desc,
+            trailing: false,
+            desc: desc.into(),
             required,
             policy,
+            aliases: Vec::new(),
+            duplicates: DuplicatePolicy::Error,
+            choices: Vec::new(),
+            defaults: Vec::new(),
+            requires: Vec::new(),
+            required_unless: Vec::new(),
+            required_if: Vec::new(),
+            env: None,
+            validators: Vec::new(),
+            callbacks: Vec::new(),
+            negatable: false,
+            hidden: false,
+            deprecated: None,
+            global: false,
+            exclusive: false,
+            hyphen_values: false,
+            delimiter: None,
+            key_value: None,
+            missing: Vec::new(),
+            section: None,
+            value_hint: ValueHint::Unknown,
+            value_name: None,
+            long_desc: None,
+            display_order: None,
         }
     }
 
-    // Consumes and checks supplied values with the option policy
-    // Returns Ok(values) on success
-    // Returns Err(reason) on failure
-    fn enforce(&self, values: Vec<String>) -> Result<Vec<String>, String> {
-        match self.policy {
-            OptionPolicy::Exact(n) => {
-                if values.len() != n {
-                    return Err(format!(
-                        "{} values supplied for option '{}', expected exactly {}",
-                        values.len(),
-                        self.name,
-                        n,
-                    ));
-                };
-                Ok(values)
-            }
-            OptionPolicy::AtLeast(n) => {
-                if values.len() < n {
-                    return Err(format!(
-                        "{} values supplied for option '{}', expected at least {}",
-                        values.len(),
-                        self.name,
-                        n,
-                    ));
-                };
-                Ok(values)
-            }
-            OptionPolicy::AtMost(n) => {
-                if values.len() > n {
-                    return Err(format!(
-                        "{} values supplied for option '{}', expected at most {}",
-                        values.len(),
-                        self.name,
-                        n,
-                    ));
-                };
-                Ok(values)
-            }
-            OptionPolicy::Finalize() => Ok(values),
-            OptionPolicy::FinalizeIgnore() => Ok(values),
-        }
+    /// Makes the option depend on another option, parsing fails if this option is given without the other<br>
+    /// Can be called several times to depend on multiple options<br>
+    /// The dependencies are shown in the generated usage
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('z', "compress", "Compress the output", false, OptionPolicy::Exact(0))
+    ///     .requires("output");
+    /// ```
+    pub fn requires(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.requires.push(name.into());
+        self
     }
-}
 
-impl std::fmt::Display for OptionSpec {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "    -{}, --{} {}\n        {}\n\n",
-            self.abrev,
-            self.name,
-            if self.required { "[required]" } else { "" },
-            indent(self.desc, 8, ' '),
-        )
+    /// Makes the option required unless another option is given<br>
+    /// Can be called several times, the option is then required unless any of them is given
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1))
+    ///     .required_unless("config");
+    /// ```
+    pub fn required_unless(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.required_unless.push(name.into());
+        self
     }
-}
 
-// Indents every line in passed string, not just the first and returns the result
-// Not including the first line
-fn indent(string: &str, depth: usize, indent_ch: char) -> String {
-    let mut result = String::with_capacity(string.len());
-    let mut buf = String::with_capacity(256);
-    let mut indent: [u8; 4] = [b'\0'; 4];
-    let indent = indent_ch.encode_utf8(&mut indent).repeat(depth);
-    for ch in string.chars() {
-        buf.push(ch);
-        if ch == '\n' {
-            buf.push_str(&indent);
-        }
+    /// Makes the option required if another option is given<br>
+    /// Can be called several times, the option is then required if any of them is given
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('u', "url", "Url of the remote", false, OptionPolicy::Exact(1))
+    ///     .required_if("remote");
+    /// ```
+    pub fn required_if(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.required_if.push(name.into());
+        self
+    }
 
-        if buf.len() > buf.capacity() - 2 {
-            result.push_str(&buf);
-            buf.clear();
-        }
+    /// Sets an environment variable which is read when the option isn't supplied on the command line<br>
+    /// The precedence is command line, environment variable and last default values<br>
+    /// The variable is taken as a single value, for a switch it enables the option unless empty, "0" or "false"<br>
+    /// The variable is shown in the generated usage
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1))
+    ///     .env("MYAPP_OUTPUT");
+    /// ```
+    pub fn env(mut self, var: impl Into<Cow<'static, str>>) -> Self {
+        self.env = Some(var.into());
+        self
     }
-    result.push_str(&buf);
-    buf.clear();
-    result
-}
 
-/// Specifies a configuration of parsed arguments
-/// Each option which was given as a spec can be accessed by option(name)
-/// This returns a Option<Vec<String>> containing the values of the option (if any)
-pub struct Config {
-    command: String,
-    parsed: HashMap<&'static str, Vec<String>>,
-}
+    /// Sets the values used when the option isn't supplied<br>
+    /// A required option with default values is always satisfied<br>
+    /// The default values are shown in the generated usage
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('c', "color", "When to use colors", false, OptionPolicy::Exact(1))
+    ///     .default_values(&["auto"]);
+    /// ```
+    pub fn default_values<S>(mut self, defaults: &[S]) -> Self
+    where
+        S: Clone + Into<Cow<'static, str>>,
+    {
+        self.defaults = defaults.iter().cloned().map(Into::into).collect();
+        self
+    }
 
-/// Parses and generates configuration from supplied arguments and option specifications
-/// Can also generate usage strings
-impl Config {
-    /// Same as Config::new but uses the arguments passed to the program (env::args)
-    /// The program path, first argument, is included in the unnamed args<br>
-    /// Note, the spec isn't stored with config<br>
-    pub fn new_env(specs: &[OptionSpec]) -> Result<Config, String> {
-        Config::parse(std::env::args(), specs)
+    /// Sets the values used when the option is given on the command line without any values<br>
+    /// Useful with a policy like AtMost(1), e.g; `--color` meaning `--color auto`<br>
+    /// The values are shown in the generated usage
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('c', "color", "When to use colors", false, OptionPolicy::AtMost(1))
+    ///     .default_missing_values(&["auto"])
+    ///     .default_values(&["never"]);
+    /// ```
+    pub fn default_missing_values<S>(mut self, missing: &[S]) -> Self
+    where
+        S: Clone + Into<Cow<'static, str>>,
+    {
+        self.missing = missing.iter().cloned().map(Into::into).collect();
+        self
     }
-    /// Parses config from custom supplied arguments<br>
-    /// Specs is a list containing specifications for the available options a use can supply<br>
-    /// Returns Err(msg) if a spec doesn't match what is specified<br>
-    /// The arguments before any option are specified with the (unnamed)<br>
-    /// The values for the options can be accessed with the option(name) method<br>
-    /// Parsing will fail if an option with policy other than Exact(0) is used twice
-    /// Note, the spec isn't stored with config<br>
-    pub fn new(args: &[&str], specs: &[OptionSpec]) -> Result<Config, String> {
-        Config::parse(args.iter().map(|arg| arg.to_string()), specs)
+
+    /// Creates an option which can only be given by its full name, e.g; `--dry-run`
+    pub fn long(
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        required: bool,
+        policy: OptionPolicy,
+    ) -> Self {
+        Self::new('\0', name, desc, required, policy)
     }
 
-    /// Generates a usage string from supplied specs
-    // Through a combination of list_required and list_unrequired you can configure it to only show required options and vice versa
-    pub fn generate_usage(
-        specs: &[OptionSpec],
-        list_required: bool,
-        list_unrequired: bool,
-    ) -> String {
-        let mut required_string = String::new();
-        let mut unrequired_string = String::new();
-        if list_required {
-            required_string = specs
-                .iter()
-                .filter(|spec| spec.required)
-                .map(|spec| spec.to_string())
-                .collect();
+    /// Creates an option which can only be given by its abbreviation, e.g; `-x`<br>
+    /// The name is not accepted on the command line and is only used to access the values with Config::option and in messages
+    pub fn short(
+        abrev: char,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        required: bool,
+        policy: OptionPolicy,
+    ) -> Self {
+        Self {
+            long: false,
+            ..Self::new(abrev, name, desc, required, policy)
         }
-        if list_unrequired {
-            unrequired_string = specs
-                .iter()
-                .filter(|spec| !spec.required)
-                .map(|spec| spec.to_string())
-                .collect();
+    }
+
+    /// Creates a named positional argument, e.g; `SRC` and `DEST` in `cp SRC DEST`<br>
+    /// The arguments given before any option are distributed over the positional specs in the order they are specified<br>
+    /// Each takes as many values as its policy allows while leaving enough for the minimum of the following ones<br>
+    /// The values are accessed with the name through Config::option
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::positional("SRC", "Files to copy", true, OptionPolicy::AtLeast(1)),
+    ///     OptionSpec::positional("DEST", "Destination", true, OptionPolicy::Exact(1)),
+    /// ];
+    /// ```
+    pub fn positional(
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        required: bool,
+        policy: OptionPolicy,
+    ) -> Self {
+        Self {
+            abrev: None,
+            long: false,
+            positional: true,
+            ..Self::new('\0', name, desc, required, policy)
         }
+    }
 
-        return required_string + &unrequired_string;
+    /// Creates a spec capturing all arguments after a `--` verbatim, e.g; `mytool run -- cmd --flags`<br>
+    /// The captured arguments aren't interpreted as options and only need to be at least min in count<br>
+    /// The values are accessed with the name through Config::option
+    /// ## Example
+    /// ```
+    /// # use libcli::args::OptionSpec;
+    /// let spec = OptionSpec::trailing("COMMAND", "Command to run", 1);
+    /// ```
+    pub fn trailing(
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        min: usize,
+    ) -> Self {
+        Self {
+            abrev: None,
+            long: false,
+            trailing: true,
+            ..Self::new('\0', name, desc, false, OptionPolicy::AtLeast(min))
+        }
     }
 
-    // Parses config from passed iterator
-    fn parse<'a>(
-        mut args: impl Iterator<Item = String>,
-        specs: &[OptionSpec],
-    ) -> Result<Config, String> {
-        let command = args.next().unwrap_or_else(|| String::new());
-        // For quickly locating options
-        let name_map: HashMap<&str, &OptionSpec> =
-            specs.iter().map(|spec| (spec.name, spec)).collect();
+    /// Adds an alternative name for the option, e.g; "colour" for "color"<br>
+    /// The values given with an alias are accessed with the name of the option<br>
+    /// The alias isn't shown in the generated usage, see visible_alias()
+    pub fn alias(mut self, alias: impl Into<Cow<'static, str>>) -> Self {
+        self.aliases.push((alias.into(), false));
+        self
+    }
 
-        let abrev_map: HashMap<char, &OptionSpec> =
-            specs.iter().map(|spec| (spec.abrev, spec)).collect();
+    /// Same as alias() but the alias is shown in the generated usage
+    pub fn visible_alias(mut self, alias: impl Into<Cow<'static, str>>) -> Self {
+        self.aliases.push((alias.into(), true));
+        self
+    }
 
-        let mut parsed: HashMap<&'static str, Vec<String>> = HashMap::new();
+    /// Sets how repeated occurrences of the option are handled, the default is DuplicatePolicy::Error<br>
+    /// The policy of the option is enforced for every occurrence
+    pub fn duplicate_policy(mut self, duplicates: DuplicatePolicy) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
 
-        // Tries to find a spec with an empty name, the unnamed spec
-        // If some it will go by that ruling
-        // If none, it will accept as many unnamed args as there are
-        let mut current_spec: &OptionSpec = match name_map.get("(unnamed)") {
-            Some(v) => v,
-            None => return Err("No specification for unnamed arguments found".to_string()),
-        };
+    /// Restricts the values of the option to a set of allowed values<br>
+    /// Parsing fails if a supplied value isn't one of the choices<br>
+    /// The possible values are listed in the generated usage
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('f', "format", "Output format", false, OptionPolicy::Exact(1))
+    ///     .choices(&["json", "yaml", "text"]);
+    /// ```
+    pub fn choices<S>(mut self, choices: &[S]) -> Self
+    where
+        S: Clone + Into<Cow<'static, str>>,
+    {
+        self.choices = choices.iter().cloned().map(Into::into).collect();
+        self
+    }
 
-        let mut values = Vec::new();
-        let mut in_finalize = false;
-        for arg in args {
-            // New option
-            if !in_finalize && arg.starts_with("-") {
-                // If Final or FinalIgnore
-                match current_spec.policy {
-                    OptionPolicy::Finalize() | OptionPolicy::FinalizeIgnore() => {
-                        in_finalize = true;
-                        values.push(arg);
-                        continue;
-                    }
-                    _ => (),
-                }
+    /// Adds a validator which is run for every supplied value after the policy has been enforced<br>
+    /// Parsing fails with the returned message and the option name if a value is rejected<br>
+    /// Several validators can be added and are run in order
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('p', "port", "Port to listen on", false, OptionPolicy::Exact(1))
+    ///     .with_validator(|v| match v.parse::<u16>() {
+    ///         Ok(port) if port >= 1024 => Ok(()),
+    ///         _ => Err("expected a port number above 1023".to_string()),
+    ///     });
+    /// ```
+    pub fn with_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + 'static,
+    {
+        self.validators.push(Box::new(validator));
+        self
+    }
 
-                // Collect the last option values
-                values = current_spec.enforce(values)?;
+    /// Only accepts values matching the regular expression pattern, e.g; `^\d{4}-\d{2}-\d{2}$` for a date<br>
+    /// Parsing fails showing the pattern if a value doesn't match<br>
+    /// Requires the `regex` feature, panics if pattern isn't a valid regular expression
+    /// ## Example
+    /// ```
+    /// # #[cfg(feature = "regex")]
+    /// # {
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('d', "date", "Date of the report", false, OptionPolicy::Exact(1))
+    ///     .matches(r"^\d{4}-\d{2}-\d{2}$");
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn matches(self, pattern: &str) -> Self {
+        let regex = regex::Regex::new(pattern)
+            .unwrap_or_else(|e| panic!("Invalid pattern for option '{}': {}", self.name, e));
+        self.with_validator(move |value| match regex.is_match(value) {
+            true => Ok(()),
+            false => Err(format!("expected a value matching '{}'", regex.as_str())),
+        })
+    }
 
-                Self::insert_non_duplicate(&mut parsed, current_spec, values)?;
+    /// Only accepts integer values within range, e.g; `1..=65535`<br>
+    /// Parsing fails naming the allowed range if a value isn't an integer or is out of range
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('p', "port", "Port to listen on", false, OptionPolicy::Exact(1))
+    ///     .range(1..=65535);
+    /// ```
+    pub fn range<R>(self, range: R) -> Self
+    where
+        R: std::ops::RangeBounds<i64> + std::fmt::Debug + 'static,
+    {
+        self.with_validator(move |value| match value.parse::<i64>() {
+            Ok(number) if range.contains(&number) => Ok(()),
+            Ok(_) => Err(format!("expected a number in the range {:?}", range)),
+            Err(_) => Err(format!("expected an integer in the range {:?}", range)),
+        })
+    }
 
-                values = Vec::new();
+    /// Adds a callback which is called with the values of the option as soon as it is parsed, before the following arguments<br>
+    /// It is called for every occurrence on the command line and for a value from the environment or a config file, but not for default values<br>
+    /// The callback may already have been called when parsing fails on a later argument
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('v', "verbose", "Shows verbose output", false, OptionPolicy::Exact(0))
+    ///     .on_parse(|_| println!("Verbose output enabled"));
+    /// ```
+    pub fn on_parse<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&[String]) + 'static,
+    {
+        self.callbacks.push(RefCell::new(Box::new(callback)));
+        self
+    }
 
-                // Single full name argument
-                if arg.starts_with("--") {
-                    current_spec = match name_map.get(&arg[2..]) {
-                        Some(spec) => {
-                            if let Some(_) = parsed.get(spec.name) {
-                                return Err(format!("Duplicate option '{}'", spec.name));
-                            }
-                            spec
-                        }
-                        None => return Err(format!("Invalid option {}", arg)),
-                    };
-                }
-                // One or more abbreviated options
-                else {
-                    let options: Vec<_> = arg.chars().skip(1).collect();
+    /// Gives a switch a `--no-<name>` counterpart which clears it<br>
+    /// The last of the two given wins, and a cleared switch isn't read from the environment, config file or default values<br>
+    /// Only applies to full name switches, i.e; options with policy Exact(0)
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('c', "color", "Colorize the output", false, OptionPolicy::Exact(0))
+    ///     .env("MYAPP_COLOR")
+    ///     .negatable();
+    /// ```
+    pub fn negatable(mut self) -> Self {
+        self.negatable = true;
+        self
+    }
 
-                    // The values after a group of abbreviated options refer to the last option
-                    for (index, option) in options.iter().enumerate() {
-                        let spec = match abrev_map.get(&option) {
-                            Some(spec) => spec,
-                            None => return Err(format!("Invalid abbreviated option '{}'", option)),
-                        };
+    /// Leaves the option out of the generated usage while still parsing it, e.g; for internal or debug options
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('\0', "dump-tokens", "Prints the lexed tokens", false, OptionPolicy::Exact(0))
+    ///     .hidden();
+    /// ```
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
 
-                        // The last option is set to collect the values following
-                        if index == options.len() - 1 {
-                            current_spec = spec;
-                            break;
-                        }
+    /// Marks the option as deprecated, it is still parsed but a warning containing note is recorded when it is given<br>
+    /// The warnings are accessed with Config::warnings, the note is shown in the generated usage
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('\0', "out", "Output file", false, OptionPolicy::Exact(1))
+    ///     .deprecated("use --output instead");
+    /// ```
+    pub fn deprecated(mut self, note: impl Into<Cow<'static, str>>) -> Self {
+        self.deprecated = Some(note.into());
+        self
+    }
 
-                        if let Some(_) = parsed.get(spec.name) {
-                            return Err(format!("Duplicate option '{}'", spec.name));
-                        }
-                        Self::insert_non_duplicate(&mut parsed, spec, vec![])?;
-                    }
-                }
-                continue;
-            }
-            values.push(arg);
-        }
+    /// Makes the option global, it is then also accepted after the name of a subcommand, e.g; `tool build --verbose`<br>
+    /// The values are visible on the config where the option is specified as well as on the configs of its subcommands<br>
+    /// If given both before and after a subcommand, the values given after take precedence<br>
+    /// The environment variable, config file and default values are read on the config where the option is specified
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('v', "verbose", "Prints more information", false, OptionPolicy::Exact(0))
+    ///     .global();
+    /// ```
+    pub fn global(mut self) -> Self {
+        self.global = true;
+        self
+    }
 
-        // Collect what remains
-        values = current_spec.enforce(values)?;
+    /// Makes the option exclusive, it has to be the only option or argument given to its command, e.g; `myapp --init`<br>
+    /// When given, missing required options aren't an error, and any other option, positional argument or subcommand on the command line is<br>
+    /// Values from the environment, a config file or default values don't count as given
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec, ParseError};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", true, OptionPolicy::AtLeast(1)),
+    ///     OptionSpec::new('\0', "init", "Creates a config file", false, OptionPolicy::Exact(0))
+    ///         .exclusive(),
+    ///     OptionSpec::new('v', "verbose", "Prints more information", false, OptionPolicy::Exact(0)),
+    /// ];
+    ///
+    /// assert!(Config::new(&["./test", "--init"], &specs).is_ok());
+    /// assert_eq!(
+    ///     Config::new(&["./test", "--init", "-v"], &specs).err(),
+    ///     Some(ParseError::ExclusiveOption { name: "init".into(), other: "verbose".into() })
+    /// );
+    /// ```
+    pub fn exclusive(mut self) -> Self {
+        self.exclusive = true;
+        self
+    }
 
-        Self::insert_non_duplicate(&mut parsed, current_spec, values)?;
+    /// Accepts values starting with '-', e.g; `--offset -5`<br>
+    /// While the option accepts more values according to its OptionPolicy, the following arguments are taken as its values even if they look like options<br>
+    /// An option with an unbounded policy therefore takes all the remaining arguments
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('\0', "offset", "Offset to apply", false, OptionPolicy::Exact(1))
+    ///         .allow_hyphen_values(),
+    /// ];
+    ///
+    /// let config = Config::new(&["./test", "--offset", "-5"], &specs).unwrap();
+    /// assert_eq!(config.single("offset"), Some("-5"));
+    /// ```
+    pub fn allow_hyphen_values(mut self) -> Self {
+        self.hyphen_values = true;
+        self
+    }
+
+    /// Splits each supplied value at delimiter, e.g; `--features a,b,c` gives three values<br>
+    /// The split values are counted individually against the OptionPolicy
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('F', "features", "Features to enable", false, OptionPolicy::AtLeast(1))
+    ///     .value_delimiter(',');
+    /// ```
+    pub fn value_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    /// Makes every value of the option a `key=value` pair, e.g; `-D mode=release -D jobs=4`<br>
+    /// Parsing fails if a value doesn't contain '=', duplicates decides what happens when a key is repeated<br>
+    /// The pairs are accessed with Config::map
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{DuplicateKeyPolicy, DuplicatePolicy, OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('D', "define", "Defines a variable", false, OptionPolicy::Exact(1))
+    ///     .duplicate_policy(DuplicatePolicy::Append)
+    ///     .key_value(DuplicateKeyPolicy::LastWins);
+    /// ```
+    pub fn key_value(mut self, duplicates: DuplicateKeyPolicy) -> Self {
+        self.key_value = Some(duplicates);
+        self
+    }
+
+    /// Lists the option under a titled section in the generated usage, e.g; "Input" or "Logging"<br>
+    /// The sections are listed in the order they first appear in the specs, after the options without a section
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('q', "quiet", "Suppresses all output", false, OptionPolicy::Exact(0))
+    ///     .section("Logging");
+    /// ```
+    pub fn section(mut self, section: impl Into<Cow<'static, str>>) -> Self {
+        self.section = Some(section.into());
+        self
+    }
+
+    /// Describes the values of the option for shell completions, e.g; ValueHint::FilePath to complete paths<br>
+    /// The hint doesn't affect parsing, use a validator like exists_file to check the values
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec, ValueHint};
+    /// let spec = OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1))
+    ///     .value_hint(ValueHint::FilePath);
+    /// ```
+    pub fn value_hint(mut self, hint: ValueHint) -> Self {
+        self.value_hint = hint;
+        self
+    }
+
+    /// Sets a longer description shown instead of the description in the long help, e.g; with `--help` rather than `-h`<br>
+    /// See Help::long
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('j', "jobs", "Number of jobs", false, OptionPolicy::Exact(1))
+    ///     .long_desc("Number of jobs to run in parallel\nDefaults to the number of CPUs");
+    /// ```
+    pub fn long_desc(mut self, long_desc: impl Into<Cow<'static, str>>) -> Self {
+        self.long_desc = Some(long_desc.into());
+        self
+    }
+
+    /// Sets the position of the option in the generated usage, the options with a key are listed first in ascending order<br>
+    /// The order of the other options is given by the UsageOrder, see Help::order
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{OptionPolicy, OptionSpec};
+    /// let spec = OptionSpec::new('v', "verbose", "Shows verbose output", false, OptionPolicy::Exact(0))
+    ///     .display_order(0);
+    /// ```
+    pub fn display_order(mut self, key: usize) -> Self {
+        self.display_order = Some(key);
+        self
+    }
+
+    /// Sets the placeholder of the values in the generated usage and synopsis, e.g; `-o, --output <FILE>`<br>
+    /// The placeholder is repeated or marked optional and repeating as the OptionPolicy allows, e.g; `[FILE]...`<br>
+    /// Defaults to `VALUE`
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [OptionSpec::new('o', "output", "Output file", true, OptionPolicy::Exact(1))
+    ///     .value_name("FILE")];
+    /// assert!(Config::generate_usage(&specs, true, true).starts_with("    -o, --output <FILE> [required]"));
+    /// ```
+    pub fn value_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.value_name = Some(name.into());
+        self
+    }
+
+    /// Returns the hint set with OptionSpec::value_hint, ValueHint::Unknown by default
+    pub fn get_value_hint(&self) -> ValueHint {
+        self.value_hint
+    }
+
+    /// Returns the full name of the option
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the description of the option
+    pub fn desc(&self) -> &str {
+        &self.desc
+    }
+
+    /// Renders the usage entry of the Display implementation with the labels of messages
+    pub fn usage_with(&self, messages: &dyn Messages) -> String {
+        self.usage_entry(false, messages)
+    }
+
+    // Calls the callbacks added with on_parse
+    fn notify(&self, values: &[String]) {
+        for callback in &self.callbacks {
+            (callback.borrow_mut())(values);
+        }
+    }
+
+    // Returns true if the option is a switch which doesn't take any values
+    fn takes_no_values(&self) -> bool {
+        matches!(self.policy, OptionPolicy::Exact(0))
+    }
+
+    // Returns true if the policy allows the option to have count values
+    fn accepts(&self, count: usize) -> bool {
+        self.policy.max().map_or(true, |max| count <= max)
+    }
+
+    // Reads the values of the option from an environment variable
+    // Returns None if the variable isn't set or disables a switch
+    fn env_values(&self, var: &str) -> Option<Vec<String>> {
+        let value = std::env::var(var).ok()?;
+        if !self.takes_no_values() {
+            return Some(vec![value]);
+        }
+
+        match value.as_str() {
+            "" | "0" | "false" => None,
+            _ => Some(Vec::new()),
+        }
+    }
+
+    // Splits the values of a key=value option into a map, handling repeated keys according to the DuplicateKeyPolicy
+    fn key_values(&self, values: &[String]) -> Result<HashMap<String, String>, ParseError> {
+        let mut map = HashMap::new();
+        for (key, value) in values.iter().filter_map(|value| value.split_once('=')) {
+            match (map.contains_key(key), &self.key_value) {
+                (true, Some(DuplicateKeyPolicy::Error)) => {
+                    return Err(ParseError::DuplicateKey {
+                        name: self.name.to_string(),
+                        key: key.to_string(),
+                    })
+                }
+                (true, Some(DuplicateKeyPolicy::FirstWins)) => (),
+                _ => {
+                    map.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    // Consumes and checks supplied values with the option policy and validators
+    // Returns Ok(values) on success
+    // Returns Err(reason) on failure
+    fn enforce(&self, values: Vec<String>) -> Result<Vec<String>, ParseError> {
+        let values = match self.delimiter {
+            Some(delimiter) => values
+                .iter()
+                .flat_map(|value| value.split(delimiter))
+                .map(|value| value.to_string())
+                .collect(),
+            None => values,
+        };
+
+        let accepted = match self.policy {
+            OptionPolicy::Exact(n) => values.len() == n,
+            OptionPolicy::AtLeast(n) => values.len() >= n,
+            OptionPolicy::AtMost(n) => values.len() <= n,
+            OptionPolicy::Range(min, max) => values.len() >= min && values.len() <= max,
+            OptionPolicy::Finalize() | OptionPolicy::FinalizeIgnore() => true,
+        };
+        if !accepted {
+            return Err(ParseError::WrongValueCount {
+                name: self.name.to_string(),
+                count: values.len(),
+                expected: self.policy,
+                span: None,
+            });
+        }
+
+        let invalid = |value: &str, reason: String| ParseError::InvalidValue {
+            name: self.name.to_string(),
+            value: value.to_string(),
+            reason,
+            span: None,
+        };
+
+        for value in &values {
+            if self.key_value.is_some() && !value.contains('=') {
+                return Err(invalid(value, "expected key=value".to_string()));
+            }
+
+            if !self.choices.is_empty() && !self.choices.iter().any(|choice| choice == value) {
+                return Err(invalid(
+                    value,
+                    format!("possible values are: {}", self.choices.join(", ")),
+                ));
+            }
+
+            for validator in &self.validators {
+                validator(value).map_err(|e| invalid(value, e))?;
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+impl OptionSpec {
+    // The names listed in the usage, the missing form is left out, aligning the full names
+    fn usage_names(&self) -> String {
+        let aliases: String = self
+            .aliases
+            .iter()
+            .filter(|(_, visible)| *visible)
+            .map(|(alias, _)| format!(", --{}", alias))
+            .collect();
+
+        let name = if self.negatable && self.takes_no_values() {
+            format!("[no-]{}", self.name)
+        } else {
+            self.name.to_string()
+        };
+
+        let names = match (self.abrev, self.long) {
+            _ if self.positional => return self.name.to_string(),
+            _ if self.trailing => return format!("-- {}...", self.name),
+            (Some(abrev), true) => format!("-{}, --{}{}", abrev, name, aliases),
+            (Some(abrev), false) => format!("-{}", abrev),
+            (None, _) => format!("    --{}{}", name, aliases),
+        };
+
+        // Options stopping the parsing, like --help, are shown without values unless named
+        match self.policy {
+            OptionPolicy::FinalizeIgnore() if self.value_name.is_none() => names,
+            _ if self.takes_no_values() => names,
+            _ => format!("{} {}", names, self.value_placeholder(false)),
+        }
+    }
+
+    // The values rendered as placeholders by the policy, see OptionSpec::value_name
+    fn value_placeholder(&self, required: bool) -> String {
+        self.policy
+            .placeholder(self.value_name.as_deref().unwrap_or("VALUE"), required)
+    }
+
+    // The attributes listed in brackets after the description in the usage, e.g; `default: auto`
+    // With env_prefix, the variable the option is read from is listed for the options without one of their own
+    fn usage_attributes(&self, env_prefix: Option<&str>, messages: &dyn Messages) -> Vec<String> {
+        let label = |text: HelpText| messages.help(text);
+        let mut attributes = Vec::new();
+        match self.delimiter {
+            _ if self.takes_no_values() => (),
+            _ if self.policy == OptionPolicy::FinalizeIgnore() && self.value_name.is_none() => (),
+            Some(delimiter) => attributes.push(format!(
+                "{}: {}, {} '{}'",
+                label(HelpText::Values),
+                messages.policy(&self.policy),
+                label(HelpText::SeparatedBy),
+                delimiter
+            )),
+            None => attributes.push(format!(
+                "{}: {}",
+                label(HelpText::Values),
+                messages.policy(&self.policy)
+            )),
+        }
+
+        if !self.defaults.is_empty() {
+            attributes.push(format!(
+                "{}: {}",
+                label(HelpText::Default),
+                self.defaults.join(", ")
+            ));
+        }
+
+        if !self.missing.is_empty() {
+            attributes.push(format!(
+                "{}: {}",
+                label(HelpText::DefaultWithoutValue),
+                self.missing.join(", ")
+            ));
+        }
+
+        match (&self.env, env_prefix) {
+            (Some(var), _) => attributes.push(format!("{}: {}", label(HelpText::Env), var)),
+            (None, Some(prefix)) if !self.positional && !self.trailing => attributes.push(format!(
+                "{}: {}",
+                label(HelpText::Env),
+                env_var(prefix, &self.name)
+            )),
+            _ => (),
+        }
+
+        if !self.choices.is_empty() {
+            attributes.push(format!(
+                "{}: {}",
+                label(HelpText::PossibleValues),
+                self.choices.join(", ")
+            ));
+        }
+
+        if let Some(note) = &self.deprecated {
+            attributes.push(format!("{}: {}", label(HelpText::Deprecated), note));
+        }
+
+        let options = |names: &[Cow<'static, str>]| {
+            names
+                .iter()
+                .map(|name| format!("--{}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        if !self.required_unless.is_empty() {
+            attributes.push(format!(
+                "{}: {}",
+                label(HelpText::RequiredUnless),
+                options(&self.required_unless)
+            ));
+        }
+
+        if !self.required_if.is_empty() {
+            attributes.push(format!(
+                "{}: {}",
+                label(HelpText::RequiredIf),
+                options(&self.required_if)
+            ));
+        }
+
+        if !self.requires.is_empty() {
+            attributes.push(format!(
+                "{}: {}",
+                label(HelpText::Requires),
+                options(&self.requires)
+            ));
+        }
+
+        attributes
+    }
+
+    // Lists the names with the description and the attributes indented below, the layout of Config::generate_usage
+    fn usage_entry(&self, color: bool, messages: &dyn Messages) -> String {
+        let required = format!("[{}]", messages.help(HelpText::Required));
+        let mut entry = format!(
+            "    {} {}\n        {}\n",
+            paint(&self.usage_names(), STYLE_NAME, color),
+            if self.required {
+                paint(&required, STYLE_REQUIRED, color)
+            } else {
+                String::new()
+            },
+            indent(&self.desc, 8, ' '),
+        );
+
+        let attributes = self.usage_attributes(None, messages);
+        if !attributes.is_empty() {
+            let attributes: Vec<String> = attributes
+                .iter()
+                .map(|attribute| format!("[{}]", attribute))
+                .collect();
+            entry += &format!("        {}\n", attributes.join(" "));
+        }
+
+        entry + "\n"
+    }
+
+    // Lists the names in the first column and the wrapped description and attributes in a second column starting at column
+    // The attributes follow the last line of the description, the description starts on the next line when the names don't fit before the column
+    fn usage_columns(&self, layout: &UsageLayout) -> String {
+        let UsageLayout {
+            column,
+            width,
+            color,
+            ..
+        } = *layout;
+        let names = self.usage_names();
+        let names_width = names.chars().count() + 4;
+        let names = format!("    {}", paint(&names, STYLE_NAME, color));
+        let text_width = width.saturating_sub(column).max(MIN_HELP_TEXT_WIDTH);
+
+        let desc = match (&self.long_desc, layout.long) {
+            (Some(long_desc), true) => long_desc,
+            _ => &self.desc,
+        };
+        let mut paragraphs: Vec<String> = desc.lines().map(String::from).collect();
+        let required = format!("[{}]", layout.messages.help(HelpText::Required));
+        let mut attributes = Vec::new();
+        if self.required {
+            attributes.push(required.clone());
+        }
+        attributes.extend(
+            self.usage_attributes(layout.env_prefix, layout.messages)
+                .iter()
+                .map(|attribute| format!("[{}]", attribute)),
+        );
+        if !attributes.is_empty() {
+            match paragraphs.last_mut() {
+                Some(last) if !last.trim().is_empty() => {
+                    *last = format!("{} {}", last.trim_end(), attributes.join(" "))
+                }
+                _ => paragraphs.push(attributes.join(" ")),
+            }
+        }
+
+        // Painted after wrapping, the escape codes take no columns
+        let mut lines = paragraphs
+            .iter()
+            .flat_map(|paragraph| wrap(paragraph, text_width))
+            .map(|line| line.replace(&required, &paint(&required, STYLE_REQUIRED, color)));
+        let first = match names_width + 2 <= column {
+            true => lines.next().filter(|line| !line.is_empty()),
+            false => None,
+        };
+        let mut usage = match first {
+            Some(line) => format!("{}{:pad$}{}\n", names, "", line, pad = column - names_width),
+            None => format!("{}\n", names),
+        };
+        for line in lines {
+            match line.is_empty() {
+                true => usage.push('\n'),
+                false => usage += &format!("{:column$}{}\n", "", line, column = column),
+            }
+        }
+
+        // The long descriptions are separated by a blank line
+        if layout.long {
+            usage.push('\n');
+        }
+        usage
+    }
+}
+
+// How the entries of the usage are laid out in columns, see OptionSpec::usage_columns
+#[derive(Clone, Copy)]
+struct UsageLayout<'a> {
+    // The texts of the labels
+    messages: &'a dyn Messages,
+    // Where the descriptions start
+    column: usize,
+    // The width the descriptions are wrapped to
+    width: usize,
+    color: bool,
+    // Lists the environment variables of the options, see Help::env_prefix
+    env_prefix: Option<&'a str>,
+    // Uses the long descriptions, see Help::long
+    long: bool,
+}
+
+impl std::fmt::Display for OptionSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.usage_entry(false, &English))
+    }
+}
+
+/// When the usage is colored with ANSI escape codes, see Config::generate_usage_colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Colored when stdout is a terminal and the `NO_COLOR` environment variable isn't set
+    #[default]
+    Auto,
+    /// Always colored
+    Always,
+    /// Never colored
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns true if the output should be colored<br>
+    /// For Auto, a non empty `NO_COLOR` disables colors, see <https://no-color.org>
+    pub fn enabled(&self) -> bool {
+        use std::io::IsTerminal;
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").map_or(true, |value| value.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// The order the options are listed in by the generated usage, within each section, see Help::order<br>
+/// The options with an OptionSpec::display_order are listed first by their key, regardless of the order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsageOrder {
+    /// The required options followed by the others, each in the order they are specified
+    #[default]
+    Required,
+    /// The order the options are specified in
+    Declaration,
+    /// Sorted by name, ignoring case
+    Alphabetical,
+}
+
+// The styles of the parts of the usage when colored
+const STYLE_NAME: &str = "\x1b[1m";
+const STYLE_REQUIRED: &str = "\x1b[31m";
+const STYLE_SECTION: &str = "\x1b[1;4m";
+
+// Wraps text in the ANSI escape codes of style, if color is enabled
+fn paint(text: &str, style: &str, color: bool) -> String {
+    match color {
+        true => format!("{}{}\x1b[0m", style, text),
+        false => text.to_string(),
+    }
+}
+
+// How deep response files can refer to other response files, guards against cycles
+const MAX_RESPONSE_FILE_DEPTH: usize = 16;
+
+// Replaces every `@file` argument, except the command, with the whitespace separated arguments in the file
+// Arguments read from a file are expanded recursively
+fn expand_response_files(args: Vec<String>, depth: usize) -> Result<Vec<String>, ParseError> {
+    let mut result = Vec::with_capacity(args.len());
+    for (index, arg) in args.into_iter().enumerate() {
+        let path = match arg.strip_prefix('@') {
+            Some(path) if depth > 0 || index > 0 => path,
+            _ => {
+                result.push(arg);
+                continue;
+            }
+        };
+
+        if depth >= MAX_RESPONSE_FILE_DEPTH {
+            return Err(ParseError::File {
+                path: path.to_string(),
+                reason: "response files are nested too deep".to_string(),
+            });
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|e| ParseError::File {
+            path: path.to_string(),
+            reason: e.to_string(),
+        })?;
+        let file_args = contents
+            .split_whitespace()
+            .map(|arg| arg.to_string())
+            .collect();
+        result.extend(expand_response_files(file_args, depth + 1)?);
+    }
+
+    Ok(result)
+}
+
+/// Splits a command line into arguments like a POSIX shell, e.g; for a REPL or for tests<br>
+/// Arguments are separated by whitespace, which can be kept in an argument by quoting or escaping it<br>
+/// Single quotes keep everything literally, double quotes allow escaping `"`, `\`, `$` and `` ` `` with a backslash<br>
+/// Outside of quotes a backslash escapes any character<br>
+/// Returns Err if a quote is left open or the line ends with a backslash
+/// ## Example
+/// ```
+/// # use libcli::args;
+/// let args = args::split_args(r#"-o out.txt file1 "file two" it\'s 'a "b"'"#).unwrap();
+/// assert_eq!(args, ["-o", "out.txt", "file1", "file two", "it's", "a \"b\""]);
+/// ```
+pub fn split_args(line: &str) -> Result<Vec<String>, ParseError> {
+    let unterminated =
+        |what: &str| ParseError::Custom(format!("Unterminated {} in '{}'", what, line));
+    let mut args = Vec::new();
+    // The current argument, None between arguments so that "" gives an empty argument
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            ch if ch.is_whitespace() => args.extend(current.take()),
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or_else(|| unterminated("'"))? {
+                        '\'' => break,
+                        ch => arg.push(ch),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().ok_or_else(|| unterminated("\""))? {
+                        '"' => break,
+                        '\\' => match chars.next().ok_or_else(|| unterminated("\""))? {
+                            ch @ ('"' | '\\' | '$' | '`') => arg.push(ch),
+                            ch => {
+                                arg.push('\\');
+                                arg.push(ch);
+                            }
+                        },
+                        ch => arg.push(ch),
+                    }
+                }
+            }
+            '\\' => {
+                let ch = chars.next().ok_or_else(|| unterminated("escape"))?;
+                current.get_or_insert_with(String::new).push(ch);
+            }
+            ch => current.get_or_insert_with(String::new).push(ch),
+        }
+    }
+    args.extend(current);
+
+    Ok(args)
+}
+
+// Converts an OS argument to a String the parser can work with, without losing the parts that aren't valid unicode
+// Each byte of invalid UTF-8 is mapped to a character at the end of the private use plane, which decode_os_arg maps back
+// The characters in that range are escaped byte by byte as well, so they aren't mistaken for escaped bytes
+#[cfg(unix)]
+fn encode_os_arg(arg: &OsStr) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let escape = |result: &mut String, bytes: &[u8]| {
+        result.extend(
+            bytes
+                .iter()
+                .filter_map(|byte| char::from_u32(OS_ESCAPE_BASE + *byte as u32)),
+        )
+    };
+    let push_valid = |result: &mut String, valid: &str| {
+        for ch in valid.chars() {
+            match ch as u32 >= OS_ESCAPE_BASE {
+                true => escape(result, ch.encode_utf8(&mut [0; 4]).as_bytes()),
+                false => result.push(ch),
+            }
+        }
+    };
+
+    let mut result = String::with_capacity(arg.len());
+    let mut bytes = arg.as_bytes();
+    while !bytes.is_empty() {
+        let err = match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                push_valid(&mut result, valid);
+                break;
+            }
+            Err(err) => err,
+        };
+
+        // The bytes up to the error are valid, the sequence at the error is cut short at the end
+        let (valid, rest) = bytes.split_at(err.valid_up_to());
+        push_valid(&mut result, std::str::from_utf8(valid).unwrap_or_default());
+        let (invalid, rest) = rest.split_at(err.error_len().unwrap_or(rest.len()));
+        escape(&mut result, invalid);
+        bytes = rest;
+    }
+    result
+}
+
+#[cfg(unix)]
+fn decode_os_arg(arg: &str) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    let mut bytes = Vec::with_capacity(arg.len());
+    for ch in arg.chars() {
+        match (ch as u32).checked_sub(OS_ESCAPE_BASE) {
+            Some(byte) => bytes.push(byte as u8),
+            None => bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    OsString::from_vec(bytes)
+}
+
+// Each unpaired surrogate is mapped to a character at the end of the private use plane, which decode_os_arg maps back
+// The characters in that range are escaped as their two surrogates, so they aren't mistaken for escaped surrogates
+#[cfg(windows)]
+fn encode_os_arg(arg: &OsStr) -> String {
+    use std::os::windows::ffi::OsStrExt;
+    let escape = |surrogate: u16| char::from_u32(OS_ESCAPE_BASE + (surrogate - 0xD800) as u32);
+    let mut result = String::with_capacity(arg.len());
+    for ch in char::decode_utf16(arg.encode_wide()) {
+        match ch {
+            Ok(ch) if ch as u32 >= OS_ESCAPE_BASE => result.extend(
+                ch.encode_utf16(&mut [0; 2])
+                    .iter()
+                    .filter_map(|unit| escape(*unit)),
+            ),
+            Ok(ch) => result.push(ch),
+            Err(e) => result.extend(escape(e.unpaired_surrogate())),
+        }
+    }
+    result
+}
+
+#[cfg(windows)]
+fn decode_os_arg(arg: &str) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    let mut wide = Vec::with_capacity(arg.len());
+    for ch in arg.chars() {
+        match (ch as u32).checked_sub(OS_ESCAPE_BASE) {
+            Some(surrogate) => wide.push(0xD800 + surrogate as u16),
+            None => wide.extend_from_slice(ch.encode_utf16(&mut [0; 2])),
+        }
+    }
+    OsString::from_wide(&wide)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn encode_os_arg(arg: &OsStr) -> String {
+    arg.to_string_lossy().into_owned()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn decode_os_arg(arg: &str) -> OsString {
+    OsString::from(arg)
+}
+
+// The first character used to escape the parts of an OS argument that aren't valid unicode
+#[cfg(unix)]
+const OS_ESCAPE_BASE: u32 = 0x10FF00;
+#[cfg(windows)]
+const OS_ESCAPE_BASE: u32 = 0x10F800;
+
+// Returns the Levenshtein distance between two strings
+// I.e; the number of single character insertions, deletions or substitutions to turn one into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + if ca == *cb { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+// The width used for the usage when the terminal width can't be detected
+const DEFAULT_HELP_WIDTH: usize = 80;
+
+// The narrowest the second column of the usage gets, in very narrow terminals the lines overflow instead
+const MIN_HELP_TEXT_WIDTH: usize = 20;
+
+/// Returns the width of the terminal in columns, used by Config::generate_usage_wrapped<br>
+/// The COLUMNS environment variable takes precedence, with the `terminal_size` feature the width of the terminal attached to stdout is detected<br>
+/// Falls back to 80 columns, e.g; when the output is piped
+pub fn terminal_width() -> usize {
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|columns| *columns > 0)
+    {
+        return columns;
+    }
+
+    #[cfg(feature = "terminal_size")]
+    if let Some((terminal_size::Width(width), _)) = terminal_size::terminal_size() {
+        return width as usize;
+    }
+
+    DEFAULT_HELP_WIDTH
+}
+
+// Breaks a line of text at whitespace into lines of at most width characters
+// A bracketed group, e.g; `[default: auto]`, is kept on one line if it fits, a word longer than width is put on a line of its own
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut groups: Vec<String> = Vec::new();
+    let mut open = false;
+    for word in line.split_whitespace() {
+        match groups.last_mut() {
+            Some(group) if open => *group = format!("{} {}", group, word),
+            _ => groups.push(word.to_string()),
+        }
+        if word.starts_with('[') {
+            open = true;
+        }
+        if word.ends_with(']') {
+            open = false;
+        }
+    }
+
+    let words = groups
+        .iter()
+        .flat_map(|group| match group.chars().count() > width {
+            true => group.split(' ').collect(),
+            false => vec![group.as_str()],
+        });
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in words {
+        let length = current.chars().count();
+        if length > 0 && length + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+// Indents every line in passed string, not just the first and returns the result
+// Not including the first line
+fn indent(string: &str, depth: usize, indent_ch: char) -> String {
+    let mut result = String::with_capacity(string.len());
+    let mut buf = String::with_capacity(256);
+    let mut indent: [u8; 4] = [b'\0'; 4];
+    let indent = indent_ch.encode_utf8(&mut indent).repeat(depth);
+    for ch in string.chars() {
+        buf.push(ch);
+        if ch == '\n' {
+            buf.push_str(&indent);
+        }
+
+        if buf.len() > buf.capacity() - 2 {
+            result.push_str(&buf);
+            buf.clear();
+        }
+    }
+    result.push_str(&buf);
+    buf.clear();
+    result
+}
+
+/// A struct whose fields are parsed from the command line<br>
+/// Usually derived with `#[derive(Options)]` using the `derive` feature, each field becomes an option named after the field<br>
+/// The kind of option follows from the type of the field:<br>
+/// `bool`: a switch<br>
+/// `Option<T>`: an optional option with a single value<br>
+/// `Vec<T>`: an option with any number of values<br>
+/// Any other `T`: a required option with a single value<br>
+/// The values are parsed with FromStr, the description is taken from the doc comment of the field<br>
+/// The field attribute `#[option(..)]` accepts `abrev = 'c'`, `name = "..."`, `desc = "..."`, `policy = AtLeast(1)`, `default = "..."`, `required` and `positional`
+/// ## Example
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use libcli::args::Options;
+///
+/// #[derive(Options)]
+/// struct Opts {
+///     /// Input files
+///     #[option(positional)]
+///     files: Vec<String>,
+///     /// Number of jobs
+///     #[option(abrev = 'j', default = "1")]
+///     jobs: u32,
+///     /// Prints more information
+///     #[option(abrev = 'v')]
+///     verbose: bool,
+/// }
+///
+/// let opts = Opts::parse(&["./test", "a.txt", "-j", "4"]).unwrap();
+/// assert_eq!(opts.jobs, 4);
+/// # }
+/// ```
+pub trait Options: Sized {
+    /// Returns the option specifications of the fields
+    fn specs() -> Vec<OptionSpec>;
+
+    /// Reads the fields from a parsed config
+    /// Returns Err if a value couldn't be parsed to the type of its field
+    fn from_config(config: &Config) -> Result<Self, ParseError>;
+
+    /// Parses the fields from custom supplied arguments, see Config::new
+    fn parse(args: &[&str]) -> Result<Self, ParseError> {
+        Self::from_config(&Config::new(args, &Self::specs())?)
+    }
+
+    /// Same as Options::parse but uses the arguments passed to the program (env::args)
+    fn parse_env() -> Result<Self, ParseError> {
+        Self::from_config(&Config::new_env(&Self::specs())?)
+    }
+}
+
+/// Specifies a subcommand, e.g; the `build` in `myapp build --release`<br>
+/// A subcommand has its own list of option specifications which are used to parse the arguments following it<br>
+/// Subcommands can in turn own subcommands, e.g; `tool remote add <name>`<br>
+/// ## Example
+/// ```
+/// use libcli::args::{Command, OptionPolicy, OptionSpec};
+/// let build = Command::new(
+///     "build",
+///     "Compiles the project",
+///     vec![
+///         OptionSpec::new('\0', "(unnamed)", "Targets", false, OptionPolicy::AtLeast(0)),
+///         OptionSpec::new('r', "release", "Build with optimizations", false, OptionPolicy::Exact(0)),
+///     ],
+/// );
+/// ```
+pub struct Command {
+    name: Cow<'static, str>,
+    desc: Cow<'static, str>,
+    specs: Vec<OptionSpec>,
+    subcommands: Vec<Command>,
+    aliases: Vec<(Cow<'static, str>, bool)>,
+    default: bool,
+}
+
+impl Command {
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        specs: Vec<OptionSpec>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            desc: desc.into(),
+            specs,
+            subcommands: Vec::new(),
+            aliases: Vec::new(),
+            default: false,
+        }
+    }
+
+    /// Adds a hidden alternative name the subcommand is also recognized by<br>
+    /// Config::subcommand returns the name of the subcommand, not the alias that was given
+    pub fn alias(mut self, alias: impl Into<Cow<'static, str>>) -> Self {
+        self.aliases.push((alias.into(), false));
+        self
+    }
+
+    /// Adds an alternative name which is listed with the name in the help, e.g; `b` for `build`
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Command, Config, OptionPolicy, OptionSpec};
+    /// let commands = [Command::new("build", "Builds the project", Vec::new()).visible_alias("b")];
+    /// let specs = [OptionSpec::new('v', "verbose", "Shows verbose output", false, OptionPolicy::Exact(0))];
+    ///
+    /// let config = Config::new_with_commands(&["./test", "b"], &specs, &commands).unwrap();
+    /// assert_eq!(config.subcommand().map(|(name, _)| name), Some("build"));
+    /// ```
+    pub fn visible_alias(mut self, alias: impl Into<Cow<'static, str>>) -> Self {
+        self.aliases.push((alias.into(), true));
+        self
+    }
+
+    /// Marks the subcommand as the one taken when no subcommand is given<br>
+    /// It is parsed without arguments, so the config of the default subcommand holds its default values<br>
+    /// Not taken when parsing was stopped, e.g; by `--help`
+    pub fn default_subcommand(mut self) -> Self {
+        self.default = true;
+        self
+    }
+
+    /// Adds a nested subcommand which is recognized as the first positional argument after this command
+    pub fn subcommand(mut self, command: Command) -> Self {
+        self.subcommands.push(command);
+        self
+    }
+
+    /// Returns the name used to invoke the subcommand
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the short description of the subcommand
+    pub fn desc(&self) -> &str {
+        &self.desc
+    }
+
+    /// Returns the option specifications of the subcommand
+    pub fn specs(&self) -> &[OptionSpec] {
+        &self.specs
+    }
+
+    /// Returns the nested subcommands
+    pub fn subcommands(&self) -> &[Command] {
+        &self.subcommands
+    }
+
+    /// Returns the aliases of the subcommand, the hidden ones included
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.aliases.iter().map(|(alias, _)| alias.as_ref())
+    }
+
+    /// Returns true if the subcommand is taken when none is given, see Command::default_subcommand
+    pub fn is_default(&self) -> bool {
+        self.default
+    }
+
+    // Returns true if the name or an alias is name
+    fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|(alias, _)| alias == name)
+    }
+}
+
+/// An external subcommand, an executable named `<prefix>-<name>` on the PATH, see ConfigBuilder::external_subcommands<br>
+/// The arguments following its name are forwarded untouched, e.g; `myapp foo --bar` runs `myapp-foo --bar`
+/// ## Example
+/// ```no_run
+/// # use libcli::args::{ConfigBuilder, OptionPolicy, OptionSpec};
+/// # let specs = [OptionSpec::new('v', "verbose", "Shows verbose output", false, OptionPolicy::Exact(0))];
+/// let config = ConfigBuilder::new(&specs).external_subcommands("myapp").build().unwrap();
+///
+/// if let Some(external) = config.external_subcommand() {
+///     let status = external.run().unwrap();
+///     std::process::exit(status.code().unwrap_or(1));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCommand {
+    name: String,
+    path: PathBuf,
+    args: Vec<OsString>,
+}
+
+impl ExternalCommand {
+    /// Returns the name given on the command line, e.g; `foo`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path of the executable found on the PATH
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the arguments following the name
+    pub fn args(&self) -> &[OsString] {
+        &self.args
+    }
+
+    /// Runs the executable with the arguments and waits for it to exit
+    pub fn run(&self) -> std::io::Result<std::process::ExitStatus> {
+        std::process::Command::new(&self.path)
+            .args(&self.args)
+            .status()
+    }
+}
+
+// Finds an executable by name in the directories of the PATH
+fn find_executable(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// Returns true if a value for the spec can be entered, a switch takes none
+fn can_prompt(spec: &OptionSpec, settings: &Settings) -> bool {
+    let interactive = match &settings.prompt_input {
+        Some(input) => input.borrow().is_interactive(),
+        None => crate::input::is_interactive(),
+    };
+    !spec.takes_no_values() && interactive
+}
+
+// Prompts for the values of a missing option on the Input of ConfigBuilder::prompt_input, or stdin and stdout
+fn prompt_values(spec: &OptionSpec, settings: &Settings) -> Result<Vec<String>, ParseError> {
+    match &settings.prompt_input {
+        Some(input) => prompt_values_on(spec, &mut input.borrow_mut()),
+        None => prompt_values_on(spec, &mut Input::stdio()),
+    }
+}
+
+// Prompts for the values of a missing option until they are accepted, the rejected entries are reported on the Input
+// Fails with MissingRequired at the end of the input or if it can't be read
+fn prompt_values_on<R: BufRead, W: Write>(
+    spec: &OptionSpec,
+    input: &mut Input<R, W>,
+) -> Result<Vec<String>, ParseError> {
+    let msg = match spec.positional {
+        true => format!("{} <{}>", spec.desc, spec.name),
+        false => format!("{} (--{})", spec.desc, spec.name),
+    };
+    let values = |line: &str| split_args(line).and_then(|values| spec.enforce(values));
+    match input.read_validated(&msg, ": ", |line| {
+        values(line).map(drop).map_err(|err| err.to_string())
+    }) {
+        Ok(line) => values(&line),
+        // A closed input is treated like its end
+        Err(_) => {
+            let _ = input.print(format_args!("\n"));
+            Err(ParseError::MissingRequired {
+                name: spec.name.to_string(),
+            })
+        }
+    }
+}
+
+/// A problem found while parsing which doesn't prevent the arguments from being used, see Config::warnings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// An option marked with OptionSpec::deprecated was given, with the note of the spec
+    Deprecated { name: String, note: String },
+    /// A full name was matched case insensitively or as a prefix, e.g; `--verb` for `--verbose`
+    Corrected { given: String, name: String },
+    /// An environment variable starts with the prefix of the application but matches no option, e.g; a misspelled `MYAPP_VERBSE`
+    UnknownEnv { var: String },
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarning::Deprecated { name, note } => {
+                write!(f, "Option '{}' is deprecated, {}", name, note)
+            }
+            ParseWarning::Corrected { given, name } => {
+                write!(f, "Option '--{}' was interpreted as '--{}'", given, name)
+            }
+            ParseWarning::UnknownEnv { var } => {
+                write!(f, "Environment variable '{}' doesn't match any option", var)
+            }
+        }
+    }
+}
+
+/// Where the values of an option came from, see Config::source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Given on the command line, including values given to a global option after a subcommand
+    Cli,
+    /// Read from the environment variable
+    Env(String),
+    /// The default values of the spec
+    Default,
+    /// Read from the config file
+    File(PathBuf),
+    /// Entered when prompted for a missing required option, see ConfigBuilder::prompt_missing
+    Prompt,
+}
+
+/// Specifies a configuration of parsed arguments
+/// Each option which was given as a spec can be accessed by option(name)
+/// This returns a Option<Vec<String>> containing the values of the option (if any)
+pub struct Config {
+    command: String,
+    parsed: HashMap<String, Vec<String>>,
+    parsed_os: HashMap<String, Vec<OsString>>,
+    occurrences: HashMap<String, Vec<Vec<String>>>,
+    subcommand: Option<Box<Config>>,
+    external: Option<ExternalCommand>,
+    unknown: Vec<String>,
+    warnings: Vec<ParseWarning>,
+    maps: HashMap<String, HashMap<String, String>>,
+    sources: HashMap<String, Source>,
+    // The negatable switches cleared by their `--no-<name>` counterpart
+    negated: HashSet<String>,
+    // Parsing was stopped by a FinalizeIgnore option, here or in a subcommand
+    stopped: bool,
+    help_requested: bool,
+    // The help was requested by its full name, `--help` rather than `-h`
+    long_help: bool,
+    version_requested: bool,
+}
+
+// Settings changing how arguments are parsed
+#[derive(Default, Clone)]
+struct Settings {
+    // Prefix used to map every option to an environment variable
+    env_prefix: Option<String>,
+    // Arguments after an option which has all its values are positional
+    interleaved: bool,
+    // Option recognition stops at the first positional argument
+    posix: bool,
+    // Unknown options are collected instead of failing
+    allow_unknown: bool,
+    // Full option names are matched regardless of case
+    case_insensitive: bool,
+    // Full option names can be abbreviated to an unambiguous prefix
+    prefix_matching: bool,
+    // The arguments were encoded from OsStrings with encode_os_arg
+    os_args: bool,
+    // The name and version printed by -V and --version at the top level
+    version: Option<String>,
+    // -h and --help are recognized by every command
+    auto_help: bool,
+    // Values of the top level options read from a config file
+    file_values: HashMap<String, Vec<String>>,
+    // The path of the config file
+    file_path: Option<PathBuf>,
+    // The prefix of the external subcommands on the PATH, e.g; `myapp` for `myapp-foo`
+    external_prefix: Option<String>,
+    // Missing required options are prompted for when stdin is a terminal or prompt_input is interactive
+    prompt_missing: bool,
+    // The Input prompted on instead of stdin and stdout, shared with the subcommands
+    prompt_input: Option<Rc<RefCell<BoxedInput>>>,
+}
+
+// The option recognized when the application registers its version
+fn version_spec() -> OptionSpec {
+    OptionSpec::new(
+        'V',
+        "version",
+        "Prints version information",
+        false,
+        OptionPolicy::FinalizeIgnore(),
+    )
+}
+
+// The option recognized with automatic help
+fn help_spec() -> OptionSpec {
+    OptionSpec::new(
+        'h',
+        "help",
+        "Prints help information",
+        false,
+        OptionPolicy::FinalizeIgnore(),
+    )
+}
+
+impl Settings {
+    fn with_prefix(prefix: &str) -> Self {
+        Self {
+            env_prefix: Some(prefix.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    fn with_file(path: &Path, specs: &[OptionSpec]) -> Result<Self, ParseError> {
+        Ok(Self {
+            file_values: load_toml(path, specs)?,
+            file_path: Some(path.to_path_buf()),
+            ..Default::default()
+        })
+    }
+
+    // The settings passed on when parsing the arguments of a subcommand
+    // The config file only applies to the top level options
+    fn for_subcommand(&self) -> Self {
+        Self {
+            file_values: HashMap::new(),
+            file_path: None,
+            version: None,
+            external_prefix: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Composes the sources of option values into a Config<br>
+/// The sources are, from highest to lowest precedence: the command line arguments, environment variables, a config file and the default values of the specs<br>
+/// The value of an option is taken from the highest source which supplies it, the sources are not merged per value<br>
+/// The OptionPolicy and validators are enforced on the value that ends up in the Config<br>
+/// ## Example
+/// ```
+/// # use libcli::args::{ConfigBuilder, OptionPolicy, OptionSpec};
+/// let specs = [
+///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1))
+///         .default_values(&["out.txt"]),
+/// ];
+///
+/// let config = ConfigBuilder::new(&specs)
+///     .args(&["./test", "input.txt"])
+///     .env_prefix("MYAPP")
+///     .build()
+///     .unwrap();
+/// ```
+pub struct ConfigBuilder<'a> {
+    specs: &'a [OptionSpec],
+    commands: &'a [Command],
+    args: Option<Vec<String>>,
+    response_files: bool,
+    help_width: Option<usize>,
+    color: ColorChoice,
+    help_template: Option<String>,
+    help_order: UsageOrder,
+    messages: &'a dyn Messages,
+    about: String,
+    long_about: Option<String>,
+    examples: String,
+    before_help: String,
+    after_help: String,
+    settings: Settings,
+    #[cfg(feature = "toml")]
+    file: Option<std::path::PathBuf>,
+}
+
+impl<'a> ConfigBuilder<'a> {
+    pub fn new(specs: &'a [OptionSpec]) -> Self {
+        Self {
+            specs,
+            commands: &[],
+            args: None,
+            response_files: false,
+            help_width: None,
+            color: ColorChoice::Never,
+            help_template: None,
+            help_order: UsageOrder::Required,
+            messages: &English,
+            about: String::new(),
+            long_about: None,
+            examples: String::new(),
+            before_help: String::new(),
+            after_help: String::new(),
+            settings: Settings::default(),
+            #[cfg(feature = "toml")]
+            file: None,
+        }
+    }
+
+    /// Sets the command line arguments to parse, including the command
+    /// The arguments passed to the program (env::args) are used if not set
+    pub fn args(mut self, args: &[&str]) -> Self {
+        self.args = Some(args.iter().map(|arg| arg.to_string()).collect());
+        self
+    }
+
+    /// Sets the subcommands which are recognized, see Config::new_with_commands
+    pub fn commands(mut self, commands: &'a [Command]) -> Self {
+        self.commands = commands;
+        self
+    }
+
+    /// Maps every named option to the environment variable `<PREFIX>_<NAME>`, see Config::new_with_prefix<br>
+    /// Variables with the prefix which match no option are recorded as a ParseWarning::UnknownEnv
+    pub fn env_prefix(mut self, prefix: &str) -> Self {
+        self.settings.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Enables GNU style interleaving of options and positional arguments<br>
+    /// Once an option has as many values as its policy allows, the following arguments are positional again<br>
+    /// E.g; `prog -o out.txt file1 file2` where output is Exact(1)
+    pub fn interleaved(mut self, interleaved: bool) -> Self {
+        self.settings.interleaved = interleaved;
+        self
+    }
+
+    /// Enables POSIX strict parsing where option recognition stops at the first positional argument<br>
+    /// The positional argument and all following are collected as positional values, e.g; `prog file --verbose`<br>
+    /// An option takes no more values than its policy allows, like in interleaved mode
+    pub fn posix(mut self, posix: bool) -> Self {
+        self.settings.posix = posix;
+        self
+    }
+
+    /// Enables a lenient mode where unknown options are collected instead of failing parsing<br>
+    /// The unknown options are accessed in their original order with Config::unknown<br>
+    /// The values of an unknown option can't be told apart, use the `--name=value` form to keep them together
+    pub fn allow_unknown(mut self, allow_unknown: bool) -> Self {
+        self.settings.allow_unknown = allow_unknown;
+        self
+    }
+
+    /// Matches full option names regardless of case, e.g; `--Verbose` for `--verbose`<br>
+    /// An option name matched this way is recorded as a ParseWarning::Corrected
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.settings.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Allows full option names to be abbreviated to an unambiguous prefix, e.g; `--verb` for `--verbose`<br>
+    /// Parsing fails listing the candidates if the prefix matches several options<br>
+    /// An option name matched this way is recorded as a ParseWarning::Corrected
+    pub fn prefix_matching(mut self, prefix_matching: bool) -> Self {
+        self.settings.prefix_matching = prefix_matching;
+        self
+    }
+
+    /// Expands arguments of the form `@file` to the whitespace separated arguments contained in the file before parsing<br>
+    /// Response files can refer to other response files, the command itself is never expanded
+    pub fn response_files(mut self, response_files: bool) -> Self {
+        self.response_files = response_files;
+        self
+    }
+
+    /// Registers the name and version of the application, recognizing `-V` and `--version`<br>
+    /// When given, parsing stops like with FinalizeIgnore, skipping the checks for required options, and ConfigBuilder::parse returns `name version`<br>
+    /// With build(), the application checks for it with Config::version_requested and exits<br>
+    /// A spec with the name "version" or abbreviation 'V' takes precedence
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{ConfigBuilder, OptionPolicy, OptionSpec};
+    /// # let specs = [OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0))];
+    /// let config = ConfigBuilder::new(&specs)
+    ///     .version(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// if config.version_requested() {
+    ///     return;
+    /// }
+    /// ```
+    pub fn version(mut self, name: &str, version: &str) -> Self {
+        self.settings.version = Some(format!("{} {}", name, version));
+        self
+    }
+
+    /// Resolves an unknown subcommand `foo` to an executable `<prefix>-foo` on the PATH, like git and cargo<br>
+    /// It is only looked up where a subcommand is recognized at the top level and when no Command matches<br>
+    /// Parsing stops at the name, the rest of the arguments are forwarded untouched, see Config::external_subcommand
+    pub fn external_subcommands(mut self, prefix: &str) -> Self {
+        self.settings.external_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Prompts for the values of missing required options instead of failing, when stdin is a terminal or the Input of ConfigBuilder::prompt_input is interactive<br>
+    /// The entered line is split like a shell would, see split_args, and checked against the policy and validators of the option<br>
+    /// An invalid entry is reported and prompted for again, the values are recorded with Source::Prompt
+    pub fn prompt_missing(mut self, prompt_missing: bool) -> Self {
+        self.settings.prompt_missing = prompt_missing;
+        self
+    }
+
+    /// Sets the Input prompt_missing prompts on and reads the values from, instead of stdout and stdin<br>
+    /// The prompts are only shown when the Input is interactive, see Input::interactive
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{ConfigBuilder, OptionPolicy, OptionSpec};
+    /// use libcli::input::Input;
+    /// let specs = [OptionSpec::new('o', "output", "Output file", true, OptionPolicy::Exact(1))];
+    ///
+    /// let config = ConfigBuilder::new(&specs)
+    ///     .args(&["./test"])
+    ///     .prompt_missing(true)
+    ///     .prompt_input(Input::new("out.txt\n".as_bytes(), std::io::sink()))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(config.single("output"), Some("out.txt"));
+    /// ```
+    pub fn prompt_input<R, W>(mut self, input: Input<R, W>) -> Self
+    where
+        R: BufRead + 'static,
+        W: Write + 'static,
+    {
+        self.settings.prompt_input = Some(Rc::new(RefCell::new(input.boxed())));
+        self
+    }
+
+    /// Recognizes `-h` and `--help` for the top level and every subcommand<br>
+    /// When given, parsing stops like with FinalizeIgnore and the usage of the command is rendered, see ConfigBuilder::parse<br>
+    /// `-h` renders the compact help and `--help` the long one, see Help::long<br>
+    /// A spec with the name "help" or abbreviation 'h' takes precedence
+    pub fn auto_help(mut self, auto_help: bool) -> Self {
+        self.settings.auto_help = auto_help;
+        self
+    }
+
+    /// Sets the width the automatic help is fitted to, instead of the width of the terminal<br>
+    /// See Config::generate_usage_wrapped
+    pub fn help_width(mut self, width: usize) -> Self {
+        self.help_width = Some(width);
+        self
+    }
+
+    /// Sets when the automatic help is colored, it isn't by default<br>
+    /// See Config::generate_usage_colored
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the layout of the automatic help, see Help::template
+    pub fn help_template(mut self, template: &str) -> Self {
+        self.help_template = Some(template.to_string());
+        self
+    }
+
+    /// Sets the order the options are listed in by the automatic help, see Help::order
+    pub fn help_order(mut self, order: UsageOrder) -> Self {
+        self.help_order = order;
+        self
+    }
+
+    /// Sets the messages the automatic help is rendered with, see Messages
+    pub fn messages(mut self, messages: &'a dyn Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Sets the description of the program at the top of the automatic help, see Help::about
+    pub fn about(mut self, about: &str) -> Self {
+        self.about = about.to_string();
+        self
+    }
+
+    /// Sets the description of the program in the help for `--help`, see Help::long_about
+    pub fn long_about(mut self, long_about: &str) -> Self {
+        self.long_about = Some(long_about.to_string());
+        self
+    }
+
+    /// Sets the examples listed in the automatic help, see Help::examples
+    pub fn examples(mut self, examples: &str) -> Self {
+        self.examples = examples.to_string();
+        self
+    }
+
+    /// Sets the text at the top of the automatic help, see Help::before_help
+    pub fn before_help(mut self, before_help: &str) -> Self {
+        self.before_help = before_help.to_string();
+        self
+    }
+
+    /// Sets the text at the end of the automatic help, see Help::after_help
+    pub fn after_help(mut self, after_help: &str) -> Self {
+        self.after_help = after_help.to_string();
+        self
+    }
+
+    /// Reads the options from a TOML file, see Config::new_with_file
+    #[cfg(feature = "toml")]
+    pub fn file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Reads all sources and parses the arguments, without printing anything
+    /// Returns Err(ParseError) if a source couldn't be read or a spec doesn't match what is specified
+    /// A requested help or version is only recorded, see Config::help_requested and Config::version_requested, use ConfigBuilder::parse to render them
+    pub fn build(self) -> Result<Config, ParseError> {
+        self.parse_args()
+    }
+
+    /// Reads all sources and parses the arguments, without printing anything
+    /// Returns the rendered usage or version instead of the config if requested with ConfigBuilder::auto_help or ConfigBuilder::version
+    /// Returns Err(ParseError) if a source couldn't be read or a spec doesn't match what is specified
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{ConfigBuilder, OptionPolicy, OptionSpec, ParseOutcome};
+    /// # let specs = [OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0))];
+    /// match ConfigBuilder::new(&specs).auto_help(true).parse().unwrap() {
+    ///     ParseOutcome::Parsed(config) => println!("{:?}", config.option("(unnamed)")),
+    ///     ParseOutcome::HelpRequested(text) | ParseOutcome::VersionRequested(text) => print!("{}", text),
+    /// }
+    /// ```
+    pub fn parse(self) -> Result<ParseOutcome, ParseError> {
+        let config = self.parse_args()?;
+        Ok(match self.request(&config) {
+            Some(outcome) => outcome,
+            None => ParseOutcome::Parsed(config),
+        })
+    }
+
+    /// Same as ConfigBuilder::parse but exits the program instead of returning the help, version or an error<br>
+    /// The help or version are printed to stdout and the program exits with status 0<br>
+    /// An error is printed to stderr with the messages of the builder like Config::parse_or_exit and the program exits with status 2
+    /// ## Example
+    /// ```no_run
+    /// # use libcli::args::{ConfigBuilder, English, OptionPolicy, OptionSpec};
+    /// # let specs = [OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0))];
+    /// let config = ConfigBuilder::new(&specs).auto_help(true).messages(&English).parse_or_exit();
+    /// ```
+    pub fn parse_or_exit(self) -> Config {
+        let messages = self.messages;
+        let specs = self.specs;
+        let command = match &self.args {
+            Some(args) => args.first().cloned(),
+            None => std::env::args().next(),
+        };
+
+        match self.parse() {
+            Ok(ParseOutcome::Parsed(config)) => config,
+            Ok(ParseOutcome::HelpRequested(text) | ParseOutcome::VersionRequested(text)) => {
+                print!("{}", text);
+                std::process::exit(0)
+            }
+            Err(err) => {
+                let synopsis = Config::generate_synopsis(&command.unwrap_or_default(), specs);
+                eprint!("{}", messages.report(&err, &synopsis));
+                std::process::exit(2)
+            }
+        }
+    }
+
+    fn parse_args(&self) -> Result<Config, ParseError> {
+        #[allow(unused_mut)]
+        let mut settings = self.settings.clone();
+
+        #[cfg(feature = "toml")]
+        if let Some(path) = &self.file {
+            settings.file_values = load_toml(path, self.specs)?;
+            settings.file_path = Some(path.clone());
+        }
+
+        let mut args = self
+            .args
+            .clone()
+            .unwrap_or_else(|| std::env::args().collect());
+        if self.response_files {
+            args = expand_response_files(args, 0)?;
+        }
+
+        Config::parse(args, self.specs, self.commands, &settings)
+    }
+
+    // Renders the version or the usage of the command where help was requested
+    fn request(&self, config: &Config) -> Option<ParseOutcome> {
+        if let (true, Some(version)) = (config.version_requested, &self.settings.version) {
+            return Some(ParseOutcome::VersionRequested(format!("{}\n", version)));
+        }
+
+        let (mut specs, mut commands, mut current) = (self.specs, self.commands, config);
+        let mut program = config.program_name().to_string();
+        while !current.help_requested {
+            let (name, sub) = current.subcommand()?;
+            let command = commands.iter().find(|command| command.name == name)?;
+            specs = &command.specs;
+            commands = &command.subcommands;
+            current = sub;
+            program = format!("{} {}", program, name);
+        }
+
+        let mut added = vec![help_spec()];
+        if std::ptr::eq(current, config) && self.settings.version.is_some() {
+            added.push(version_spec());
+        }
+
+        let mut help = Help::new(&program, specs)
+            .commands(commands)
+            .added(added)
+            .color(self.color)
+            .order(self.help_order)
+            .messages(self.messages)
+            .long(current.long_help);
+        // The texts describe the program, not its subcommands
+        if std::ptr::eq(current, config) {
+            help = help
+                .about(&self.about)
+                .examples(&self.examples)
+                .before_help(&self.before_help)
+                .after_help(&self.after_help);
+            if let Some(long_about) = &self.long_about {
+                help = help.long_about(long_about);
+            }
+        }
+        if let Some(width) = self.help_width {
+            help = help.width(width);
+        }
+        if let Some(prefix) = &self.settings.env_prefix {
+            help = help.env_prefix(prefix);
+        }
+        if let Some(template) = &self.help_template {
+            help = help.template(template);
+        }
+        let usage = help.render();
+        Some(ParseOutcome::HelpRequested(usage))
+    }
+}
+
+/// The result of ConfigBuilder::parse
+#[allow(clippy::large_enum_variant)]
+pub enum ParseOutcome {
+    /// The arguments were parsed
+    Parsed(Config),
+    /// Help was requested, contains the usage of the command it was requested for
+    HelpRequested(String),
+    /// The version was requested, contains the name and version of the application
+    VersionRequested(String),
+}
+
+/// Parses and generates configuration from supplied arguments and option specifications
+/// Can also generate usage strings
+impl Config {
+    /// Same as Config::new but uses the arguments passed to the program (env::args)
+    /// The program path, first argument, is included in the unnamed args<br>
+    /// Note, the spec isn't stored with config<br>
+    pub fn new_env(specs: &[OptionSpec]) -> Result<Config, ParseError> {
+        Config::parse(std::env::args().collect(), specs, &[], &Settings::default())
+    }
+
+    /// Same as Config::new_env but exits the program instead of returning Err<br>
+    /// On failure the error and a one line usage are printed to stderr, followed by a hint to use `--help`, see Messages::report<br>
+    /// The program exits with status 2, the convention for invalid usage<br>
+    /// See ConfigBuilder::parse_or_exit for translated messages
+    /// ## Example
+    /// ```no_run
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", true, OptionPolicy::AtLeast(1)),
+    ///     OptionSpec::new('v', "verbose", "Shows verbose output", false, OptionPolicy::Exact(0)),
+    /// ];
+    ///
+    /// let config = Config::parse_or_exit(&specs);
+    /// ```
+    pub fn parse_or_exit(specs: &[OptionSpec]) -> Config {
+        let args: Vec<String> = std::env::args().collect();
+        let command = args.first().cloned().unwrap_or_default();
+        Config::parse(args, specs, &[], &Settings::default()).unwrap_or_else(|err| {
+            eprint!(
+                "{}",
+                English.report(&err, &Self::generate_synopsis(&command, specs))
+            );
+            std::process::exit(2)
+        })
+    }
+
+    /// Same as Config::new_os but uses the arguments passed to the program (env::args_os)
+    /// Unlike Config::new_env, arguments that aren't valid unicode don't cause a panic
+    pub fn new_env_os(specs: &[OptionSpec]) -> Result<Config, ParseError> {
+        Config::new_os(std::env::args_os(), specs)
+    }
+
+    /// Parses config from arguments which may not be valid unicode, e.g; file paths<br>
+    /// The values are accessed losslessly with the option_os(name) method<br>
+    /// The values returned by option(name) and the other methods have invalid parts replaced with U+FFFD<br>
+    pub fn new_os<I, S>(args: I, specs: &[OptionSpec]) -> Result<Config, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        Config::parse(
+            args.into_iter()
+                .map(|arg| encode_os_arg(arg.as_ref()))
+                .collect(),
+            specs,
+            &[],
+            &Settings {
+                os_args: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Parses config from custom supplied arguments<br>
+    /// Specs is a list containing specifications for the available options a use can supply<br>
+    /// Returns Err(ParseError) if a spec doesn't match what is specified<br>
+    /// The arguments before any option are specified with the (unnamed)<br>
+    /// The values for the options can be accessed with the option(name) method<br>
+    /// Parsing will fail if an option with policy other than Exact(0) is used twice, unless configured with duplicate_policy()
+    /// A value can be attached to a full name option with '=', e.g; `--output=out.txt`<br>
+    /// A value can be attached directly to an abbreviated option, e.g; `-ooutput.txt` or `-n3`<br>
+    /// The arguments after `--` are captured verbatim by the spec created with OptionSpec::trailing<br>
+    /// Note, the spec isn't stored with config<br>
+    pub fn new(args: &[&str], specs: &[OptionSpec]) -> Result<Config, ParseError> {
+        Config::parse(
+            args.iter().map(|arg| arg.to_string()).collect(),
+            specs,
+            &[],
+            &Settings::default(),
+        )
+    }
+
+    /// Parses config from a command line string, split into arguments with split_args<br>
+    /// The string only contains the arguments, the command of the config is left empty
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+    /// ];
+    ///
+    /// let config = Config::from_str(r#"file1 "file two" -o out.txt"#, &specs).unwrap();
+    /// assert_eq!(config.single("output"), Some("out.txt"));
+    /// assert_eq!(config.option("(unnamed)").unwrap(), ["file1", "file two"]);
+    /// ```
+    pub fn from_str(line: &str, specs: &[OptionSpec]) -> Result<Config, ParseError> {
+        let args = std::iter::once(String::new())
+            .chain(split_args(line)?)
+            .collect();
+        Config::parse(args, specs, &[], &Settings::default())
+    }
+
+    /// Same as Config::new_with_commands but uses the arguments passed to the program (env::args)
+    pub fn new_env_with_commands(
+        specs: &[OptionSpec],
+        commands: &[Command],
+    ) -> Result<Config, ParseError> {
+        Config::parse(
+            std::env::args().collect(),
+            specs,
+            commands,
+            &Settings::default(),
+        )
+    }
+
+    /// Parses config from custom supplied arguments, recognizing subcommands<br>
+    /// The first positional argument, given before any value has been collected by an option, is matched against the names of commands<br>
+    /// If it matches, the remaining arguments are parsed with the specs of that command<br>
+    /// The matched command and its config can be accessed with the subcommand() method<br>
+    pub fn new_with_commands(
+        args: &[&str],
+        specs: &[OptionSpec],
+        commands: &[Command],
+    ) -> Result<Config, ParseError> {
+        Config::parse(
+            args.iter().map(|arg| arg.to_string()).collect(),
+            specs,
+            commands,
+            &Settings::default(),
+        )
+    }
+
+    /// Same as Config::new_with_prefix but uses the arguments passed to the program (env::args)
+    pub fn new_env_with_prefix(prefix: &str, specs: &[OptionSpec]) -> Result<Config, ParseError> {
+        Config::parse(
+            std::env::args().collect(),
+            specs,
+            &[],
+            &Settings::with_prefix(prefix),
+        )
+    }
+
+    /// Parses config from custom supplied arguments, reading options that weren't given from the environment<br>
+    /// Every named option is mapped to the variable `<PREFIX>_<NAME>`, e.g; `--dry-run` to `MYAPP_DRY_RUN`<br>
+    /// A variable set explicitly on a spec with env() takes precedence over the mapped name<br>
+    pub fn new_with_prefix(
+        args: &[&str],
+        prefix: &str,
+        specs: &[OptionSpec],
+    ) -> Result<Config, ParseError> {
+        Config::parse(
+            args.iter().map(|arg| arg.to_string()).collect(),
+            specs,
+            &[],
+            &Settings::with_prefix(prefix),
+        )
+    }
+
+    /// Same as Config::new_with_file but uses the arguments passed to the program (env::args)
+    #[cfg(feature = "toml")]
+    pub fn with_file<P: AsRef<Path>>(path: P, specs: &[OptionSpec]) -> Result<Config, ParseError> {
+        Config::parse(
+            std::env::args().collect(),
+            specs,
+            &[],
+            &Settings::with_file(path.as_ref(), specs)?,
+        )
+    }
+
+    /// Parses config from custom supplied arguments, reading options that weren't given from a TOML file<br>
+    /// Each top level key of the file is the name of an option, e.g; `output = "out.txt"`<br>
+    /// Strings, numbers and arrays of them are taken as values, a boolean enables or disables a switch<br>
+    /// The precedence is command line, environment variable, config file and last default values<br>
+    /// A missing file is treated as empty, an unknown key or a malformed file returns Err<br>
+    #[cfg(feature = "toml")]
+    pub fn new_with_file<P: AsRef<Path>>(
+        args: &[&str],
+        path: P,
+        specs: &[OptionSpec],
+    ) -> Result<Config, ParseError> {
+        Config::parse(
+            args.iter().map(|arg| arg.to_string()).collect(),
+            specs,
+            &[],
+            &Settings::with_file(path.as_ref(), specs)?,
+        )
+    }
+
+    /// Generates a one line synopsis of the program, e.g; `myapp [OPTIONS] --output <VALUE> <FILES>...`<br>
+    /// The required options are listed with their values, the other options are summarized as `[OPTIONS]`<br>
+    /// The values are rendered from the OptionPolicy as `<VALUE>`, `[VALUE]` if optional and followed by `...` if repeated, see OptionSpec::value_name<br>
+    /// The positional arguments are listed by name after the options, the hidden options are left out
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::positional("FILES", "Input files", true, OptionPolicy::AtLeast(1)),
+    ///     OptionSpec::new('o', "output", "Output file", true, OptionPolicy::Exact(1)),
+    ///     OptionSpec::new('v', "verbose", "Shows verbose output", false, OptionPolicy::Exact(0)),
+    /// ];
+    ///
+    /// assert_eq!(
+    ///     Config::generate_synopsis("./target/myapp", &specs),
+    ///     "myapp [OPTIONS] --output <VALUE> <FILES>..."
+    /// );
+    /// ```
+    pub fn generate_synopsis(program: &str, specs: &[OptionSpec]) -> String {
+        let mut synopsis = Path::new(program)
+            .file_name()
+            .map_or(program.into(), |name| name.to_string_lossy())
+            .into_owned();
+
+        let named = specs
+            .iter()
+            .filter(|spec| !spec.positional && !spec.trailing && !spec.hidden);
+        if named.clone().any(|spec| !spec.required) {
+            synopsis += " [OPTIONS]";
+        }
+        for spec in named.filter(|spec| spec.required) {
+            match (spec.long, spec.abrev) {
+                (false, Some(abrev)) => synopsis += &format!(" -{}", abrev),
+                _ => synopsis += &format!(" --{}", spec.name),
+            }
+            if !spec.takes_no_values() {
+                synopsis += &format!(" {}", spec.value_placeholder(false));
+            }
+        }
+
+        for spec in specs
+            .iter()
+            .filter(|spec| (spec.positional || spec.trailing) && !spec.hidden)
+            .filter(|spec| spec.policy.max() != Some(0))
+        {
+            let name = match spec.name.as_ref() {
+                UNNAMED => "ARGS",
+                name => name,
+            };
+            if spec.trailing {
+                synopsis += " --";
+            }
+            synopsis += &format!(" {}", spec.policy.placeholder(name, spec.required));
+        }
+
+        synopsis
+    }
+
+    /// Generates a usage string from supplied specs
+    /// The options marked with OptionSpec::hidden are left out
+    /// The options with a section are listed under its title after the other options, see OptionSpec::section
+    // Through a combination of list_required and list_unrequired you can configure it to only show required options and vice versa
+    pub fn generate_usage(
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+    ) -> String {
+        Config::generate_usage_with(specs, list_required, list_unrequired, &English)
+    }
+
+    /// Generates a usage string like Config::generate_usage, with the labels and markers of messages
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, English, OptionPolicy, OptionSpec};
+    /// let specs = [OptionSpec::new('o', "output", "Output file", true, OptionPolicy::Exact(1))];
+    ///
+    /// let usage = Config::generate_usage_with(&specs, true, true, &English);
+    /// assert!(usage.starts_with("    -o, --output <VALUE> [required]\n"));
+    /// ```
+    pub fn generate_usage_with(
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+        messages: &dyn Messages,
+    ) -> String {
+        let mut usage = Vec::new();
+        Config::write_usage_with(&mut usage, specs, list_required, list_unrequired, messages)
+            .expect("Writing to a Vec doesn't fail");
+        String::from_utf8(usage).expect("The usage is valid UTF-8")
+    }
+
+    /// Writes the usage of Config::generate_usage to w, one entry at a time<br>
+    /// Returns Err if writing fails, e.g; when stdout is closed
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1))];
+    ///
+    /// let mut stdout = std::io::stdout().lock();
+    /// Config::write_usage(&mut stdout, &specs, true, true).unwrap();
+    /// ```
+    pub fn write_usage<W: Write>(
+        w: &mut W,
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+    ) -> std::io::Result<()> {
+        Config::write_usage_with(w, specs, list_required, list_unrequired, &English)
+    }
+
+    /// Writes the usage of Config::generate_usage_with to w, one entry at a time<br>
+    /// Returns Err if writing fails, e.g; when stdout is closed
+    pub fn write_usage_with<W: Write>(
+        w: &mut W,
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+        messages: &dyn Messages,
+    ) -> std::io::Result<()> {
+        Config::write_rendered_usage(
+            w,
+            specs,
+            list_required,
+            list_unrequired,
+            false,
+            UsageOrder::Required,
+            |spec| spec.usage_entry(false, messages),
+        )
+    }
+
+    /// Generates a usage string like Config::generate_usage, colored with ANSI escape codes if color is enabled<br>
+    /// The option names and section titles are bold and the required markers red, see ColorChoice
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{ColorChoice, Config, OptionPolicy, OptionSpec};
+    /// let specs = [OptionSpec::new('o', "output", "Output file", true, OptionPolicy::Exact(1))];
+    ///
+    /// let usage = Config::generate_usage_colored(&specs, true, true, ColorChoice::Always);
+    /// assert!(usage.starts_with("    \x1b[1m-o, --output <VALUE>\x1b[0m \x1b[31m[required]\x1b[0m\n"));
+    /// ```
+    pub fn generate_usage_colored(
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+        color: ColorChoice,
+    ) -> String {
+        Config::generate_usage_colored_with(specs, list_required, list_unrequired, color, &English)
+    }
+
+    /// Generates a usage string like Config::generate_usage_colored, with the labels and markers of messages
+    pub fn generate_usage_colored_with(
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+        color: ColorChoice,
+        messages: &dyn Messages,
+    ) -> String {
+        let color = color.enabled();
+        Config::render_usage(
+            specs,
+            list_required,
+            list_unrequired,
+            color,
+            UsageOrder::Required,
+            |spec| spec.usage_entry(color, messages),
+        )
+    }
+
+    /// Generates a usage string like Config::generate_usage, fitted to width columns<br>
+    /// The names are listed in a first column, the descriptions and attributes are wrapped and aligned in a second column<br>
+    /// The width of the terminal is used if width is None, see terminal_width
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('o', "output", "Where to write the result of the run", false, OptionPolicy::Exact(1)),
+    ///     OptionSpec::new('v', "verbose", "Prints more", false, OptionPolicy::Exact(0)),
+    /// ];
+    ///
+    /// let usage = Config::generate_usage_wrapped(&specs, true, true, Some(52));
+    /// assert!(usage.starts_with("    -o, --output <VALUE>  Where to write the result\n                          of the run\n"));
+    /// ```
+    pub fn generate_usage_wrapped(
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+        width: Option<usize>,
+    ) -> String {
+        Config::generate_usage_wrapped_with(specs, list_required, list_unrequired, width, &English)
+    }
+
+    /// Generates a usage string like Config::generate_usage_wrapped, with the labels and markers of messages
+    pub fn generate_usage_wrapped_with(
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+        width: Option<usize>,
+        messages: &dyn Messages,
+    ) -> String {
+        let width = width.unwrap_or_else(terminal_width);
+        let layout = UsageLayout {
+            column: Config::usage_column(specs.iter(), width),
+            width,
+            color: false,
+            env_prefix: None,
+            long: false,
+            messages,
+        };
+        Config::render_usage(
+            specs,
+            list_required,
+            list_unrequired,
+            false,
+            UsageOrder::Required,
+            |spec| spec.usage_columns(&layout),
+        )
+    }
+
+    // The column where the descriptions start, after the longest names and a gap of two
+    // At most half the width, longer names are followed by the description on the next line
+    fn usage_column<'a>(specs: impl Iterator<Item = &'a OptionSpec>, width: usize) -> usize {
+        let longest = specs
+            .filter(|spec| !spec.hidden)
+            .map(|spec| spec.usage_names().chars().count() + 4)
+            .max()
+            .unwrap_or(0);
+        (longest + 2).min(width / 2)
+    }
+
+    // Lists the specs rendered with render, grouped by section
+    fn render_usage(
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+        color: bool,
+        order: UsageOrder,
+        render: impl Fn(&OptionSpec) -> String,
+    ) -> String {
+        let mut usage = Vec::new();
+        Config::write_rendered_usage(
+            &mut usage,
+            specs,
+            list_required,
+            list_unrequired,
+            color,
+            order,
+            render,
+        )
+        .expect("Writing to a Vec doesn't fail");
+        String::from_utf8(usage).expect("The usage is valid UTF-8")
+    }
+
+    // Writes the specs rendered with render to w, grouped by section
+    fn write_rendered_usage<W: Write>(
+        w: &mut W,
+        specs: &[OptionSpec],
+        list_required: bool,
+        list_unrequired: bool,
+        color: bool,
+        order: UsageOrder,
+        render: impl Fn(&OptionSpec) -> String,
+    ) -> std::io::Result<()> {
+        // The sections in the order they first appear, None for the options without a section
+        let mut sections: Vec<Option<&str>> = vec![None];
+        for section in specs.iter().filter_map(|spec| spec.section.as_deref()) {
+            if !sections.contains(&Some(section)) {
+                sections.push(Some(section));
+            }
+        }
+
+        // The last entry written
+        let mut last: Option<String> = None;
+        for section in sections {
+            let mut entries: Vec<&OptionSpec> = specs
+                .iter()
+                .filter(|spec| spec.section.as_deref() == section && !spec.hidden)
+                .filter(|spec| match spec.required {
+                    true => list_required,
+                    false => list_unrequired,
+                })
+                .collect();
+            match order {
+                UsageOrder::Required => entries.sort_by_key(|spec| !spec.required),
+                UsageOrder::Declaration => (),
+                UsageOrder::Alphabetical => entries.sort_by_key(|spec| spec.name.to_lowercase()),
+            }
+            // The sorts are stable, the entries without a key keep their order after the others
+            entries.sort_by_key(|spec| spec.display_order.unwrap_or(usize::MAX));
+
+            if let (Some(title), false) = (section, entries.is_empty()) {
+                // Entries without a trailing blank line are separated from the title
+                if last.as_ref().is_some_and(|last| !last.ends_with("\n\n")) {
+                    writeln!(w)?;
+                }
+                writeln!(w, "{}:", paint(title, STYLE_SECTION, color))?;
+            }
+            for spec in entries {
+                let entry = render(spec);
+                w.write_all(entry.as_bytes())?;
+                last = Some(entry);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Parses config from passed arguments
+    // The first argument is taken as the command
+    fn parse(
+        args: Vec<String>,
+        specs: &[OptionSpec],
+        commands: &[Command],
+        settings: &Settings,
+    ) -> Result<Config, ParseError> {
+        validate_commands(specs, commands).map_err(ParseError::InvalidSpec)?;
+        Self::parse_command(args, specs, commands, &[], settings, 0)
+    }
+
+    // Parses the arguments of a command
+    // The global options of the parent commands are recognized as well, unless the command has an option with the same name
+    // The offset is the index of the command in the arguments of the top level, locating the arguments of errors
+    fn parse_command(
+        args: Vec<String>,
+        specs: &[OptionSpec],
+        commands: &[Command],
+        globals: &[&OptionSpec],
+        settings: &Settings,
+        offset: usize,
+    ) -> Result<Config, ParseError> {
+        // The arguments as given, for the spans of errors
+        let tokens: Vec<String> = args
+            .iter()
+            .map(|arg| match settings.os_args {
+                true => decode_os_arg(arg).to_string_lossy().into_owned(),
+                false => arg.clone(),
+            })
+            .collect();
+        let locate = |err: ParseError, start: usize| err.locate(&tokens, start, offset);
+
+        let mut args = args.into_iter();
+        let command = args.next().unwrap_or_default();
+
+        // The global options passed on to the subcommands
+        let sub_globals: Vec<&OptionSpec> = globals
+            .iter()
+            .copied()
+            .chain(specs.iter().filter(|spec| spec.global && !spec.positional))
+            .collect();
+
+        // The options added by the settings, the specs take precedence
+        let version_spec = settings.version.as_ref().map(|_| version_spec());
+        let help_spec = settings.auto_help.then(help_spec);
+        // Takes the positional arguments when no positional spec is given, rejecting any
+        let unnamed_spec = OptionSpec::new('\0', UNNAMED, "", false, OptionPolicy::Exact(0));
+
+        // For quickly locating options
+        let name_map: HashMap<&str, &OptionSpec> = version_spec
+            .iter()
+            .chain(&help_spec)
+            .chain(globals.iter().copied())
+            .chain(specs)
+            .flat_map(|spec| {
+                std::iter::once(spec.name.as_ref())
+                    .chain(spec.aliases.iter().map(|(alias, _)| alias.as_ref()))
+                    .map(move |name| (name, spec))
+            })
+            .collect();
+
+        let abrev_map: HashMap<char, &OptionSpec> = version_spec
+            .iter()
+            .chain(&help_spec)
+            .chain(globals.iter().copied())
+            .chain(specs)
+            .filter_map(|spec| spec.abrev.map(|abrev| (abrev, spec)))
+            .collect();
+
+        // The values of every occurrence of the options
+        let mut parsed: HashMap<&str, Vec<Vec<String>>> = HashMap::new();
+        let mut subcommand = None;
+        let mut external = None;
+
+        // The positional specs, including the unnamed spec, are filled in order by the arguments before any option
+        let mut positionals: Vec<&OptionSpec> =
+            specs.iter().filter(|spec| spec.positional).collect();
+        if positionals.is_empty() {
+            positionals.push(&unnamed_spec);
+        }
+        let mut current_spec: &OptionSpec = positionals[0];
+
+        let trailing = specs.iter().find(|spec| spec.trailing);
+        let mut trailing_values = None;
+
+        let mut values = Vec::new();
+        let mut positional_values = Vec::new();
+        let mut unknown = Vec::new();
+        // The switches cleared with --no-<name>
+        let mut negated = HashSet::new();
+        let mut warnings = Vec::new();
+        let mut long_help = false;
+        let mut in_finalize = false;
+        // The index of the current argument and of the argument which started the current option
+        let mut index = 0;
+        let mut current_start = 0;
+        while let Some(arg) = args.next() {
+            index += 1;
+
+            // A bare argument is positional rather than a value of the current option
+            let is_positional = current_spec.positional
+                || (current_spec.takes_no_values() && values.is_empty())
+                || ((settings.interleaved || settings.posix)
+                    && !current_spec.accepts(values.len() + 1));
+
+            // Subcommand, only recognized as the first positional argument
+            if !in_finalize
+                && is_positional
+                && positional_values.is_empty()
+                && (!current_spec.positional || values.is_empty())
+            {
+                if let Some(cmd) = commands.iter().find(|cmd| cmd.matches(&arg)) {
+                    // The config of the subcommand carries its name rather than the alias given
+                    let sub_args = std::iter::once(cmd.name.to_string())
+                        .chain(args.by_ref())
+                        .collect();
+                    subcommand = Some(Box::new(Config::parse_command(
+                        sub_args,
+                        &cmd.specs,
+                        &cmd.subcommands,
+                        &sub_globals,
+                        &settings.for_subcommand(),
+                        offset + index,
+                    )?));
+                    break;
+                }
+
+                if let Some(path) = settings
+                    .external_prefix
+                    .as_ref()
+                    .filter(|_| !arg.starts_with('-') && !arg.contains(std::path::is_separator))
+                    .and_then(|prefix| find_executable(&format!("{}-{}", prefix, arg)))
+                {
+                    let decode = |arg: String| match settings.os_args {
+                        true => decode_os_arg(&arg),
+                        false => OsString::from(arg),
+                    };
+                    external = Some(ExternalCommand {
+                        name: decode(arg).to_string_lossy().into_owned(),
+                        path,
+                        args: args.by_ref().map(decode).collect(),
+                    });
+                    break;
+                }
+            }
+
+            // The rest of the arguments are positional in POSIX mode
+            // A lone `-` is a value, by convention referring to stdin
+            // So is anything taken by an option which allows values starting with '-'
+            // A negative number is a value as well, unless its first digit is an abbreviation, e.g; `-3` or `-1.5`
+            let is_number = arg.parse::<f64>().is_ok()
+                && arg[1..].starts_with(|ch: char| {
+                    (ch.is_ascii_digit() || ch == '.') && !abrev_map.contains_key(&ch)
+                });
+            let is_option = arg.starts_with('-')
+                && arg != "-"
+                && !is_number
+                && !(current_spec.hyphen_values && current_spec.accepts(values.len() + 1));
+
+            if settings.posix && !in_finalize && is_positional && !is_option {
+                Self::collect(&mut parsed, current_spec, &mut positional_values, values)
+                    .map_err(|err| locate(err, current_start))?;
+                values = Vec::new();
+                current_spec = positionals[0];
+
+                positional_values.push(arg);
+                positional_values.extend(args.by_ref());
+                break;
+            }
+
+            // New option
+            if !in_finalize && is_option {
+                // If Final or FinalIgnore
+                match current_spec.policy {
+                    OptionPolicy::Finalize() | OptionPolicy::FinalizeIgnore() => {
+                        in_finalize = true;
+                        values.push(arg);
+                        continue;
+                    }
+                    _ => (),
+                }
+
+                // The remaining arguments are captured verbatim by the trailing spec
+                if let (Some(spec), "--") = (trailing, arg.as_str()) {
+                    trailing_values = Some((spec, index, args.by_ref().collect()));
+                    break;
+                }
+
+                // Unknown options are passed through in lenient mode
+                if settings.allow_unknown && !Self::is_known(&arg, &name_map, &abrev_map, settings)
+                {
+                    unknown.push(arg);
+                    continue;
+                }
+
+                // Collect the last option values
+                Self::collect(&mut parsed, current_spec, &mut positional_values, values)
+                    .map_err(|err| locate(err, current_start))?;
+
+                values = Vec::new();
+                current_start = index;
+
+                // Single full name argument
+                if let Some(name) = arg.strip_prefix("--") {
+                    // A value can be attached with '=', e.g; --output=out.txt
+                    let (name, value) = match name.split_once('=') {
+                        Some((name, value)) => (name, Some(value)),
+                        None => (name, None),
+                    };
+
+                    // A negatable switch is cleared by --no-<name>
+                    if let Some(spec) = Self::lookup_negated(name, &name_map, settings)
+                        .map_err(|err| locate(err, index))?
+                    {
+                        if value.is_some() {
+                            return Err(locate(
+                                ParseError::UnexpectedValue {
+                                    name: name.to_string(),
+                                    span: None,
+                                },
+                                index,
+                            ));
+                        }
+                        parsed.remove(spec.name.as_ref());
+                        negated.insert(spec.name.as_ref());
+                        current_spec = positionals[0];
+                        continue;
+                    }
+
+                    current_spec = match Self::lookup_long(name, &name_map, settings)
+                        .map_err(|err| locate(err, index))?
+                    {
+                        Some(spec) => spec,
+                        None => {
+                            return Err(locate(
+                                ParseError::UnknownOption {
+                                    suggestion: Self::suggest(name, &name_map).map(String::from),
+                                    token: arg,
+                                    span: None,
+                                },
+                                index,
+                            ))
+                        }
+                    };
+
+                    negated.remove(current_spec.name.as_ref());
+                    if help_spec
+                        .as_ref()
+                        .is_some_and(|spec| std::ptr::eq(spec, current_spec))
+                    {
+                        long_help = true;
+                    }
+                    if !name_map.contains_key(name) {
+                        warnings.push(ParseWarning::Corrected {
+                            given: name.to_string(),
+                            name: current_spec.name.to_string(),
+                        });
+                    }
+
+                    if let Some(value) = value {
+                        values.push(value.to_string());
+                    }
+                }
+                // One or more abbreviated options
+                else {
+                    let options: Vec<_> = arg.chars().skip(1).collect();
+                    let mut last: Option<&OptionSpec> = None;
+
+                    // The values after a group of abbreviated options refer to the last option
+                    for (position, option) in options.iter().enumerate() {
+                        if let Some(spec) = last {
+                            // The rest of the group is a value attached to the previous option, e.g; -ooutput.txt or -n3
+                            // Only if the option accepts values and the rest isn't made up of known abbreviations
+                            if !spec.takes_no_values()
+                                && options[position..]
+                                    .iter()
+                                    .any(|ch| !abrev_map.contains_key(ch))
+                            {
+                                values.push(options[position..].iter().collect());
+                                break;
+                            }
+
+                            Self::insert_values(&mut parsed, spec, vec![])
+                                .map_err(|err| locate(err, index))?;
+                        }
+
+                        last = match abrev_map.get(option) {
+                            Some(spec) => {
+                                negated.remove(spec.name.as_ref());
+                                Some(spec)
+                            }
+                            None if settings.allow_unknown => {
+                                unknown.push(format!("-{}", option));
+                                None
+                            }
+                            None => {
+                                return Err(locate(
+                                    ParseError::UnknownOption {
+                                        token: format!("-{}", option),
+                                        suggestion: None,
+                                        span: None,
+                                    },
+                                    index,
+                                ))
+                            }
+                        };
+                    }
+
+                    // The last option is set to collect the values following
+                    // The following values are positional if the group ended with an unknown option
+                    current_spec = last.unwrap_or(positionals[0]);
+                }
+                continue;
+            }
+
+            if settings.interleaved && is_positional && !current_spec.positional {
+                positional_values.push(arg);
+            } else {
+                values.push(arg);
+            }
+        }
+
+        // Collect what remains
+        Self::collect(&mut parsed, current_spec, &mut positional_values, values)
+            .map_err(|err| locate(err, current_start))?;
+        // Only a rejected value can be traced back to a single positional argument
+        // Without any positional arguments the counts are checked with the required options
+        let no_positionals = positional_values.is_empty();
+        if let (Some(token), true) = (
+            positional_values.first(),
+            std::ptr::eq(positionals[0], &unnamed_spec),
+        ) {
+            return Err(locate(
+                ParseError::UnexpectedArgument {
+                    token: token.clone(),
+                    span: None,
+                },
+                1,
+            ));
+        }
+        if !no_positionals {
+            Self::insert_positionals(&mut parsed, &positionals, positional_values).map_err(
+                |err| match err {
+                    ParseError::InvalidValue { .. } => locate(err, 1),
+                    err => err,
+                },
+            )?;
+        }
+
+        if let Some((spec, start, values)) = trailing_values {
+            let values = spec.enforce(values).map_err(|err| locate(err, start))?;
+            spec.notify(&values);
+            parsed.insert(spec.name.as_ref(), vec![values]);
+        }
+
+        // An exclusive option has to be the only thing given on the command line
+        // The options are taken in the order of the specs, so the error is the same between runs
+        let given: Vec<&OptionSpec> = version_spec
+            .iter()
+            .chain(&help_spec)
+            .chain(globals.iter().copied())
+            .chain(specs)
+            .filter(|spec| parsed.contains_key(spec.name.as_ref()))
+            .collect();
+        let exclusive = given.iter().find(|spec| spec.exclusive);
+        if let Some(spec) = exclusive {
+            let other = given
+                .iter()
+                .find(|other| other.name != spec.name)
+                .map(|other| other.name.to_string())
+                .or_else(|| subcommand.as_ref().map(|sub| sub.command.clone()));
+            if let Some(other) = other {
+                return Err(ParseError::ExclusiveOption {
+                    name: spec.name.to_string(),
+                    other,
+                });
+            }
+        }
+
+        // The global options given after the subcommand take precedence
+        if let Some(sub) = &subcommand {
+            for spec in &sub_globals {
+                let (occurrences, values_os) = match (
+                    sub.occurrences(spec.name.as_ref()),
+                    sub.option_os(spec.name.as_ref()),
+                ) {
+                    (Some(occurrences), Some(values_os)) => (occurrences, values_os),
+                    _ => continue,
+                };
+                let occurrences = match settings.os_args {
+                    true => {
+                        let mut values = values_os.iter().map(|v| encode_os_arg(v));
+                        occurrences
+                            .iter()
+                            .map(|occurrence| values.by_ref().take(occurrence.len()).collect())
+                            .collect()
+                    }
+                    false => occurrences.to_vec(),
+                };
+                negated.remove(spec.name.as_ref());
+                parsed.insert(spec.name.as_ref(), occurrences);
+            }
+        }
+
+        // Everything parsed so far was given on the command line
+        let mut sources: HashMap<&str, Source> =
+            parsed.keys().map(|name| (*name, Source::Cli)).collect();
+
+        // Read the options that weren't given from their environment variables
+        for spec in specs {
+            if parsed.contains_key(spec.name.as_ref()) || negated.contains(spec.name.as_ref()) {
+                continue;
+            }
+            let var = match (&spec.env, &settings.env_prefix) {
+                (Some(var), _) => var.to_string(),
+                (None, Some(prefix)) if !spec.positional => env_var(prefix, &spec.name),
+                _ => continue,
+            };
+            if let Some(values) = spec.env_values(&var) {
+                let values = spec.enforce(values)?;
+                spec.notify(&values);
+                parsed.insert(spec.name.as_ref(), vec![values]);
+                sources.insert(spec.name.as_ref(), Source::Env(var));
+            }
+        }
+
+        // Fill in the options that weren't given from the config file
+        for (name, values) in &settings.file_values {
+            if parsed.contains_key(name.as_str()) || negated.contains(name.as_str()) {
+                continue;
+            }
+            if let Some(spec) = name_map.get(name.as_str()) {
+                let values = spec.enforce(values.clone())?;
+                spec.notify(&values);
+                parsed.insert(spec.name.as_ref(), vec![values]);
+                sources.insert(
+                    spec.name.as_ref(),
+                    Source::File(settings.file_path.clone().unwrap_or_default()),
+                );
+            }
+        }
+
+        // Default values don't count as using a deprecated option
+        warnings.extend(
+            specs
+                .iter()
+                .filter(|spec| parsed.contains_key(spec.name.as_ref()))
+                .filter_map(|spec| {
+                    spec.deprecated
+                        .as_ref()
+                        .map(|note| ParseWarning::Deprecated {
+                            name: spec.name.to_string(),
+                            note: note.to_string(),
+                        })
+                }),
+        );
+
+        // Variables with the prefix are checked once, against the options of every command
+        if let (Some(prefix), 0) = (&settings.env_prefix, offset) {
+            let mut known = HashSet::new();
+            known_env_vars(prefix, specs, commands, &mut known);
+            let mut unknown_vars: Vec<String> = std::env::vars_os()
+                .filter_map(|(var, _)| var.into_string().ok())
+                .filter(|var| var.starts_with(&format!("{}_", prefix)) && !known.contains(var))
+                .collect();
+            unknown_vars.sort();
+            warnings.extend(
+                unknown_vars
+                    .into_iter()
+                    .map(|var| ParseWarning::UnknownEnv { var }),
+            );
+        }
+
+        // Fill in the default values of the options that weren't given
+        for spec in specs
+            .iter()
+            .filter(|spec| !spec.defaults.is_empty() && !negated.contains(spec.name.as_ref()))
+        {
+            parsed.entry(spec.name.as_ref()).or_insert_with(|| {
+                sources.insert(spec.name.as_ref(), Source::Default);
+                vec![spec.defaults.iter().map(|v| v.to_string()).collect()]
+            });
+        }
+
+        // An added option is only requested if no spec replaced it
+        let requested = |added: &Option<OptionSpec>| {
+            added.as_ref().is_some_and(|spec| {
+                parsed.contains_key(spec.name.as_ref())
+                    && name_map
+                        .get(spec.name.as_ref())
+                        .is_some_and(|found| std::ptr::eq(*found, spec))
+            })
+        };
+        let version_requested = requested(&version_spec);
+        let help_requested = requested(&help_spec);
+
+        let stopped = matches!(current_spec.policy, OptionPolicy::FinalizeIgnore())
+            || exclusive.is_some()
+            || subcommand.as_ref().is_some_and(|sub| sub.stopped);
+
+        // The default subcommand is taken when none was given
+        if let Some(cmd) = commands
+            .iter()
+            .find(|cmd| cmd.default)
+            .filter(|_| !stopped && subcommand.is_none() && external.is_none())
+        {
+            subcommand = Some(Box::new(Config::parse_command(
+                vec![cmd.name.to_string()],
+                &cmd.specs,
+                &cmd.subcommands,
+                &sub_globals,
+                &settings.for_subcommand(),
+                offset + index,
+            )?));
+        }
+
+        // Check all required options where specified or Err if not in Finalgnore
+        if !stopped {
+            // The minimum count of a trailing spec also applies when no '--' was given
+            if let Some(spec) = trailing.filter(|spec| !parsed.contains_key(spec.name.as_ref())) {
+                spec.enforce(Vec::new())?;
+            }
+            if no_positionals {
+                for spec in &positionals {
+                    if parsed.contains_key(spec.name.as_ref()) {
+                        continue;
+                    }
+                    let values = spec.enforce(Vec::new())?;
+                    // The unnamed arguments are present without any values, as long as they accept none
+                    if spec.name == UNNAMED && !std::ptr::eq(*spec, &unnamed_spec) {
+                        spec.notify(&values);
+                        parsed.insert(spec.name.as_ref(), vec![values]);
+                        sources.insert(spec.name.as_ref(), Source::Cli);
+                    }
+                }
+            }
 
-        // Check all required options where specified or Err if not in Finalgnore
-        if let OptionPolicy::FinalizeIgnore() = current_spec.policy {
-        } else {
             for required in specs.iter().filter(|spec| spec.required) {
-                if let None = parsed.get(required.name) {
-                    return Err(format!("Missing required option '{}'", required.name));
+                if !parsed.contains_key(required.name.as_ref()) {
+                    let values = match settings.prompt_missing && can_prompt(required, settings) {
+                        true => prompt_values(required, settings)?,
+                        false => {
+                            return Err(ParseError::MissingRequired {
+                                name: required.name.to_string(),
+                            })
+                        }
+                    };
+                    required.notify(&values);
+                    parsed.insert(required.name.as_ref(), vec![values]);
+                    sources.insert(required.name.as_ref(), Source::Prompt);
+                }
+            }
+
+            // Check the conditionally required options
+            for spec in specs
+                .iter()
+                .filter(|spec| !parsed.contains_key(spec.name.as_ref()))
+            {
+                if !spec.required_unless.is_empty()
+                    && !spec
+                        .required_unless
+                        .iter()
+                        .any(|name| parsed.contains_key(name.as_ref()))
+                {
+                    return Err(ParseError::RequiredUnless {
+                        name: spec.name.to_string(),
+                        unless: spec.required_unless.iter().map(|v| v.to_string()).collect(),
+                    });
+                }
+
+                if let Some(name) = spec
+                    .required_if
+                    .iter()
+                    .find(|name| parsed.contains_key(name.as_ref()))
+                {
+                    return Err(ParseError::RequiredIf {
+                        name: spec.name.to_string(),
+                        given: name.to_string(),
+                    });
+                }
+            }
+
+            // Check the dependencies of the given options
+            for spec in specs
+                .iter()
+                .filter(|spec| parsed.contains_key(spec.name.as_ref()))
+            {
+                if let Some(missing) = spec
+                    .requires
+                    .iter()
+                    .find(|name| !parsed.contains_key(name.as_ref()))
+                {
+                    return Err(ParseError::MissingDependency {
+                        name: spec.name.to_string(),
+                        requires: missing.to_string(),
+                    });
+                }
+            }
+        }
+
+        // Restores the values of OS arguments, the String values are converted lossily
+        let decode = |value: &String| match settings.os_args {
+            true => decode_os_arg(value),
+            false => OsString::from(value),
+        };
+        let parsed_os = parsed
+            .iter()
+            .map(|(name, values)| {
+                (
+                    name.to_string(),
+                    values.iter().flatten().map(decode).collect(),
+                )
+            })
+            .collect();
+        let lossy = |value: String| match settings.os_args {
+            true => decode_os_arg(&value).to_string_lossy().into_owned(),
+            false => value,
+        };
+        let occurrences: HashMap<String, Vec<Vec<String>>> = parsed
+            .into_iter()
+            .map(|(name, values)| {
+                let values = values
+                    .into_iter()
+                    .map(|occurrence| occurrence.into_iter().map(lossy).collect())
+                    .collect();
+                (name.to_string(), values)
+            })
+            .collect();
+        let parsed: HashMap<String, Vec<String>> = occurrences
+            .iter()
+            .map(|(name, values)| (name.clone(), values.concat()))
+            .collect();
+
+        let mut maps = HashMap::new();
+        for spec in specs.iter().filter(|spec| spec.key_value.is_some()) {
+            if let Some(values) = parsed.get(spec.name.as_ref()) {
+                maps.insert(spec.name.to_string(), spec.key_values(values)?);
+            }
+        }
+
+        let mut config = Config {
+            command: lossy(command),
+            parsed,
+            parsed_os,
+            occurrences,
+            subcommand,
+            external,
+            unknown: unknown.into_iter().map(lossy).collect(),
+            warnings,
+            maps,
+            sources: sources
+                .into_iter()
+                .map(|(name, source)| (name.to_string(), source))
+                .collect(),
+            negated: negated.into_iter().map(str::to_string).collect(),
+            stopped,
+            help_requested,
+            long_help,
+            version_requested,
+        };
+
+        // The global options are visible on the configs of the subcommands
+        for spec in &sub_globals {
+            if let (Some(values), Some(values_os), Some(occurrences), Some(source)) = (
+                config.parsed.get(spec.name.as_ref()),
+                config.parsed_os.get(spec.name.as_ref()),
+                config.occurrences.get(spec.name.as_ref()),
+                config.sources.get(spec.name.as_ref()),
+            ) {
+                let (values, values_os, occurrences, source) = (
+                    values.clone(),
+                    values_os.clone(),
+                    occurrences.clone(),
+                    source.clone(),
+                );
+                let mut current = &mut config.subcommand;
+                while let Some(sub) = current {
+                    sub.parsed.insert(spec.name.to_string(), values.clone());
+                    sub.parsed_os
+                        .insert(spec.name.to_string(), values_os.clone());
+                    sub.occurrences
+                        .insert(spec.name.to_string(), occurrences.clone());
+                    sub.sources.insert(spec.name.to_string(), source.clone());
+                    current = &mut sub.subcommand;
                 }
             }
         }
 
-        Ok(Config { command, parsed })
+        Ok(config)
+    }
+
+    // Finds the name or alias of a full name option closest to a mistyped name
+    // Returns None if no name is similar enough
+    fn suggest<'a>(name: &str, name_map: &HashMap<&'a str, &OptionSpec>) -> Option<&'a str> {
+        // Allow roughly one typo per three characters
+        let max_distance = std::cmp::max(1, name.chars().count() / 3);
+        name_map
+            .iter()
+            .filter(|(_, spec)| spec.long && !spec.hidden)
+            .map(|(candidate, _)| (edit_distance(name, candidate), *candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min()
+            .map(|(_, candidate)| candidate)
+    }
+
+    // Finds the spec of a full name option by its name or alias
+    // Depending on the settings the name is matched case insensitively or as an unambiguous prefix
+    // Returns Err listing the candidates if the prefix is ambiguous
+    fn lookup_long<'s>(
+        name: &str,
+        name_map: &HashMap<&str, &'s OptionSpec>,
+        settings: &Settings,
+    ) -> Result<Option<&'s OptionSpec>, ParseError> {
+        if let Some(spec) = name_map.get(name).filter(|spec| spec.long) {
+            return Ok(Some(spec));
+        }
+
+        if !settings.case_insensitive && !settings.prefix_matching {
+            return Ok(None);
+        }
+
+        let matches = |candidate: &str| match (settings.case_insensitive, settings.prefix_matching)
+        {
+            (true, true) => candidate.to_lowercase().starts_with(&name.to_lowercase()),
+            (true, false) => candidate.to_lowercase() == name.to_lowercase(),
+            _ => candidate.starts_with(name),
+        };
+
+        let mut candidates: Vec<(&str, &OptionSpec)> = name_map
+            .iter()
+            .filter(|(candidate, spec)| spec.long && matches(candidate))
+            .map(|(candidate, spec)| (*candidate, *spec))
+            .collect();
+        candidates.sort_by_key(|(candidate, _)| *candidate);
+
+        // Several aliases of the same option are not ambiguous
+        let spec = match candidates.first() {
+            Some((_, spec)) => *spec,
+            None => return Ok(None),
+        };
+
+        if candidates
+            .iter()
+            .all(|(_, other)| std::ptr::eq(spec, *other))
+        {
+            return Ok(Some(spec));
+        }
+
+        Err(ParseError::AmbiguousOption {
+            token: format!("--{}", name),
+            candidates: candidates
+                .iter()
+                .map(|(candidate, _)| candidate.to_string())
+                .collect(),
+            span: None,
+        })
+    }
+
+    // Finds the negatable switch cleared by a full name of the form no-<name>
+    // A spec named no-<name> itself takes precedence
+    fn lookup_negated<'s>(
+        name: &str,
+        name_map: &HashMap<&str, &'s OptionSpec>,
+        settings: &Settings,
+    ) -> Result<Option<&'s OptionSpec>, ParseError> {
+        let name = match name.strip_prefix("no-") {
+            Some(negated) if !name_map.get(name).is_some_and(|spec| spec.long) => negated,
+            _ => return Ok(None),
+        };
+        Ok(Self::lookup_long(name, name_map, settings)?
+            .filter(|spec| spec.negatable && spec.takes_no_values()))
+    }
+
+    // Returns true if the option argument refers to a spec, for a group of abbreviated options the first is checked
+    // An ambiguous name is considered known so the ambiguity is reported
+    fn is_known(
+        arg: &str,
+        name_map: &HashMap<&str, &OptionSpec>,
+        abrev_map: &HashMap<char, &OptionSpec>,
+        settings: &Settings,
+    ) -> bool {
+        match arg.strip_prefix("--") {
+            Some(name) => {
+                let name = name.split_once('=').map_or(name, |(name, _)| name);
+                !matches!(Self::lookup_long(name, name_map, settings), Ok(None))
+                    || !matches!(Self::lookup_negated(name, name_map, settings), Ok(None))
+            }
+            None => arg
+                .chars()
+                .nth(1)
+                .is_some_and(|abrev| abrev_map.contains_key(&abrev)),
+        }
+    }
+
+    // Enforces and inserts the values collected for an option
+    // The values collected for the positional specs are set aside to be distributed among them when all are known
+    fn collect<'a>(
+        map: &mut HashMap<&'a str, Vec<Vec<String>>>,
+        spec: &'a OptionSpec,
+        positional_values: &mut Vec<String>,
+        values: Vec<String>,
+    ) -> Result<(), ParseError> {
+        if spec.positional {
+            positional_values.extend(values);
+            return Ok(());
+        }
+
+        let values = match values.is_empty() {
+            true => spec.missing.iter().map(|v| v.to_string()).collect(),
+            false => values,
+        };
+        let values = spec.enforce(values)?;
+        Self::insert_values(map, spec, values)
+    }
+
+    // Distributes the positional arguments over the positional specs in order
+    // Each spec takes as many values as its policy accepts while leaving enough for the minimum of the following specs
+    // The last spec takes what remains, a spec without values isn't inserted
+    fn insert_positionals<'a>(
+        map: &mut HashMap<&'a str, Vec<Vec<String>>>,
+        positionals: &[&'a OptionSpec],
+        values: Vec<String>,
+    ) -> Result<(), ParseError> {
+        let mut remaining = values.len();
+        let mut values = values.into_iter();
+
+        for (index, spec) in positionals.iter().enumerate() {
+            let count = if index == positionals.len() - 1 {
+                remaining
+            } else {
+                let reserved: usize = positionals[index + 1..]
+                    .iter()
+                    .map(|spec| spec.policy.min())
+                    .sum();
+                remaining
+                    .saturating_sub(reserved)
+                    .min(spec.policy.max().unwrap_or(usize::MAX))
+            };
+
+            remaining -= count;
+            let taken = spec.enforce(values.by_ref().take(count).collect())?;
+            if !taken.is_empty() {
+                Self::insert_values(map, spec, taken)?;
+            }
+        }
+
+        Ok(())
     }
 
-    // Checks if option is already present before inserting and return Err
+    // Inserts the values of an option, handling an option that is already present according to its DuplicatePolicy
     // If spec required Exact(0) it won't return Err
-    fn insert_non_duplicate(
-        map: &mut HashMap<&str, Vec<String>>,
-        spec: &OptionSpec,
+    fn insert_values<'a>(
+        map: &mut HashMap<&'a str, Vec<Vec<String>>>,
+        spec: &'a OptionSpec,
         values: Vec<String>,
-    ) -> Result<(), String> {
-        match spec.policy {
-            OptionPolicy::Exact(0) => (),
+    ) -> Result<(), ParseError> {
+        if let (true, DuplicatePolicy::Error) =
+            (map.contains_key(spec.name.as_ref()), &spec.duplicates)
+        {
+            if !spec.takes_no_values() {
+                return Err(ParseError::DuplicateOption {
+                    name: spec.name.to_string(),
+                    span: None,
+                });
+            }
+        }
+
+        spec.notify(&values);
+        match (map.get_mut(spec.name.as_ref()), &spec.duplicates) {
+            (Some(existing), DuplicatePolicy::Append | DuplicatePolicy::Error) => {
+                existing.push(values)
+            }
             _ => {
-                if let Some(_) = map.get(spec.name) {
-                    return Err(format!("Duplicate option '{}'", spec.name));
-                }
+                map.insert(&spec.name, vec![values]);
             }
         }
 
-        map.insert(spec.name, values);
         Ok(())
     }
 
-    /// Returns the command, I.e; the first argument
+    /// Keeps a reference to the specs the config was parsed with, see SpecConfig
+    pub fn with_specs(self, specs: &[OptionSpec]) -> SpecConfig<'_> {
+        SpecConfig {
+            config: self,
+            specs,
+        }
+    }
+
+    /// Returns the command, I.e; the first argument<br>
+    /// The first argument is never part of the values of the options, it is the program path for the top level config and the name of a subcommand otherwise
     pub fn command(&self) -> &String {
         &self.command
     }
 
-    /// Returns the value[s] given to named or unnamed argument
+    /// Returns the name of the program without its directory, e.g; "myapp" when run as `./target/debug/myapp`<br>
+    /// Falls back to the command as given if it has no file name
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// # let specs = [OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0))];
+    /// let config = Config::new(&["./bin/myapp", "file"], &specs).unwrap();
+    /// assert_eq!(config.program_name(), "myapp");
+    /// assert_eq!(config.option("(unnamed)").unwrap(), ["file"]);
+    /// ```
+    pub fn program_name(&self) -> &str {
+        Path::new(&self.command)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.command)
+    }
+
+    /// Reconstructs a command line from the parsed values which parses back to the same values, e.g; to relaunch a worker with the effective configuration<br>
+    /// The command is the first argument, followed by the positional values and then every option in the order of specs<br>
+    /// Values from the environment, a config file and default values are included, the subcommand isn't<br>
+    /// An option with a single value is written as `--name=value`, keeping values starting with '-' attached<br>
+    /// A negatable switch which was cleared is written as `--no-<name>`
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+    ///     OptionSpec::new('v', "verbose", "Verbose output", false, OptionPolicy::Exact(0)),
+    /// ];
+    ///
+    /// let config = Config::new(&["./test", "a", "-vo", "out.txt"], &specs).unwrap();
+    /// assert_eq!(config.to_args(&specs), ["./test", "a", "--output=out.txt", "--verbose"]);
+    /// ```
+    pub fn to_args(&self, specs: &[OptionSpec]) -> Vec<String> {
+        let mut args = vec![self.command.clone()];
+
+        for spec in specs.iter().filter(|spec| spec.positional) {
+            args.extend(self.option(&spec.name).unwrap_or_default().iter().cloned());
+        }
+
+        for spec in specs
+            .iter()
+            .filter(|spec| !spec.positional && !spec.trailing)
+        {
+            let name = match (spec.long, spec.abrev) {
+                (false, Some(abrev)) => format!("-{}", abrev),
+                _ => format!("--{}", spec.name),
+            };
+            if self.negated.contains(spec.name.as_ref()) {
+                args.push(format!("--no-{}", spec.name));
+            }
+            for values in self.occurrences(&spec.name).unwrap_or_default() {
+                match (values.as_slice(), spec.delimiter) {
+                    ([], _) => args.push(name.clone()),
+                    ([value], _) if spec.long => args.push(format!("{}={}", name, value)),
+                    (values, Some(delimiter)) if spec.long => {
+                        args.push(format!("{}={}", name, values.join(&delimiter.to_string())))
+                    }
+                    (values, _) => {
+                        args.push(name.clone());
+                        args.extend(values.iter().cloned());
+                    }
+                }
+            }
+        }
+
+        for spec in specs.iter().filter(|spec| spec.trailing) {
+            if let Some(values) = self.option(&spec.name) {
+                args.push(String::from("--"));
+                args.extend(values.iter().cloned());
+            }
+        }
+
+        args
+    }
+
+    /// Returns the value[s] given to named, positional or unnamed argument
+    /// Falls back to the environment variable and then the default values of the spec if the argument wasn't given
     /// Returns None if argument didn't exist
     pub fn option(&self, name: &str) -> Option<&[String]> {
         match self.parsed.get(name) {
@@ -327,4 +4234,410 @@ impl Config {
             None => None,
         }
     }
+
+    /// Returns true if a switch was given, shorthand for `config.option(name).is_some()`<br>
+    /// A switch cleared with `--no-<name>` or disabled in the environment or config file returns false
+    pub fn flag(&self, name: &str) -> bool {
+        self.parsed.contains_key(name)
+    }
+
+    /// Returns the value of an option taking a single value<br>
+    /// Returns None if the option wasn't given or has no values<br>
+    /// Panics naming the option if it has several values, use Config::option for those
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// # let specs = [
+    /// #     OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0)),
+    /// #     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+    /// # ];
+    /// let config = Config::new(&["./test", "-o", "out.txt"], &specs).unwrap();
+    /// assert_eq!(config.single("output"), Some("out.txt"));
+    /// ```
+    pub fn single(&self, name: &str) -> Option<&str> {
+        match self.option(name) {
+            None | Some([]) => None,
+            Some([value]) => Some(value),
+            Some(values) => panic!(
+                "Option '{}' has {} values, expected a single value",
+                name,
+                values.len()
+            ),
+        }
+    }
+
+    /// Returns true if the option was given, read from the environment or a config file, or has default values
+    pub fn contains(&self, name: &str) -> bool {
+        self.parsed.contains_key(name)
+    }
+
+    /// Returns the number of options with values, see Config::contains
+    pub fn len(&self) -> usize {
+        self.parsed.len()
+    }
+
+    /// Returns true if no option has values
+    pub fn is_empty(&self) -> bool {
+        self.parsed.is_empty()
+    }
+
+    /// Iterates the options with their values, ordered by name
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// # let specs = [
+    /// #     OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0)),
+    /// #     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+    /// # ];
+    /// let config = Config::new(&["./test", "-o", "out.txt"], &specs).unwrap();
+    /// for (name, values) in config.iter() {
+    ///     println!("{} = {:?}", name, values);
+    /// }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        let mut options: Vec<_> = self
+            .parsed
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.as_slice()))
+            .collect();
+        options.sort_unstable_by_key(|(name, _)| *name);
+        options.into_iter()
+    }
+
+    /// Returns the values of an option grouped by occurrence, e.g; `[["1", "2"], ["3", "4"]]` for `--point 1 2 --point 3 4`
+    /// The OptionPolicy is enforced against each occurrence, repeating an option requires DuplicatePolicy::Append
+    /// A value from the environment, config file or default values counts as a single occurrence
+    /// Returns None if argument didn't exist
+    pub fn occurrences(&self, name: &str) -> Option<&[Vec<String>]> {
+        self.occurrences.get(name).map(|values| values.as_slice())
+    }
+
+    /// Returns the value[s] of an option as OsStrings
+    /// The values are exactly as given when parsed with Config::new_os, including the parts that aren't valid unicode
+    /// Returns None if argument didn't exist
+    pub fn option_os(&self, name: &str) -> Option<&[OsString]> {
+        self.parsed_os.get(name).map(|values| values.as_slice())
+    }
+
+    /// Opens the file given as the first value of an option for reading, the value `-` refers to stdin<br>
+    /// Returns Ok(None) if the option wasn't given or has no values<br>
+    /// Returns Err if the file couldn't be opened, see input::open
+    /// ## Example
+    /// ```no_run
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// # use std::io::Read;
+    /// # let specs = [
+    /// #     OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0)),
+    /// #     OptionSpec::new('i', "input", "Input file, - for stdin", false, OptionPolicy::Exact(1)),
+    /// # ];
+    /// let config = Config::new(&["./test", "--input", "-"], &specs).unwrap();
+    /// let mut contents = String::new();
+    /// if let Some(mut reader) = config.reader_for("input").unwrap() {
+    ///     reader.read_to_string(&mut contents).unwrap();
+    /// }
+    /// ```
+    pub fn reader_for(&self, name: &str) -> std::io::Result<Option<Box<dyn std::io::Read>>> {
+        match self.option_os(name).and_then(|values| values.first()) {
+            Some(path) => crate::input::open(path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the first value of an option to T
+    /// Returns Ok(None) if the option wasn't given or has no values
+    /// Returns Err(ParseError::InvalidValue) naming the option and the value if it couldn't be parsed
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// # let specs = [
+    /// #     OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0)),
+    /// #     OptionSpec::new('n', "number", "Number of iterations", false, OptionPolicy::Exact(1)),
+    /// # ];
+    /// let config = Config::new(&["./test", "-n", "3"], &specs).unwrap();
+    /// let number: Option<u32> = config.get("number").unwrap();
+    /// assert_eq!(number, Some(3));
+    /// ```
+    pub fn get<T>(&self, name: &str) -> Result<Option<T>, ParseError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        match self.option(name).and_then(|values| values.first()) {
+            Some(value) => Self::parse_value(name, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses all values of an option to T
+    /// Returns an empty Vec if the option wasn't given
+    /// Returns Err(ParseError::InvalidValue) naming the option and the first value that couldn't be parsed
+    pub fn get_many<T>(&self, name: &str) -> Result<Vec<T>, ParseError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.option(name)
+            .unwrap_or_default()
+            .iter()
+            .map(|value| Self::parse_value(name, value))
+            .collect()
+    }
+
+    // Parses a single value of an option and describes the failure
+    fn parse_value<T>(name: &str, value: &str) -> Result<T, ParseError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        value.parse().map_err(|e: T::Err| ParseError::InvalidValue {
+            name: name.to_string(),
+            value: value.to_string(),
+            reason: e.to_string(),
+            span: None,
+        })
+    }
+
+    /// Returns the unknown options in their original order when parsed with ConfigBuilder::allow_unknown
+    pub fn unknown(&self) -> &[String] {
+        &self.unknown
+    }
+
+    /// Returns the key=value pairs given to an option specified with OptionSpec::key_value
+    /// Returns None if the option wasn't given or isn't a key=value option
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, DuplicateKeyPolicy, DuplicatePolicy, OptionPolicy, OptionSpec};
+    /// # let specs = [
+    /// #     OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0)),
+    /// #     OptionSpec::new('D', "define", "Defines a variable", false, OptionPolicy::Exact(1))
+    /// #         .duplicate_policy(DuplicatePolicy::Append)
+    /// #         .key_value(DuplicateKeyPolicy::Error),
+    /// # ];
+    /// let config = Config::new(&["./test", "-D", "mode=release", "-D", "jobs=4"], &specs).unwrap();
+    /// let defines = config.map("define").unwrap();
+    /// assert_eq!(defines["jobs"], "4");
+    /// ```
+    pub fn map(&self, name: &str) -> Option<&HashMap<String, String>> {
+        self.maps.get(name)
+    }
+
+    /// Returns where the values of an option came from, None if the option has no values<br>
+    /// The command line takes precedence over the environment, the config file and the default values, in that order
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec, Source};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('j', "jobs", "Number of jobs", false, OptionPolicy::Exact(1))
+    ///         .default_values(&["1"]),
+    /// ];
+    ///
+    /// let config = Config::new(&["./test", "file"], &specs).unwrap();
+    /// assert_eq!(config.source("jobs"), Some(&Source::Default));
+    /// assert_eq!(config.source("(unnamed)"), Some(&Source::Cli));
+    /// ```
+    pub fn source(&self, name: &str) -> Option<&Source> {
+        self.sources.get(name)
+    }
+
+    /// Returns true if `-h` or `--help` was given to the command or one of its subcommands, see ConfigBuilder::auto_help
+    pub fn help_requested(&self) -> bool {
+        self.help_requested
+            || self
+                .subcommand
+                .as_ref()
+                .is_some_and(|sub| sub.help_requested())
+    }
+
+    /// Returns true if `-V` or `--version` was given, see ConfigBuilder::version
+    pub fn version_requested(&self) -> bool {
+        self.version_requested
+    }
+
+    /// Returns the warnings recorded while parsing, e.g; for deprecated options that were given
+    /// The application decides how to show them, e.g; printing them to stderr
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Deserializes the options into any type implementing serde::Deserialize
+    /// Options given once become scalars, switches become bools and options with several values become Vecs
+    /// A struct field named `dry_run` reads the option `dry-run`, options that weren't given become None or false
+    /// Returns Err if an option is missing or a value couldn't be parsed
+    /// ## Example
+    /// ```
+    /// # #[cfg(feature = "serde")]
+    /// # {
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// #[derive(serde::Deserialize)]
+    /// struct Opts {
+    ///     verbose: bool,
+    ///     jobs: u32,
+    ///     files: Vec<String>,
+    /// }
+    /// # let specs = [
+    /// #     OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0)),
+    /// #     OptionSpec::new('v', "verbose", "Verbose output", false, OptionPolicy::Exact(0)),
+    /// #     OptionSpec::new('j', "jobs", "Number of jobs", false, OptionPolicy::Exact(1)),
+    /// #     OptionSpec::new('f', "files", "Files to process", false, OptionPolicy::AtLeast(0)),
+    /// # ];
+    /// let config = Config::new(&["./test", "-j", "4", "-f", "a", "b"], &specs).unwrap();
+    /// let opts: Opts = config.deserialize().unwrap();
+    /// assert_eq!((opts.verbose, opts.jobs, opts.files.len()), (false, 4, 2));
+    /// # }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::de::DeserializeOwned>(&self) -> Result<T, ParseError> {
+        T::deserialize(de::ConfigDeserializer(self))
+    }
+
+    /// Returns the external subcommand that was given, if any, see ConfigBuilder::external_subcommands
+    pub fn external_subcommand(&self) -> Option<&ExternalCommand> {
+        self.external.as_ref()
+    }
+
+    /// Returns the name and config of the subcommand that was given, if any
+    /// The config of the subcommand contains the options given after the subcommand name
+    pub fn subcommand(&self) -> Option<(&str, &Config)> {
+        self.subcommand
+            .as_ref()
+            .map(|config| (config.command.as_str(), config.as_ref()))
+    }
+
+    /// Returns the names of the resolved chain of nested subcommands, not including the program name
+    /// E.g; `["remote", "add"]` for `tool remote add origin`
+    pub fn command_path(&self) -> Vec<&str> {
+        let mut path = Vec::new();
+        let mut current = self;
+        while let Some((name, config)) = current.subcommand() {
+            path.push(name);
+            current = config;
+        }
+        path
+    }
+}
+
+/// A Config which keeps a reference to the specs it was parsed with<br>
+/// Dereferences to the Config, adding usage() and spec(name) for looking up the specs at runtime
+/// ## Example
+/// ```
+/// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+/// let specs = [
+///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+/// ];
+///
+/// let config = Config::new(&["./test", "-o", "out.txt"], &specs).unwrap().with_specs(&specs);
+/// assert_eq!(config.option("output").unwrap(), ["out.txt"]);
+/// assert_eq!(config.spec("output").unwrap().desc(), "Output file");
+/// ```
+pub struct SpecConfig<'a> {
+    config: Config,
+    specs: &'a [OptionSpec],
+}
+
+impl<'a> SpecConfig<'a> {
+    /// Parses config from custom supplied arguments, see Config::new
+    pub fn new(args: &[&str], specs: &'a [OptionSpec]) -> Result<Self, ParseError> {
+        Ok(Config::new(args, specs)?.with_specs(specs))
+    }
+
+    /// Same as SpecConfig::new but uses the arguments passed to the program (env::args)
+    pub fn new_env(specs: &'a [OptionSpec]) -> Result<Self, ParseError> {
+        Ok(Config::new_env(specs)?.with_specs(specs))
+    }
+
+    /// Returns the specs the config was parsed with
+    pub fn specs(&self) -> &'a [OptionSpec] {
+        self.specs
+    }
+
+    /// Returns the spec of an option by its name or alias
+    pub fn spec(&self, name: &str) -> Option<&'a OptionSpec> {
+        self.specs
+            .iter()
+            .find(|spec| spec.name == name || spec.aliases.iter().any(|(alias, _)| alias == name))
+    }
+
+    /// Generates the usage of all the options, see Config::generate_usage
+    pub fn usage(&self) -> String {
+        Config::generate_usage(self.specs, true, true)
+    }
+
+    /// Returns the config without the specs
+    pub fn into_inner(self) -> Config {
+        self.config
+    }
+}
+
+impl std::ops::Deref for SpecConfig<'_> {
+    type Target = Config;
+
+    fn deref(&self) -> &Config {
+        &self.config
+    }
+}
+
+// Reads the values of options from a TOML file
+// Each top level key is the name of an option
+#[cfg(feature = "toml")]
+fn load_toml(
+    path: &Path,
+    specs: &[OptionSpec],
+) -> Result<HashMap<String, Vec<String>>, ParseError> {
+    let file_error = |reason: String| ParseError::File {
+        path: path.display().to_string(),
+        reason,
+    };
+
+    let contents = match std::fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(file_error(e.to_string())),
+    };
+
+    let table: toml::Table = contents
+        .parse()
+        .map_err(|e: toml::de::Error| file_error(e.to_string()))?;
+
+    let mut result = HashMap::new();
+    for (key, value) in table {
+        let spec = match specs.iter().find(|spec| spec.name == key) {
+            Some(spec) => spec,
+            None => return Err(file_error(format!("invalid option '{}'", key))),
+        };
+
+        let values = match value {
+            toml::Value::Boolean(false) if spec.takes_no_values() => continue,
+            toml::Value::Boolean(true) if spec.takes_no_values() => Some(Vec::new()),
+            toml::Value::Array(array) => array.into_iter().map(toml_scalar).collect(),
+            value => toml_scalar(value).map(|value| vec![value]),
+        };
+
+        match values {
+            Some(values) => result.insert(spec.name.to_string(), values),
+            None => {
+                return Err(file_error(format!(
+                    "unsupported value for option '{}'",
+                    key
+                )))
+            }
+        };
+    }
+
+    Ok(result)
+}
+
+// Converts a single TOML value to an option value
+// Returns None for arrays and tables
+#[cfg(feature = "toml")]
+fn toml_scalar(value: toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(v) => Some(v),
+        toml::Value::Integer(v) => Some(v.to_string()),
+        toml::Value::Float(v) => Some(v.to_string()),
+        toml::Value::Boolean(v) => Some(v.to_string()),
+        toml::Value::Datetime(v) => Some(v.to_string()),
+        toml::Value::Array(_) | toml::Value::Table(_) => None,
+    }
 }
@@ -0,0 +1,338 @@
+//! Bundles the option specifications of a program with its name, version, author and description
+
+use super::{
+    help_spec, version_spec, Command, Config, ConfigBuilder, English, Help, Messages, OptionSpec,
+    ParseError, ParseOutcome,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// A function run with the parsed config, see App::subcommand and App::handler<br>
+/// An error is printed to stderr and makes App::run return 1
+pub type Handler = Box<dyn Fn(&Config) -> Result<(), Box<dyn Error>>>;
+
+/// A program owning its option specifications, subcommands and metadata<br>
+/// The name and version answer `-V` and `--version`, the author and description head the help shown for `-h` and `--help`<br>
+/// See App::builder to parse with further settings and App::run_env to dispatch to handlers
+/// ## Example
+/// ```
+/// # use libcli::args::{App, OptionPolicy, OptionSpec, ParseOutcome};
+/// let app = App::new("myapp")
+///     .version("1.2.0")
+///     .author("Tim Roberts <ten3roberts@gmail.com>")
+///     .about("Processes files")
+///     .spec(OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)));
+///
+/// assert_eq!(app.version_string(), "myapp 1.2.0");
+/// match app.parse(&["./myapp", "--version"]).unwrap() {
+///     ParseOutcome::VersionRequested(text) => assert_eq!(text, "myapp 1.2.0\n"),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub struct App {
+    name: Cow<'static, str>,
+    version: Option<Cow<'static, str>>,
+    author: Option<Cow<'static, str>>,
+    about: Option<Cow<'static, str>>,
+    specs: Vec<OptionSpec>,
+    commands: Vec<Command>,
+    handlers: HashMap<String, Handler>,
+    handler: Option<Handler>,
+    external_subcommands: bool,
+    messages: Box<dyn Messages>,
+}
+
+impl App {
+    /// Creates a program without options, name is shown in the version and the help
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            version: None,
+            author: None,
+            about: None,
+            specs: Vec::new(),
+            commands: Vec::new(),
+            handlers: HashMap::new(),
+            handler: None,
+            external_subcommands: false,
+            messages: Box::new(English),
+        }
+    }
+
+    /// Sets the version, recognizing `-V` and `--version`, e.g; `env!("CARGO_PKG_VERSION")`
+    pub fn version(mut self, version: impl Into<Cow<'static, str>>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Sets the author, shown below the version at the top of the help
+    pub fn author(mut self, author: impl Into<Cow<'static, str>>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Sets the description of the program, shown above the usage in the help
+    pub fn about(mut self, about: impl Into<Cow<'static, str>>) -> Self {
+        self.about = Some(about.into());
+        self
+    }
+
+    /// Adds an option specification
+    pub fn spec(mut self, spec: OptionSpec) -> Self {
+        self.specs.push(spec);
+        self
+    }
+
+    /// Adds several option specifications, e.g; from Config::load_specs
+    pub fn specs(mut self, specs: impl IntoIterator<Item = OptionSpec>) -> Self {
+        self.specs.extend(specs);
+        self
+    }
+
+    /// Adds a subcommand without a handler, see Command<br>
+    /// App::run runs the handler of the program when it is given
+    pub fn command(mut self, command: Command) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    /// Adds a subcommand with the handler App::run calls with its config when it is given<br>
+    /// The nested subcommands are reached through Config::subcommand of that config
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{App, Command, OptionPolicy, OptionSpec};
+    /// let build = vec![OptionSpec::new('r', "release", "Build with optimizations", false, OptionPolicy::Exact(0))];
+    ///
+    /// let app = App::new("myapp").subcommand(Command::new("build", "Builds the project", build), |config| {
+    ///     match config.flag("release") {
+    ///         true => Ok(()),
+    ///         false => Err("only release builds are supported"),
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(app.run(&["./myapp", "build", "--release"]), 0);
+    /// assert_eq!(app.run(&["./myapp", "build"]), 1);
+    /// ```
+    pub fn subcommand<F, E>(mut self, command: Command, handler: F) -> Self
+    where
+        F: Fn(&Config) -> Result<(), E> + 'static,
+        E: Into<Box<dyn Error>>,
+    {
+        self.handlers
+            .insert(command.name().to_string(), boxed(handler));
+        self.commands.push(command);
+        self
+    }
+
+    /// Sets the handler App::run calls with the config when no subcommand with a handler is given
+    pub fn handler<F, E>(mut self, handler: F) -> Self
+    where
+        F: Fn(&Config) -> Result<(), E> + 'static,
+        E: Into<Box<dyn Error>>,
+    {
+        self.handler = Some(boxed(handler));
+        self
+    }
+
+    /// Resolves an unknown subcommand `foo` to an executable `<name>-foo` on the PATH, which App::run runs<br>
+    /// See ConfigBuilder::external_subcommands
+    pub fn external_subcommands(mut self, external_subcommands: bool) -> Self {
+        self.external_subcommands = external_subcommands;
+        self
+    }
+
+    /// Sets the messages of the help and the parse errors, see Messages
+    pub fn messages(mut self, messages: impl Messages + 'static) -> Self {
+        self.messages = Box::new(messages);
+        self
+    }
+
+    /// Returns the name of the program
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the option specifications
+    pub fn option_specs(&self) -> &[OptionSpec] {
+        &self.specs
+    }
+
+    /// Returns the subcommands
+    pub fn subcommands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Returns the name and version, e.g; `myapp 1.2.0`, only the name without a version
+    pub fn version_string(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{} {}", self.name, version),
+            None => self.name.to_string(),
+        }
+    }
+
+    /// Renders the help of the program, the same as shown for `-h`
+    pub fn usage(&self) -> String {
+        let mut added = vec![help_spec()];
+        if self.version.is_some() {
+            added.push(version_spec());
+        }
+
+        Help::new(&self.name, &self.specs)
+            .commands(&self.commands)
+            .added(added)
+            .messages(self.messages.as_ref())
+            .before_help(&self.header())
+            .about(self.about.as_deref().unwrap_or_default())
+            .render()
+    }
+
+    /// Returns a builder parsing with the specs and subcommands of the program<br>
+    /// Automatic help and the version are enabled, further settings can be chained before parsing
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{App, OptionPolicy, OptionSpec};
+    /// let app = App::new("myapp")
+    ///     .spec(OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)));
+    ///
+    /// let config = app.builder().args(&["./myapp", "a.txt"]).env_prefix("MYAPP").build().unwrap();
+    /// ```
+    pub fn builder(&self) -> ConfigBuilder<'_> {
+        let mut builder = ConfigBuilder::new(&self.specs)
+            .commands(&self.commands)
+            .messages(self.messages.as_ref())
+            .auto_help(true)
+            .before_help(&self.header())
+            .about(self.about.as_deref().unwrap_or_default());
+        if let Some(version) = &self.version {
+            builder = builder.version(&self.name, version);
+        }
+        if self.external_subcommands {
+            builder = builder.external_subcommands(&self.name);
+        }
+        builder
+    }
+
+    /// Parses args, including the command, returning the help or version instead of the config if requested<br>
+    /// See ConfigBuilder::parse
+    pub fn parse(&self, args: &[&str]) -> Result<ParseOutcome, ParseError> {
+        self.builder().args(args).parse()
+    }
+
+    /// Parses the arguments passed to the program (env::args) and exits if the config isn't needed<br>
+    /// The help or version are printed to stdout when requested and the program exits with status 0<br>
+    /// On failure the error is printed like Config::parse_or_exit and the program exits with status 2
+    /// ## Example
+    /// ```no_run
+    /// # use libcli::args::{App, OptionPolicy, OptionSpec};
+    /// let app = App::new(env!("CARGO_PKG_NAME"))
+    ///     .version(env!("CARGO_PKG_VERSION"))
+    ///     .spec(OptionSpec::new('v', "verbose", "Shows verbose output", false, OptionPolicy::Exact(0)));
+    ///
+    /// let config = app.parse_env();
+    /// ```
+    pub fn parse_env(&self) -> Config {
+        match self.builder().parse() {
+            Ok(ParseOutcome::Parsed(config)) => config,
+            Ok(ParseOutcome::HelpRequested(text) | ParseOutcome::VersionRequested(text)) => {
+                print!("{}", text);
+                std::process::exit(0)
+            }
+            Err(err) => {
+                self.report(&err);
+                std::process::exit(2)
+            }
+        }
+    }
+
+    /// Parses args, including the command, and runs the handler of the given subcommand, returning the exit status<br>
+    /// The status is 0 when the handler succeeds or the help or version is printed, 1 when the handler fails<br>
+    /// and 2 when the arguments are invalid or there is no handler to run, the errors are printed to stderr<br>
+    /// An external subcommand is run instead and its exit status returned, see App::external_subcommands
+    pub fn run(&self, args: &[&str]) -> i32 {
+        self.dispatch(self.builder().args(args).parse())
+    }
+
+    /// Same as App::run but uses the arguments passed to the program (env::args)
+    /// ## Example
+    /// ```no_run
+    /// # use libcli::args::{App, Command};
+    /// let app = App::new("myapp")
+    ///     .subcommand(Command::new("clean", "Removes the build output", Vec::new()), |_| {
+    ///         std::fs::remove_dir_all("target")
+    ///     });
+    ///
+    /// std::process::exit(app.run_env());
+    /// ```
+    pub fn run_env(&self) -> i32 {
+        self.dispatch(self.builder().parse())
+    }
+
+    // Runs the handler matching the outcome of parsing
+    fn dispatch(&self, outcome: Result<ParseOutcome, ParseError>) -> i32 {
+        let config = match outcome {
+            Ok(ParseOutcome::Parsed(config)) => config,
+            Ok(ParseOutcome::HelpRequested(text) | ParseOutcome::VersionRequested(text)) => {
+                print!("{}", text);
+                return 0;
+            }
+            Err(err) => {
+                self.report(&err);
+                return 2;
+            }
+        };
+
+        if let Some(external) = config.external_subcommand() {
+            return match external.run() {
+                Ok(status) => status.code().unwrap_or(1),
+                Err(err) => {
+                    eprintln!("{}: {}", external.path().display(), err);
+                    1
+                }
+            };
+        }
+
+        let handled = config
+            .subcommand()
+            .and_then(|(name, sub)| Some((self.handlers.get(name)?, sub)));
+        let result = match (handled, &self.handler) {
+            (Some((handler, sub)), _) => handler(sub),
+            (None, Some(handler)) => handler(&config),
+            (None, None) => {
+                eprint!("{}", self.usage());
+                return 2;
+            }
+        };
+
+        match result {
+            Ok(()) => 0,
+            Err(err) => {
+                eprintln!("{}", err);
+                1
+            }
+        }
+    }
+
+    // Prints a parse error with the synopsis to stderr, like Config::parse_or_exit
+    fn report(&self, err: &ParseError) {
+        let synopsis = Config::generate_synopsis(&self.name, &self.specs);
+        eprint!("{}", self.messages.report(err, &synopsis));
+    }
+
+    // The version and author at the top of the help
+    fn header(&self) -> String {
+        match &self.author {
+            Some(author) => format!("{}\n{}", self.version_string(), author),
+            None => self.version_string(),
+        }
+    }
+}
+
+// Boxes a handler, converting its error
+fn boxed<F, E>(handler: F) -> Handler
+where
+    F: Fn(&Config) -> Result<(), E> + 'static,
+    E: Into<Box<dyn Error>>,
+{
+    Box::new(move |config| handler(config).map_err(Into::into))
+}
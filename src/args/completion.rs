@@ -0,0 +1,739 @@
+//! Generates shell completion scripts from the option specifications
+//! The scripts complete the names of options and subcommands, and the values of options from their choices or ValueHint
+
+use super::{split_args, Command, Config, OptionSpec, ValueHint};
+use std::path::Path;
+
+impl Config {
+    /// Generates a zsh completion script for the program bin_name, completing the options of specs and the commands<br>
+    /// The descriptions of the options and commands are shown next to the candidates<br>
+    /// Save the script as `_<bin_name>` in a directory of `$fpath`, the hidden options are left out
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+    /// ];
+    ///
+    /// let script = Config::generate_zsh_completion("myapp", &specs, &[]);
+    /// assert!(script.starts_with("#compdef myapp\n"));
+    /// ```
+    pub fn generate_zsh_completion(
+        bin_name: &str,
+        specs: &[OptionSpec],
+        commands: &[Command],
+    ) -> String {
+        let name = function_name(bin_name);
+        let mut script = format!("#compdef {}\n", bin_name);
+        zsh_function(&mut script, &name, specs, commands, &[]);
+        script += &format!("\n{} \"$@\"\n", name);
+        script
+    }
+
+    /// Generates a fish completion script for the program bin_name, one `complete` command per option and subcommand<br>
+    /// The options and subcommands of a subcommand are only suggested after it, global options after their command as well<br>
+    /// Save the script as `<bin_name>.fish` in `~/.config/fish/completions`, the hidden options are left out
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+    /// ];
+    ///
+    /// let script = Config::generate_fish_completion("myapp", &specs, &[]);
+    /// assert!(script.contains("complete -c myapp -s o -l output -d 'Output file' -r\n"));
+    /// ```
+    pub fn generate_fish_completion(
+        bin_name: &str,
+        specs: &[OptionSpec],
+        commands: &[Command],
+    ) -> String {
+        let mut script = String::new();
+        fish_command(&mut script, bin_name, specs, commands, &[]);
+        script
+    }
+
+    /// Generates a PowerShell completion script for the program bin_name, registering a completer with `Register-ArgumentCompleter`<br>
+    /// The options and subcommands of the command before the cursor are suggested with their descriptions<br>
+    /// Source the script from the PowerShell profile, the hidden options are left out
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+    /// ];
+    ///
+    /// let script = Config::generate_powershell_completion("myapp", &specs, &[]);
+    /// assert!(script.contains("Register-ArgumentCompleter -Native -CommandName 'myapp'"));
+    /// ```
+    pub fn generate_powershell_completion(
+        bin_name: &str,
+        specs: &[OptionSpec],
+        commands: &[Command],
+    ) -> String {
+        let mut cases = String::new();
+        powershell_cases(&mut cases, &[bin_name], specs, commands, &[]);
+
+        format!(
+            r#"using namespace System.Management.Automation
+using namespace System.Management.Automation.Language
+
+Register-ArgumentCompleter -Native -CommandName {name} -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    # The names of the command and the subcommands before the cursor, e.g; 'myapp;build'
+    $commandElements = $commandAst.CommandElements
+    $command = @(
+        {name}
+        for ($i = 1; $i -lt $commandElements.Count; $i++) {{
+            $element = $commandElements[$i]
+            if ($element -isnot [StringConstantExpressionAst] -or
+                $element.StringConstantType -ne [StringConstantType]::BareWord -or
+                $element.Value.StartsWith('-') -or
+                $element.Value -eq $wordToComplete) {{
+                break
+            }}
+            $element.Value
+        }}
+    ) -join ';'
+
+    $completions = @(switch ($command) {{
+{cases}    }})
+
+    $completions.Where{{ $_.CompletionText -like "$wordToComplete*" }} |
+        Sort-Object -Property ListItemText
+}}
+"#,
+            name = powershell_quote(bin_name),
+            cases = cases
+        )
+    }
+
+    /// Returns the candidates completing the last of words, the arguments after the program name<br>
+    /// The last word is the one being completed, an empty string when starting a new argument<br>
+    /// Options, subcommands and values are completed, values from the choices of the option or from the file system according to its ValueHint
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('c', "color", "Colorize", false, OptionPolicy::Exact(1))
+    ///         .choices(&["auto", "always", "never"]),
+    /// ];
+    ///
+    /// assert_eq!(Config::complete(&["--co"], &specs, &[]), ["--color"]);
+    /// assert_eq!(Config::complete(&["--color", "a"], &specs, &[]), ["auto", "always"]);
+    /// ```
+    pub fn complete(words: &[&str], specs: &[OptionSpec], commands: &[Command]) -> Vec<String> {
+        let (current, previous) = match words.split_last() {
+            Some((current, previous)) => (*current, previous),
+            None => ("", &[][..]),
+        };
+
+        let mut specs = specs;
+        let mut commands = commands;
+        let mut globals: Vec<&OptionSpec> = Vec::new();
+        // The option the next words are values of, with the number of values it still takes
+        let mut pending: Option<(&OptionSpec, usize)> = None;
+        let mut positionals = 0;
+        let mut trailing = false;
+
+        for word in previous {
+            let visible = || options(specs, &globals);
+            match *word {
+                _ if trailing => (),
+                "--" => trailing = true,
+                word if pending.is_some_and(|(_, remaining)| remaining > 0)
+                    && !word.starts_with('-') =>
+                {
+                    pending = pending.map(|(spec, remaining)| (spec, remaining - 1));
+                }
+                word if word.starts_with("--") => {
+                    let (name, value) = match word[2..].split_once('=') {
+                        Some((name, _)) => (name, true),
+                        None => (&word[2..], false),
+                    };
+                    pending = visible()
+                        .find(|spec| spec.long && spec.name == name)
+                        .filter(|spec| !spec.takes_no_values() && !value)
+                        .map(|spec| (spec, spec.policy.min().max(1)));
+                }
+                word if word.starts_with('-') && word.len() > 1 => {
+                    pending = word.chars().last().and_then(|abrev| {
+                        visible()
+                            .find(|spec| spec.abrev == Some(abrev))
+                            .filter(|spec| !spec.takes_no_values())
+                            .map(|spec| (spec, spec.policy.min().max(1)))
+                    });
+                }
+                word => {
+                    pending = None;
+                    match commands.iter().find(|cmd| cmd.matches(word)) {
+                        Some(cmd) if positionals == 0 => {
+                            globals.extend(specs.iter().filter(|spec| spec.global));
+                            specs = &cmd.specs;
+                            commands = &cmd.subcommands;
+                        }
+                        _ => positionals += 1,
+                    }
+                }
+            }
+        }
+
+        let mut candidates = Vec::new();
+        if let Some((spec, _)) = pending.filter(|(_, remaining)| *remaining > 0) {
+            candidates.extend(complete_value(spec, current));
+        } else if let Some((name, value)) = current
+            .strip_prefix("--")
+            .and_then(|rest| rest.split_once('='))
+        {
+            if let Some(spec) = options(specs, &globals).find(|spec| spec.long && spec.name == name)
+            {
+                candidates.extend(
+                    complete_value(spec, value)
+                        .into_iter()
+                        .map(|value| format!("--{}={}", name, value)),
+                );
+            }
+        } else if current.starts_with('-') && !trailing {
+            for spec in options(specs, &globals) {
+                if let Some(abrev) = spec.abrev.filter(|_| !current.starts_with("--")) {
+                    candidates.push(format!("-{}", abrev));
+                }
+                if spec.long {
+                    candidates.push(format!("--{}", spec.name));
+                    candidates.extend(
+                        spec.aliases
+                            .iter()
+                            .filter(|(_, visible)| *visible)
+                            .map(|(alias, _)| format!("--{}", alias)),
+                    );
+                    if spec.negatable && spec.takes_no_values() {
+                        candidates.push(format!("--no-{}", spec.name));
+                    }
+                }
+            }
+            candidates.retain(|candidate| candidate.starts_with(current));
+        } else {
+            if positionals == 0 && !trailing {
+                candidates.extend(
+                    commands
+                        .iter()
+                        .filter(|cmd| cmd.name.starts_with(current))
+                        .map(|cmd| cmd.name.to_string()),
+                );
+            }
+            let positional = specs
+                .iter()
+                .find(|spec| spec.positional && spec.policy.max() != Some(0));
+            if let Some(spec) = positional.filter(|_| commands.is_empty()) {
+                candidates.extend(complete_value(spec, current));
+            }
+        }
+
+        candidates
+    }
+
+    /// Handles the hidden `__complete` mode of dynamic completion, call it before parsing the arguments<br>
+    /// When the program is run as `myapp __complete <words>...`, the candidates of Config::complete are printed one per line and the program exits<br>
+    /// Bash passes the command line in COMP_LINE and COMP_POINT when registered with `complete -C 'myapp __complete' myapp`, they take precedence over the words<br>
+    /// Returns without doing anything if the first argument isn't `__complete`
+    /// ## Example
+    /// ```no_run
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// # let specs = [OptionSpec::new('\0', "(unnamed)", "Unnamed", false, OptionPolicy::AtLeast(0))];
+    /// Config::complete_env(&specs, &[]);
+    /// let config = Config::new_env(&specs);
+    /// ```
+    pub fn complete_env(specs: &[OptionSpec], commands: &[Command]) {
+        let mut args = std::env::args().skip(1);
+        if args.next().as_deref() != Some("__complete") {
+            return;
+        }
+
+        let words = match std::env::var("COMP_LINE") {
+            Ok(line) => {
+                let point = std::env::var("COMP_POINT")
+                    .ok()
+                    .and_then(|point| point.parse().ok())
+                    .filter(|point| line.is_char_boundary(*point))
+                    .unwrap_or(line.len());
+                line_words(&line[..point])
+            }
+            Err(_) => args.collect(),
+        };
+
+        let words: Vec<&str> = words.iter().map(|word| word.as_str()).collect();
+        for candidate in Config::complete(&words, specs, commands) {
+            println!("{}", candidate);
+        }
+        std::process::exit(0);
+    }
+}
+
+// The name of the completion function of a program or command, e.g; `_my_app` for `my-app`
+fn function_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect();
+    format!("_{}", name)
+}
+
+// Quotes a string for a shell with single quotes
+fn quote(string: &str) -> String {
+    format!("'{}'", string.replace('\'', "'\\''"))
+}
+
+// The first line of a description with the characters special to an _arguments spec escaped
+fn zsh_desc(desc: &str) -> String {
+    desc.lines()
+        .next()
+        .unwrap_or_default()
+        .replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+// The zsh action completing the values of an option
+fn zsh_action(spec: &OptionSpec) -> String {
+    if !spec.choices.is_empty() {
+        let choices: Vec<String> = spec
+            .choices
+            .iter()
+            .map(|choice| choice.replace(' ', "\\ "))
+            .collect();
+        return format!("({})", choices.join(" "));
+    }
+
+    match spec.value_hint {
+        ValueHint::Unknown => String::from(" "),
+        ValueHint::AnyPath | ValueHint::FilePath => String::from("_files"),
+        ValueHint::DirPath => String::from("_files -/"),
+        ValueHint::CommandName => String::from("_command_names -e"),
+        ValueHint::Hostname => String::from("_hosts"),
+        ValueHint::Username => String::from("_users"),
+        ValueHint::Url => String::from("_urls"),
+    }
+}
+
+// The _arguments specs of an option, one for the option and one for its negated form
+fn zsh_option(spec: &OptionSpec) -> Vec<String> {
+    let mut names: Vec<String> = spec
+        .abrev
+        .iter()
+        .map(|abrev| format!("-{}", abrev))
+        .collect();
+    if spec.long {
+        names.push(format!("--{}", spec.name));
+        names.extend(
+            spec.aliases
+                .iter()
+                .filter(|(_, visible)| *visible)
+                .map(|(alias, _)| format!("--{}", alias)),
+        );
+    }
+
+    let desc = zsh_desc(&spec.desc);
+    let action = zsh_action(spec);
+    let values = match spec.policy.max() {
+        _ if spec.takes_no_values() => 0,
+        Some(max) => max.min(spec.policy.min().max(1)),
+        None => 1,
+    };
+    let values = format!(":{}:{}", spec.name, action).repeat(values);
+
+    let mut options = Vec::new();
+    let exclusive = format!("({})", names.join(" "));
+    for name in &names {
+        let name = match values.is_empty() || !name.starts_with("--") {
+            true => name.clone(),
+            false => format!("{}=", name),
+        };
+        options.push(quote(&format!("{}{}[{}]{}", exclusive, name, desc, values)));
+    }
+    if spec.negatable && spec.long && spec.takes_no_values() {
+        options.push(quote(&format!("--no-{}[{}]", spec.name, desc)));
+    }
+    options
+}
+
+// The most positional arguments of a spec which get a line each, beyond which the spec takes the rest
+const ZSH_MAX_POSITIONALS: usize = 16;
+
+// The _arguments specs of the positional arguments, the last spec without a small maximum takes the rest
+fn zsh_positionals(specs: &[OptionSpec]) -> Vec<String> {
+    let mut positionals = Vec::new();
+    for spec in specs.iter().filter(|spec| spec.positional) {
+        let name = match spec.name.as_ref() {
+            super::UNNAMED => "args",
+            name => name,
+        };
+        let action = zsh_action(spec);
+        match spec.policy.max() {
+            Some(0) => (),
+            Some(max) if max <= ZSH_MAX_POSITIONALS => {
+                for _ in 0..max {
+                    positionals.push(quote(&format!(":{}:{}", name, action)));
+                }
+            }
+            _ => {
+                positionals.push(quote(&format!("*:{}:{}", name, action)));
+                break;
+            }
+        }
+    }
+    positionals
+}
+
+// Writes the completion function of a command, followed by the functions of its subcommands
+fn zsh_function(
+    script: &mut String,
+    name: &str,
+    specs: &[OptionSpec],
+    commands: &[Command],
+    globals: &[&OptionSpec],
+) {
+    // A global option is replaced by an option with the same name
+    let mut arguments: Vec<String> = globals
+        .iter()
+        .copied()
+        .filter(|global| !specs.iter().any(|spec| spec.name == global.name))
+        .chain(specs.iter().filter(|spec| !spec.positional && !spec.hidden))
+        .filter(|spec| !spec.trailing)
+        .flat_map(zsh_option)
+        .collect();
+
+    // A subcommand takes the place of the positional arguments
+    if commands.is_empty() {
+        arguments.extend(zsh_positionals(specs));
+    } else {
+        arguments.push(quote(": :->command"));
+        arguments.push(quote("*:: :->args"));
+    }
+
+    *script += &format!(
+        "\n{}() {{\n    local context state state_descr line\n    typeset -A opt_args\n\n    _arguments -s -S \\\n",
+        name
+    );
+    for argument in &arguments {
+        *script += &format!("        {} \\\n", argument);
+    }
+    *script += "        && return 0\n";
+
+    if !commands.is_empty() {
+        *script += "\n    case $state in\n        command)\n            local -a commands\n            commands=(\n";
+        for cmd in commands {
+            *script += &format!(
+                "                {}\n",
+                quote(&format!(
+                    "{}:{}",
+                    cmd.name.replace(':', "\\:"),
+                    cmd.desc.lines().next().unwrap_or_default()
+                ))
+            );
+        }
+        *script += "            )\n            _describe -t commands 'command' commands\n            ;;\n        args)\n            case $line[1] in\n";
+        for cmd in commands {
+            *script += &format!(
+                "                {}) {} ;;\n",
+                quote(&cmd.name),
+                function_name(&format!("{}_{}", &name[1..], cmd.name))
+            );
+        }
+        *script += "            esac\n            ;;\n    esac\n";
+    }
+    *script += "}\n";
+
+    let globals: Vec<&OptionSpec> = globals
+        .iter()
+        .copied()
+        .chain(
+            specs
+                .iter()
+                .filter(|spec| spec.global && !spec.positional && !spec.hidden),
+        )
+        .collect();
+    for cmd in commands {
+        zsh_function(
+            script,
+            &function_name(&format!("{}_{}", &name[1..], cmd.name)),
+            &cmd.specs,
+            &cmd.subcommands,
+            &globals,
+        );
+    }
+}
+
+// The arguments of `complete` describing the values of an option
+fn fish_values(spec: &OptionSpec) -> String {
+    if !spec.choices.is_empty() {
+        let choices: Vec<&str> = spec.choices.iter().map(|choice| choice.as_ref()).collect();
+        return format!(" -x -a {}", quote(&choices.join(" ")));
+    }
+
+    match spec.value_hint {
+        ValueHint::Unknown | ValueHint::Url => String::from(" -r"),
+        ValueHint::AnyPath | ValueHint::FilePath => String::from(" -r -F"),
+        ValueHint::DirPath => String::from(" -x -a '(__fish_complete_directories)'"),
+        ValueHint::CommandName => String::from(" -x -a '(__fish_complete_command)'"),
+        ValueHint::Hostname => String::from(" -x -a '(__fish_print_hostnames)'"),
+        ValueHint::Username => String::from(" -x -a '(__fish_complete_users)'"),
+    }
+}
+
+// The `complete` command of an option, and of its negated form
+fn fish_option(bin_name: &str, condition: &str, spec: &OptionSpec) -> String {
+    let mut line = format!("complete -c {}{}", bin_name, condition);
+    if let Some(abrev) = spec.abrev {
+        line += &format!(" -s {}", abrev);
+    }
+    if spec.long {
+        line += &format!(" -l {}", spec.name);
+        for (alias, _) in spec.aliases.iter().filter(|(_, visible)| *visible) {
+            line += &format!(" -l {}", alias);
+        }
+    }
+
+    let desc = quote(spec.desc.lines().next().unwrap_or_default());
+    line += &format!(" -d {}", desc);
+    if !spec.takes_no_values() {
+        line += &fish_values(spec);
+    }
+    line.push('\n');
+
+    if spec.negatable && spec.long && spec.takes_no_values() {
+        line += &format!(
+            "complete -c {}{} -l no-{} -d {}\n",
+            bin_name, condition, spec.name, desc
+        );
+    }
+    line
+}
+
+// The condition of a `complete` command, empty if it always applies
+fn fish_condition(conditions: &[String]) -> String {
+    match conditions.is_empty() {
+        true => String::new(),
+        false => format!(" -n {}", quote(&conditions.join("; and "))),
+    }
+}
+
+// Writes the completions of a command and its subcommands, path holds the names of the parent commands
+fn fish_command(
+    script: &mut String,
+    bin_name: &str,
+    specs: &[OptionSpec],
+    commands: &[Command],
+    path: &[&str],
+) {
+    // Inside the command, and inside the command but not one of its subcommands
+    let mut inside: Vec<String> = path
+        .iter()
+        .map(|name| format!("__fish_seen_subcommand_from {}", name))
+        .collect();
+    let below = fish_condition(&inside);
+    if !commands.is_empty() {
+        let names: Vec<&str> = commands.iter().map(|cmd| cmd.name.as_ref()).collect();
+        inside.push(format!(
+            "not __fish_seen_subcommand_from {}",
+            names.join(" ")
+        ));
+    }
+    let here = fish_condition(&inside);
+
+    for spec in specs
+        .iter()
+        .filter(|spec| !spec.positional && !spec.trailing && !spec.hidden)
+    {
+        let condition = if spec.global { &below } else { &here };
+        *script += &fish_option(bin_name, condition, spec);
+    }
+
+    // Files are completed as positional arguments unless the command takes none or has choices
+    let positional = specs
+        .iter()
+        .find(|spec| spec.positional && spec.policy.max() != Some(0));
+    match positional {
+        _ if !commands.is_empty() => (),
+        Some(spec) if !spec.choices.is_empty() => {
+            let choices: Vec<&str> = spec.choices.iter().map(|choice| choice.as_ref()).collect();
+            *script += &format!(
+                "complete -c {}{} -f -a {}\n",
+                bin_name,
+                here,
+                quote(&choices.join(" "))
+            );
+        }
+        Some(_) => (),
+        None => *script += &format!("complete -c {}{} -f\n", bin_name, here),
+    }
+
+    for cmd in commands {
+        *script += &format!(
+            "complete -c {}{} -f -a {} -d {}\n",
+            bin_name,
+            here,
+            quote(&cmd.name),
+            quote(cmd.desc.lines().next().unwrap_or_default())
+        );
+    }
+
+    for cmd in commands {
+        let path: Vec<&str> = path.iter().copied().chain([cmd.name.as_ref()]).collect();
+        fish_command(script, bin_name, &cmd.specs, &cmd.subcommands, &path);
+    }
+}
+
+// Quotes a string for PowerShell with single quotes
+fn powershell_quote(string: &str) -> String {
+    format!("'{}'", string.replace('\'', "''"))
+}
+
+// A CompletionResult of a PowerShell completer, the tooltip can't be empty
+fn powershell_result(text: &str, kind: &str, desc: &str) -> String {
+    let tooltip = desc
+        .lines()
+        .next()
+        .filter(|line| !line.is_empty())
+        .unwrap_or(text);
+    format!(
+        "            [CompletionResult]::new({}, {}, [CompletionResultType]::{}, {})\n",
+        powershell_quote(text),
+        powershell_quote(text.trim_start_matches('-')),
+        kind,
+        powershell_quote(tooltip)
+    )
+}
+
+// Writes the switch cases of a command and its subcommands, path holds the names leading to the command
+fn powershell_cases(
+    cases: &mut String,
+    path: &[&str],
+    specs: &[OptionSpec],
+    commands: &[Command],
+    globals: &[&OptionSpec],
+) {
+    *cases += &format!("        {} {{\n", powershell_quote(&path.join(";")));
+
+    // A global option is replaced by an option with the same name
+    let options = globals
+        .iter()
+        .copied()
+        .filter(|global| !specs.iter().any(|spec| spec.name == global.name))
+        .chain(specs.iter())
+        .filter(|spec| !spec.positional && !spec.trailing && !spec.hidden);
+    for spec in options {
+        if let Some(abrev) = spec.abrev {
+            *cases += &powershell_result(&format!("-{}", abrev), "ParameterName", &spec.desc);
+        }
+        if spec.long {
+            *cases += &powershell_result(&format!("--{}", spec.name), "ParameterName", &spec.desc);
+            for (alias, _) in spec.aliases.iter().filter(|(_, visible)| *visible) {
+                *cases += &powershell_result(&format!("--{}", alias), "ParameterName", &spec.desc);
+            }
+            if spec.negatable && spec.takes_no_values() {
+                *cases +=
+                    &powershell_result(&format!("--no-{}", spec.name), "ParameterName", &spec.desc);
+            }
+        }
+    }
+    for cmd in commands {
+        *cases += &powershell_result(&cmd.name, "ParameterValue", &cmd.desc);
+    }
+    *cases += "            break\n        }\n";
+
+    let globals: Vec<&OptionSpec> = globals
+        .iter()
+        .copied()
+        .chain(specs.iter().filter(|spec| spec.global && !spec.positional))
+        .collect();
+    for cmd in commands {
+        let path: Vec<&str> = path.iter().copied().chain([cmd.name.as_ref()]).collect();
+        powershell_cases(cases, &path, &cmd.specs, &cmd.subcommands, &globals);
+    }
+}
+
+// The visible options of a command and the global options of its parents
+fn options<'a: 'b, 'b>(
+    specs: &'a [OptionSpec],
+    globals: &'b [&'a OptionSpec],
+) -> impl Iterator<Item = &'a OptionSpec> + 'b {
+    globals
+        .iter()
+        .copied()
+        .chain(specs)
+        .filter(|spec| !spec.positional && !spec.trailing && !spec.hidden)
+}
+
+// The candidates for a value of an option starting with prefix
+fn complete_value(spec: &OptionSpec, prefix: &str) -> Vec<String> {
+    if !spec.choices.is_empty() {
+        return spec
+            .choices
+            .iter()
+            .filter(|choice| choice.starts_with(prefix))
+            .map(|choice| choice.to_string())
+            .collect();
+    }
+
+    match spec.value_hint {
+        ValueHint::AnyPath | ValueHint::FilePath => complete_path(prefix, false),
+        ValueHint::DirPath => complete_path(prefix, true),
+        _ => Vec::new(),
+    }
+}
+
+// The paths starting with prefix, directories end with a '/' so they can be completed further
+fn complete_path(prefix: &str, dirs_only: bool) -> Vec<String> {
+    let (dir, name) = match prefix.rfind('/') {
+        Some(index) => (&prefix[..=index], &prefix[index + 1..]),
+        None => ("", prefix),
+    };
+    let entries = match std::fs::read_dir(if dir.is_empty() {
+        Path::new(".")
+    } else {
+        Path::new(dir)
+    }) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut paths: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.path().is_dir();
+            if !file_name.starts_with(name) || (dirs_only && !is_dir) {
+                return None;
+            }
+            // Hidden files are only completed when asked for
+            if file_name.starts_with('.') && !name.starts_with('.') {
+                return None;
+            }
+            Some(format!(
+                "{}{}{}",
+                dir,
+                file_name,
+                if is_dir { "/" } else { "" }
+            ))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+// Splits a partial command line into the words after the program name
+// A line ending with whitespace starts a new, empty word
+fn line_words(line: &str) -> Vec<String> {
+    let mut words =
+        split_args(line).unwrap_or_else(|_| line.split_whitespace().map(String::from).collect());
+    if line.ends_with(char::is_whitespace) || words.is_empty() {
+        words.push(String::new());
+    }
+    words.into_iter().skip(1).collect()
+}
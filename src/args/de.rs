@@ -0,0 +1,227 @@
+//! Deserializes a Config into any type implementing serde::Deserialize
+//! Single values become scalars, switches become bools and options with several values become sequences
+
+use super::{Config, OptionPolicy, ParseError};
+use serde::de::{self, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+impl de::Error for ParseError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ParseError::Custom(msg.to_string())
+    }
+}
+
+// Deserializes the options of a config as a map or struct
+pub(super) struct ConfigDeserializer<'a>(pub(super) &'a Config);
+
+impl<'a> ConfigDeserializer<'a> {
+    // The field `dry_run` refers to the option `dry-run` unless an option with the exact name exists
+    fn option(&self, field: &str) -> Option<&'a [String]> {
+        self.0
+            .option(field)
+            .or_else(|| self.0.option(&field.replace('_', "-")))
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ConfigDeserializer<'a> {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        let mut names: Vec<&str> = self.0.parsed.keys().map(|name| name.as_str()).collect();
+        names.sort_unstable();
+        visitor.visit_map(OptionsAccess {
+            config: self,
+            names: names.into_iter(),
+            values: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParseError> {
+        // Every field is visited so a missing switch becomes false and a missing option None
+        visitor.visit_map(OptionsAccess {
+            config: self,
+            names: fields.iter().copied(),
+            values: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct OptionsAccess<'a, I> {
+    config: ConfigDeserializer<'a>,
+    names: I,
+    values: Option<ValuesDeserializer<'a>>,
+}
+
+impl<'de, 'a, I: Iterator<Item = &'a str>> de::MapAccess<'de> for OptionsAccess<'a, I> {
+    type Error = ParseError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ParseError> {
+        let name = match self.names.next() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        self.values = Some(ValuesDeserializer {
+            name: name.to_string(),
+            values: self.config.option(name),
+        });
+        seed.deserialize(name.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ParseError> {
+        match self.values.take() {
+            Some(values) => seed.deserialize(values),
+            None => Err(de::Error::custom("Value requested before key")),
+        }
+    }
+}
+
+// Deserializes the values of a single option, None if the option wasn't given
+struct ValuesDeserializer<'a> {
+    name: String,
+    values: Option<&'a [String]>,
+}
+
+impl<'a> ValuesDeserializer<'a> {
+    // The single value of an option
+    fn single(&self) -> Result<&'a str, ParseError> {
+        match self.values {
+            Some([value]) => Ok(value),
+            Some(values) => Err(ParseError::WrongValueCount {
+                name: self.name.clone(),
+                count: values.len(),
+                expected: OptionPolicy::Exact(1),
+                span: None,
+            }),
+            None => Err(ParseError::MissingRequired {
+                name: self.name.clone(),
+            }),
+        }
+    }
+
+    fn parse<T>(&self) -> Result<T, ParseError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        Config::parse_value(&self.name, self.single()?)
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ValuesDeserializer<'a> {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.values {
+            None => visitor.visit_none(),
+            Some([]) => visitor.visit_bool(true),
+            Some([value]) => visitor.visit_str(value),
+            Some(_) => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.values {
+            None => visitor.visit_bool(false),
+            Some([]) => visitor.visit_bool(true),
+            Some(_) => visitor.visit_bool(self.parse()?),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_str(self.single()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.values {
+            None => visitor.visit_none(),
+            Some(_) => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        let values = self.values.unwrap_or_default();
+        visitor.visit_seq(de::value::SeqDeserializer::new(values.iter().map(
+            |value| ValuesDeserializer {
+                name: self.name.clone(),
+                values: Some(std::slice::from_ref(value)),
+            },
+        )))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParseError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParseError> {
+        visitor.visit_enum(self.single()?.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, ParseError> for ValuesDeserializer<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
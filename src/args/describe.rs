@@ -0,0 +1,87 @@
+//! Describes the option specifications as JSON, e.g; for external documentation or completion generators
+
+use super::{Config, DuplicateKeyPolicy, DuplicatePolicy, OptionPolicy, OptionSpec};
+use serde_json::{json, Value};
+
+impl Config {
+    /// Describes every spec as a JSON object in an array, including the hidden ones<br>
+    /// The objects hold the name, short abbreviation, description, policy, defaults, choices and the other attributes of the spec<br>
+    /// The policy is an object with the least and most number of values, `null` if unbounded, or "finalize" and "finalize_ignore"
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1))
+    ///     .default_values(&["a.out"])];
+    ///
+    /// let description = Config::describe(&specs);
+    /// assert_eq!(description[0]["name"], "output");
+    /// assert_eq!(description[0]["short"], "o");
+    /// assert_eq!(description[0]["policy"]["min"], 1);
+    /// assert_eq!(description[0]["defaults"][0], "a.out");
+    /// ```
+    pub fn describe(specs: &[OptionSpec]) -> Value {
+        Value::Array(specs.iter().map(describe_spec).collect())
+    }
+}
+
+// The JSON object of one spec
+fn describe_spec(spec: &OptionSpec) -> Value {
+    let aliases = |visible: bool| -> Vec<&str> {
+        spec.aliases
+            .iter()
+            .filter(|(_, alias_visible)| *alias_visible == visible)
+            .map(|(alias, _)| alias.as_ref())
+            .collect()
+    };
+
+    json!({
+        "name": spec.name,
+        "short": spec.abrev.map(String::from),
+        "long": spec.long,
+        "positional": spec.positional,
+        "trailing": spec.trailing,
+        "description": spec.desc,
+        "long_description": spec.long_desc,
+        "required": spec.required,
+        "policy": describe_policy(spec.policy),
+        "aliases": aliases(false),
+        "visible_aliases": aliases(true),
+        "duplicates": match spec.duplicates {
+            DuplicatePolicy::Error => "error",
+            DuplicatePolicy::LastWins => "last_wins",
+            DuplicatePolicy::Append => "append",
+        },
+        "key_value": spec.key_value.as_ref().map(|policy| match policy {
+            DuplicateKeyPolicy::Error => "error",
+            DuplicateKeyPolicy::FirstWins => "first_wins",
+            DuplicateKeyPolicy::LastWins => "last_wins",
+        }),
+        "choices": spec.choices,
+        "defaults": spec.defaults,
+        "default_missing": spec.missing,
+        "env": spec.env,
+        "requires": spec.requires,
+        "required_unless": spec.required_unless,
+        "required_if": spec.required_if,
+        "negatable": spec.negatable,
+        "hidden": spec.hidden,
+        "deprecated": spec.deprecated,
+        "global": spec.global,
+        "exclusive": spec.exclusive,
+        "allow_hyphen_values": spec.hyphen_values,
+        "delimiter": spec.delimiter.map(String::from),
+        "section": spec.section,
+        "value_hint": spec.value_hint.name(),
+        "value_name": spec.value_name,
+        "display_order": spec.display_order,
+    })
+}
+
+// The number of values as an object with min and max, the finalizing policies by name
+fn describe_policy(policy: OptionPolicy) -> Value {
+    match policy {
+        OptionPolicy::Finalize() => json!("finalize"),
+        OptionPolicy::FinalizeIgnore() => json!("finalize_ignore"),
+        _ => json!({ "min": policy.min(), "max": policy.max() }),
+    }
+}
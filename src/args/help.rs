@@ -0,0 +1,335 @@
+//! Renders the help of a program from a template, the options are listed like Config::generate_usage_wrapped
+
+use super::{
+    paint, terminal_width, wrap, ColorChoice, Command, Config, English, HelpText, Messages,
+    OptionSpec, UsageLayout, UsageOrder, MIN_HELP_TEXT_WIDTH, STYLE_NAME,
+};
+
+/// The layout of the help when no template is set, see Help::template<br>
+/// The title of the options is translated, see Help::messages
+pub const DEFAULT_HELP_TEMPLATE: &str =
+    "{before-help}\n\n{about}\n\n{usage}\n\n{commands}\n\nOptions:\n{options}\n{examples}\n\n{after-help}";
+
+/// Renders the help of a program by filling in the placeholders of a template<br>
+/// The placeholders are `{before-help}`, `{about}`, `{usage}`, `{commands}`, `{options}`, `{examples}` and `{after-help}`, see DEFAULT_HELP_TEMPLATE<br>
+/// `{usage}` is the one line synopsis, see Config::generate_synopsis, `{options}` the generated listing of the specs and `{examples}` the examples under an `Examples:` title<br>
+/// `{commands}` lists the subcommands with their visible aliases under a `Commands:` title<br>
+/// The blank lines around empty placeholders are collapsed, the others are kept as written, and the titles and labels are translated by Help::messages
+/// ## Example
+/// ```
+/// # use libcli::args::{Help, OptionPolicy, OptionSpec};
+/// let specs = [OptionSpec::new('o', "output", "Output file", true, OptionPolicy::Exact(1))];
+///
+/// let help = Help::new("myapp", &specs)
+///     .about("Processes files")
+///     .after_help("Report bugs to <bugs@example.com>")
+///     .template("{about}\n\n{options}\n{after-help}")
+///     .width(80)
+///     .render();
+/// assert!(help.starts_with("Processes files\n\n    -o, --output"));
+/// assert!(help.ends_with("\nReport bugs to <bugs@example.com>\n"));
+/// ```
+pub struct Help<'a> {
+    program: String,
+    specs: &'a [OptionSpec],
+    commands: &'a [Command],
+    // Options added by the parser, listed after the specs, e.g; --help
+    added: Vec<OptionSpec>,
+    about: String,
+    long_about: Option<String>,
+    examples: String,
+    before_help: String,
+    after_help: String,
+    template: Option<String>,
+    width: Option<usize>,
+    color: ColorChoice,
+    env_prefix: Option<String>,
+    long: bool,
+    order: UsageOrder,
+    messages: &'a dyn Messages,
+}
+
+impl<'a> Help<'a> {
+    /// Creates the help of program with the options of specs, laid out by DEFAULT_HELP_TEMPLATE
+    pub fn new(program: &str, specs: &'a [OptionSpec]) -> Self {
+        Self {
+            program: program.to_string(),
+            specs,
+            commands: &[],
+            added: Vec::new(),
+            about: String::new(),
+            long_about: None,
+            examples: String::new(),
+            before_help: String::new(),
+            after_help: String::new(),
+            template: None,
+            width: None,
+            color: ColorChoice::Never,
+            env_prefix: None,
+            long: false,
+            order: UsageOrder::Required,
+            messages: &English,
+        }
+    }
+
+    /// Sets the subcommands, listed for `{commands}`
+    pub fn commands(mut self, commands: &'a [Command]) -> Self {
+        self.commands = commands;
+        self
+    }
+
+    /// Sets the description of the program, filled in for `{about}`
+    pub fn about(mut self, about: &str) -> Self {
+        self.about = about.to_string();
+        self
+    }
+
+    /// Sets a longer description of the program, filled in for `{about}` in the long help
+    pub fn long_about(mut self, long_about: &str) -> Self {
+        self.long_about = Some(long_about.to_string());
+        self
+    }
+
+    /// Sets the examples, filled in for `{examples}` indented under an `Examples:` title
+    pub fn examples(mut self, examples: &str) -> Self {
+        self.examples = examples.to_string();
+        self
+    }
+
+    /// Sets the text filled in for `{before-help}`, at the top of the help by default, e.g; a license note
+    pub fn before_help(mut self, before_help: &str) -> Self {
+        self.before_help = before_help.to_string();
+        self
+    }
+
+    /// Sets the text filled in for `{after-help}`, e.g; where to report bugs
+    pub fn after_help(mut self, after_help: &str) -> Self {
+        self.after_help = after_help.to_string();
+        self
+    }
+
+    /// Sets the layout of the help, replacing DEFAULT_HELP_TEMPLATE
+    pub fn template(mut self, template: &str) -> Self {
+        self.template = Some(template.to_string());
+        self
+    }
+
+    /// Sets the width the options are fitted to, instead of the width of the terminal
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets when the options are colored, they aren't by default
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Renders the long help when long is true, with the long descriptions separated by blank lines<br>
+    /// The descriptions set with OptionSpec::long_desc and Help::long_about are used, falling back to the short ones<br>
+    /// E.g; the long help for `--help` and the compact one for `-h`
+    pub fn long(mut self, long: bool) -> Self {
+        self.long = long;
+        self
+    }
+
+    /// Sets the order the options are listed in, the required options first by default
+    pub fn order(mut self, order: UsageOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Lists the environment variable of every option, `<PREFIX>_<NAME>`, see ConfigBuilder::env_prefix<br>
+    /// The variables set with OptionSpec::env are always listed
+    pub fn env_prefix(mut self, prefix: &str) -> Self {
+        self.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Sets the messages the titles and labels are taken from, English by default
+    pub fn messages(mut self, messages: &'a dyn Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    // Lists the options added by the parser after the specs
+    pub(super) fn added(mut self, added: Vec<OptionSpec>) -> Self {
+        self.added = added;
+        self
+    }
+
+    /// Renders the help by filling in the template, placeholders within the texts themselves are kept as written
+    pub fn render(&self) -> String {
+        let width = self.width.unwrap_or_else(terminal_width);
+        let layout = UsageLayout {
+            column: Config::usage_column(self.specs.iter().chain(&self.added), width),
+            width,
+            color: self.color.enabled(),
+            env_prefix: self.env_prefix.as_deref(),
+            long: self.long,
+            messages: self.messages,
+        };
+        let mut options =
+            Config::render_usage(self.specs, true, true, layout.color, self.order, |spec| {
+                spec.usage_columns(&layout)
+            });
+        let layout = UsageLayout {
+            env_prefix: None,
+            ..layout
+        };
+        for spec in &self.added {
+            options += &spec.usage_columns(&layout);
+        }
+
+        let title = |text: HelpText| format!("{}:", self.messages.help(text));
+        let examples = match self.examples.trim().is_empty() {
+            true => String::new(),
+            false => self
+                .examples
+                .lines()
+                .fold(title(HelpText::Examples), |examples, line| {
+                    match line.trim().is_empty() {
+                        true => examples + "\n",
+                        false => examples + "\n    " + line,
+                    }
+                }),
+        };
+
+        let commands = self.render_commands(width, layout.color);
+
+        let about = match (&self.long_about, self.long) {
+            (Some(long_about), true) => long_about,
+            _ => &self.about,
+        };
+
+        let template = match &self.template {
+            Some(template) => template.clone(),
+            None => DEFAULT_HELP_TEMPLATE.replace("Options:", &title(HelpText::Options)),
+        };
+        let usage = format!(
+            "{} {}",
+            title(HelpText::Usage),
+            Config::generate_synopsis(&self.program, self.specs)
+        );
+        let help = fill(
+            &template,
+            &[
+                ("{before-help}", self.before_help.trim_end()),
+                ("{about}", about.trim_end()),
+                ("{usage}", &usage),
+                ("{commands}", &commands),
+                ("{options}", &options),
+                ("{examples}", &examples),
+                ("{after-help}", self.after_help.trim_end()),
+            ],
+        );
+
+        // Collapses the blank lines around the placeholders left empty to the longest run of them
+        // The blank lines at the start and end are dropped
+        let mut lines: Vec<String> = Vec::new();
+        let (mut blanks, mut longest) = (0, 0);
+        for line in help.lines().map(str::trim_end) {
+            let text = line.replace(EMPTY_PLACEHOLDER, "");
+            match (text.trim_end().is_empty(), line.is_empty()) {
+                (true, true) => {
+                    blanks += 1;
+                    longest = longest.max(blanks);
+                }
+                // The line of an empty placeholder separates the runs of blank lines around it
+                (true, false) => blanks = 0,
+                (false, _) => {
+                    if !lines.is_empty() {
+                        lines.extend(std::iter::repeat(String::new()).take(longest));
+                    }
+                    lines.push(text.trim_end().to_string());
+                    blanks = 0;
+                    longest = 0;
+                }
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+
+    // Lists the subcommands in two columns, the names and visible aliases followed by the description
+    fn render_commands(&self, width: usize, color: bool) -> String {
+        let entries: Vec<(String, String)> = self
+            .commands
+            .iter()
+            .map(|cmd| {
+                let names: Vec<&str> = std::iter::once(cmd.name())
+                    .chain(
+                        cmd.aliases
+                            .iter()
+                            .filter(|(_, visible)| *visible)
+                            .map(|(alias, _)| alias.as_ref()),
+                    )
+                    .collect();
+                let mut desc = cmd.desc().to_string();
+                if cmd.is_default() {
+                    desc += &format!(" [{}]", self.messages.help(HelpText::Default));
+                }
+                (names.join(", "), desc.trim().to_string())
+            })
+            .collect();
+        if entries.is_empty() {
+            return String::new();
+        }
+
+        let longest = entries
+            .iter()
+            .map(|(names, _)| names.chars().count() + 4)
+            .max()
+            .unwrap_or(0);
+        let column = (longest + 2).min(width / 2);
+        let text_width = width.saturating_sub(column).max(MIN_HELP_TEXT_WIDTH);
+
+        let mut listing = format!("{}:", self.messages.help(HelpText::Commands));
+        for (names, desc) in entries {
+            let names_width = names.chars().count() + 4;
+            listing += &format!("\n    {}", paint(&names, STYLE_NAME, color));
+            let lines = wrap(&desc, text_width);
+            for (i, line) in lines.iter().enumerate() {
+                match i == 0 && names_width < column {
+                    true => listing += &" ".repeat(column - names_width),
+                    false => listing += &format!("\n{}", " ".repeat(column)),
+                }
+                listing += line;
+            }
+        }
+        listing
+    }
+}
+
+// Marks where a placeholder was filled in with nothing, see Help::render
+const EMPTY_PLACEHOLDER: char = '\0';
+
+// Replaces the placeholders of template with their values in a single pass
+// The values are inserted as is, placeholders within them are left untouched
+// An empty value is replaced by EMPTY_PLACEHOLDER
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled += &rest[..start];
+        rest = &rest[start..];
+        match values
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            Some((placeholder, "")) => {
+                filled.push(EMPTY_PLACEHOLDER);
+                rest = &rest[placeholder.len()..];
+            }
+            Some((placeholder, value)) => {
+                filled += value;
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    filled + rest
+}
@@ -0,0 +1,339 @@
+//! Loads option specifications from a declarative TOML or JSON file, the counterpart of Config::describe
+//! Each option is a table with the keys written by Config::describe, only name is needed
+
+use super::{
+    validate_specs, Config, DuplicateKeyPolicy, DuplicatePolicy, OptionPolicy, OptionSpec,
+    ParseError, ValueHint,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+// A value of either file format
+enum Node {
+    Bool(bool),
+    Integer(i64),
+    String(String),
+    Array(Vec<Node>),
+    Table(HashMap<String, Node>),
+    // A value no key accepts, e.g; a float or a JSON null outside of an object
+    Other,
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::Value> for Node {
+    fn from(value: toml::Value) -> Self {
+        match value {
+            toml::Value::Boolean(v) => Node::Bool(v),
+            toml::Value::Integer(v) => Node::Integer(v),
+            toml::Value::String(v) => Node::String(v),
+            toml::Value::Array(v) => Node::Array(v.into_iter().map(Node::from).collect()),
+            toml::Value::Table(v) => {
+                Node::Table(v.into_iter().map(|(k, v)| (k, Node::from(v))).collect())
+            }
+            toml::Value::Float(_) | toml::Value::Datetime(_) => Node::Other,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for Node {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Bool(v) => Node::Bool(v),
+            serde_json::Value::Number(v) => v.as_i64().map(Node::Integer).unwrap_or(Node::Other),
+            serde_json::Value::String(v) => Node::String(v),
+            serde_json::Value::Array(v) => Node::Array(v.into_iter().map(Node::from).collect()),
+            // A null value is treated like a left out key
+            serde_json::Value::Object(v) => Node::Table(
+                v.into_iter()
+                    .filter(|(_, v)| !v.is_null())
+                    .map(|(k, v)| (k, Node::from(v)))
+                    .collect(),
+            ),
+            serde_json::Value::Null => Node::Other,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the specs from a declarative file, e.g; a `cli.toml` maintained next to the program<br>
+    /// A file ending in `.json` is read as JSON with the `json` feature, any other file as TOML with the `toml` feature<br>
+    /// See Config::specs_from_toml for the format, fails with ParseError::File if the file can't be read or holds an invalid spec
+    /// ## Example
+    /// ```no_run
+    /// # use libcli::args::Config;
+    /// let specs = Config::load_specs("cli.toml").unwrap();
+    /// let config = Config::new_env(&specs).unwrap();
+    /// ```
+    pub fn load_specs(path: impl AsRef<Path>) -> Result<Vec<OptionSpec>, ParseError> {
+        let path = path.as_ref();
+        let file_error = |reason: String| ParseError::File {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let contents = std::fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+        let json = path
+            .extension()
+            .is_some_and(|extension| extension == "json");
+        let specs = match json {
+            #[cfg(feature = "json")]
+            true => Config::specs_from_json(&contents),
+            #[cfg(not(feature = "json"))]
+            true => return Err(file_error(String::from("the json feature is disabled"))),
+            #[cfg(feature = "toml")]
+            false => Config::specs_from_toml(&contents),
+            #[cfg(not(feature = "toml"))]
+            false => return Err(file_error(String::from("the toml feature is disabled"))),
+        };
+        specs.map_err(|e| file_error(e.to_string()))
+    }
+
+    /// Reads the specs from TOML, an `[[options]]` table for every spec in order<br>
+    /// The keys are the ones written by Config::describe, only name is needed and the rest default like OptionSpec::new<br>
+    /// The policy is a table with min and max, max left out if unbounded, or "finalize" and "finalize_ignore"<br>
+    /// Embed the file with `include_str!` to load it at build time, the loaded specs are checked with validate_specs
+    /// ## Example
+    /// ```
+    /// # use libcli::args::Config;
+    /// let specs = Config::specs_from_toml(r#"
+    ///     [[options]]
+    ///     name = "output"
+    ///     short = "o"
+    ///     description = "Output file"
+    ///     policy = { min = 1, max = 1 }
+    ///     defaults = ["a.out"]
+    /// "#).unwrap();
+    ///
+    /// let config = Config::new(&["./test"], &specs).unwrap();
+    /// assert_eq!(config.option("output"), Some(&[String::from("a.out")][..]));
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn specs_from_toml(contents: &str) -> Result<Vec<OptionSpec>, ParseError> {
+        let mut table: toml::Table = contents
+            .parse()
+            .map_err(|e: toml::de::Error| ParseError::Custom(e.to_string()))?;
+        let options = table
+            .remove("options")
+            .map(Node::from)
+            .unwrap_or(Node::Array(Vec::new()));
+        load_specs(options)
+    }
+
+    /// Reads the specs from JSON, an array of objects in the format written by Config::describe<br>
+    /// An object with the array under `options` is read as well, see Config::specs_from_toml for the keys
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1))];
+    ///
+    /// let loaded = Config::specs_from_json(&Config::describe(&specs).to_string()).unwrap();
+    /// assert_eq!(Config::describe(&loaded), Config::describe(&specs));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn specs_from_json(contents: &str) -> Result<Vec<OptionSpec>, ParseError> {
+        let value: serde_json::Value =
+            serde_json::from_str(contents).map_err(|e| ParseError::Custom(e.to_string()))?;
+        match Node::from(value) {
+            Node::Table(mut table) => load_specs(table.remove("options").unwrap_or(Node::Other)),
+            options => load_specs(options),
+        }
+    }
+}
+
+// Creates the specs of an array of tables and checks them
+fn load_specs(options: Node) -> Result<Vec<OptionSpec>, ParseError> {
+    let options = match options {
+        Node::Array(options) => options,
+        _ => {
+            return Err(ParseError::Custom(String::from(
+                "expected an array of options",
+            )))
+        }
+    };
+
+    let specs = options
+        .into_iter()
+        .enumerate()
+        .map(|(index, option)| match option {
+            Node::Table(table) => load_spec(table),
+            _ => Err(format!("option {} is not a table", index + 1)),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ParseError::Custom)?;
+
+    validate_specs(&specs).map_err(ParseError::InvalidSpec)?;
+    Ok(specs)
+}
+
+// Creates a spec from the keys of its table
+fn load_spec(mut table: HashMap<String, Node>) -> Result<OptionSpec, String> {
+    let name = match table.remove("name") {
+        Some(Node::String(name)) => name,
+        _ => return Err(String::from("an option has no name")),
+    };
+    let mut fields = Fields { name: &name, table };
+
+    let abrev = fields.char("short")?.unwrap_or('\0');
+    let policy = fields.policy()?;
+    let desc = fields.string("description")?.unwrap_or_default();
+    let required = fields.bool("required")?;
+    let mut spec = OptionSpec::new(abrev, name.clone(), desc, required, policy);
+
+    spec.positional = fields.bool_or("positional", spec.positional)?;
+    spec.trailing = fields.bool("trailing")?;
+    let long = spec.long && !spec.positional && !spec.trailing;
+    spec.long = fields.bool_or("long", long)?;
+    spec.long_desc = fields.string("long_description")?.map(Into::into);
+    spec.aliases = fields
+        .strings("aliases")?
+        .into_iter()
+        .map(|alias| (alias.into(), false))
+        .chain(
+            fields
+                .strings("visible_aliases")?
+                .into_iter()
+                .map(|alias| (alias.into(), true)),
+        )
+        .collect();
+    spec.duplicates = match fields.string("duplicates")?.as_deref() {
+        None | Some("error") => DuplicatePolicy::Error,
+        Some("last_wins") => DuplicatePolicy::LastWins,
+        Some("append") => DuplicatePolicy::Append,
+        Some(_) => return Err(fields.invalid("duplicates")),
+    };
+    spec.key_value = match fields.string("key_value")?.as_deref() {
+        None => None,
+        Some("error") => Some(DuplicateKeyPolicy::Error),
+        Some("first_wins") => Some(DuplicateKeyPolicy::FirstWins),
+        Some("last_wins") => Some(DuplicateKeyPolicy::LastWins),
+        Some(_) => return Err(fields.invalid("key_value")),
+    };
+    spec.choices = fields.cows("choices")?;
+    spec.defaults = fields.cows("defaults")?;
+    spec.missing = fields.cows("default_missing")?;
+    spec.env = fields.string("env")?.map(Into::into);
+    spec.requires = fields.cows("requires")?;
+    spec.required_unless = fields.cows("required_unless")?;
+    spec.required_if = fields.cows("required_if")?;
+    spec.negatable = fields.bool("negatable")?;
+    spec.hidden = fields.bool("hidden")?;
+    spec.deprecated = fields.string("deprecated")?.map(Into::into);
+    spec.global = fields.bool("global")?;
+    spec.exclusive = fields.bool("exclusive")?;
+    spec.hyphen_values = fields.bool("allow_hyphen_values")?;
+    spec.delimiter = fields.char("delimiter")?;
+    spec.section = fields.string("section")?.map(Into::into);
+    spec.value_hint = match fields.string("value_hint")? {
+        None => ValueHint::Unknown,
+        Some(hint) => ValueHint::from_name(&hint).ok_or_else(|| fields.invalid("value_hint"))?,
+    };
+    spec.value_name = fields.string("value_name")?.map(Into::into);
+    spec.display_order = fields.usize("display_order")?;
+
+    match fields.table.keys().next() {
+        Some(key) => Err(format!("option '{}' has an unknown key '{}'", name, key)),
+        None => Ok(spec),
+    }
+}
+
+// The remaining keys of the table of a spec, each taken once
+struct Fields<'a> {
+    name: &'a str,
+    table: HashMap<String, Node>,
+}
+
+impl Fields<'_> {
+    fn invalid(&self, key: &str) -> String {
+        format!("option '{}' has an invalid {}", self.name, key)
+    }
+
+    fn take(&mut self, key: &str) -> Option<Node> {
+        self.table.remove(key)
+    }
+
+    fn string(&mut self, key: &str) -> Result<Option<String>, String> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(Node::String(v)) => Ok(Some(v)),
+            Some(_) => Err(self.invalid(key)),
+        }
+    }
+
+    // A string of a single character
+    fn char(&mut self, key: &str) -> Result<Option<char>, String> {
+        match self.string(key)? {
+            None => Ok(None),
+            Some(v) if v.chars().count() == 1 => Ok(v.chars().next()),
+            Some(_) => Err(self.invalid(key)),
+        }
+    }
+
+    fn bool(&mut self, key: &str) -> Result<bool, String> {
+        self.bool_or(key, false)
+    }
+
+    fn bool_or(&mut self, key: &str, default: bool) -> Result<bool, String> {
+        match self.take(key) {
+            None => Ok(default),
+            Some(Node::Bool(v)) => Ok(v),
+            Some(_) => Err(self.invalid(key)),
+        }
+    }
+
+    fn usize(&mut self, key: &str) -> Result<Option<usize>, String> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(Node::Integer(v)) if v >= 0 => Ok(Some(v as usize)),
+            Some(_) => Err(self.invalid(key)),
+        }
+    }
+
+    // A list of strings, a single string is taken as a list of one
+    fn strings(&mut self, key: &str) -> Result<Vec<String>, String> {
+        match self.take(key) {
+            None => Ok(Vec::new()),
+            Some(Node::String(v)) => Ok(vec![v]),
+            Some(Node::Array(array)) => array
+                .into_iter()
+                .map(|node| match node {
+                    Node::String(v) => Ok(v),
+                    _ => Err(self.invalid(key)),
+                })
+                .collect(),
+            Some(_) => Err(self.invalid(key)),
+        }
+    }
+
+    fn cows(&mut self, key: &str) -> Result<Vec<std::borrow::Cow<'static, str>>, String> {
+        Ok(self.strings(key)?.into_iter().map(Into::into).collect())
+    }
+
+    // The policy from min and max or its name, a switch when left out
+    fn policy(&mut self) -> Result<OptionPolicy, String> {
+        let mut table = match self.take("policy") {
+            None => return Ok(OptionPolicy::Exact(0)),
+            Some(Node::String(name)) if name == "finalize" => return Ok(OptionPolicy::Finalize()),
+            Some(Node::String(name)) if name == "finalize_ignore" => {
+                return Ok(OptionPolicy::FinalizeIgnore())
+            }
+            Some(Node::Table(table)) => Fields {
+                name: self.name,
+                table,
+            },
+            Some(_) => return Err(self.invalid("policy")),
+        };
+
+        let min = table.usize("min")?.unwrap_or(0);
+        let max = table.usize("max")?;
+        if !table.table.is_empty() {
+            return Err(self.invalid("policy"));
+        }
+        Ok(match max {
+            Some(max) if max == min => OptionPolicy::Exact(min),
+            Some(max) if min == 0 => OptionPolicy::AtMost(max),
+            Some(max) => OptionPolicy::Range(min, max),
+            None => OptionPolicy::AtLeast(min),
+        })
+    }
+}
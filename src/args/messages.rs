@@ -0,0 +1,129 @@
+//! Translation of the messages of the parser and the generated help, see Messages
+
+use super::{OptionPolicy, ParseError};
+
+/// The texts of the generated help which can be translated, see Messages::help
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpText {
+    /// The title of the synopsis, "Usage"
+    Usage,
+    /// The title of the options in the default template, "Options"
+    Options,
+    /// The title of the subcommands, "Commands"
+    Commands,
+    /// The title of the examples, "Examples"
+    Examples,
+    /// The marker of a required option, "required"
+    Required,
+    /// The label of the number of values, "values"
+    Values,
+    /// Follows the number of values of an option with a delimiter, "separated by"
+    SeparatedBy,
+    /// The label of the default values, "default"
+    Default,
+    /// The label of the values used when the option is given without any, "default without value"
+    DefaultWithoutValue,
+    /// The label of the environment variable, "env"
+    Env,
+    /// The label of the choices, "possible values"
+    PossibleValues,
+    /// The label of the deprecation note, "deprecated"
+    Deprecated,
+    /// The label of the options which make an option optional, "required unless"
+    RequiredUnless,
+    /// The label of the options which make an option required, "required if"
+    RequiredIf,
+    /// The label of the options an option depends on, "requires"
+    Requires,
+}
+
+impl std::fmt::Display for HelpText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            HelpText::Usage => "Usage",
+            HelpText::Options => "Options",
+            HelpText::Commands => "Commands",
+            HelpText::Examples => "Examples",
+            HelpText::Required => "required",
+            HelpText::Values => "values",
+            HelpText::SeparatedBy => "separated by",
+            HelpText::Default => "default",
+            HelpText::DefaultWithoutValue => "default without value",
+            HelpText::Env => "env",
+            HelpText::PossibleValues => "possible values",
+            HelpText::Deprecated => "deprecated",
+            HelpText::RequiredUnless => "required unless",
+            HelpText::RequiredIf => "required if",
+            HelpText::Requires => "requires",
+        })
+    }
+}
+
+/// A catalog of the messages shown to the user, implement it to translate them<br>
+/// Every method defaults to the English message, only the translated ones need to be implemented<br>
+/// The help uses it through Help::messages, ConfigBuilder::messages and App::messages, the usage through Config::generate_usage_with and a parse error is translated with Messages::error
+/// ## Example
+/// ```
+/// # use libcli::args::{Config, HelpText, Messages, OptionPolicy, OptionSpec, ParseError};
+/// struct German;
+///
+/// impl Messages for German {
+///     fn error(&self, err: &ParseError) -> String {
+///         match err {
+///             ParseError::MissingRequired { name } => format!("Erforderliche Option '{}' fehlt", name),
+///             _ => err.to_string(),
+///         }
+///     }
+///
+///     fn help(&self, text: HelpText) -> String {
+///         match text {
+///             HelpText::Usage => String::from("Aufruf"),
+///             HelpText::Options => String::from("Optionen"),
+///             _ => text.to_string(),
+///         }
+///     }
+/// }
+///
+/// let specs = [OptionSpec::new('o', "output", "Output file", true, OptionPolicy::Exact(1))];
+/// let err = Config::new(&["./test"], &specs).err().unwrap();
+/// assert_eq!(German.error(&err), "Erforderliche Option 'output' fehlt");
+/// ```
+pub trait Messages {
+    /// Returns the message of a parse error, the Display of the error by default
+    fn error(&self, err: &ParseError) -> String {
+        err.to_string()
+    }
+
+    /// Returns a title or label of the generated help
+    fn help(&self, text: HelpText) -> String {
+        text.to_string()
+    }
+
+    /// Returns the number of values a policy accepts, e.g; "exactly 1" or "1 to 3"
+    fn policy(&self, policy: &OptionPolicy) -> String {
+        policy.to_string()
+    }
+
+    /// Returns the hint following a parse error, "Try '--help' for more information"
+    fn hint(&self) -> String {
+        String::from("Try '--help' for more information")
+    }
+
+    /// Returns the report of a parse error printed before the program exits, see Config::parse_or_exit<br>
+    /// The translated error is followed by the synopsis of the program and the hint
+    fn report(&self, err: &ParseError, synopsis: &str) -> String {
+        format!(
+            "{}\n{}: {}\n{}\n",
+            self.error(err),
+            self.help(HelpText::Usage),
+            synopsis,
+            self.hint()
+        )
+    }
+}
+
+/// The English messages, used when no other Messages are given
+#[derive(Debug, Clone, Copy, Default)]
+pub struct English;
+
+impl Messages for English {}
@@ -0,0 +1,197 @@
+//! Renders the option specifications as documentation, e.g; a man page
+//! The hidden options are left out, like in the generated usage
+
+use super::{Config, OptionSpec, UNNAMED};
+
+impl Config {
+    /// Generates a man page in troff format for the program app, with the options of specs in an OPTIONS section<br>
+    /// The positional arguments are listed in an ARGUMENTS section, about is the one line description in the NAME section<br>
+    /// Install the page as `<app>.1`, e.g; `/usr/share/man/man1/myapp.1`
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1)),
+    /// ];
+    ///
+    /// let page = Config::generate_man("myapp", "1.0.0", "Processes files", &specs);
+    /// assert!(page.starts_with(".TH MYAPP 1"));
+    /// ```
+    pub fn generate_man(app: &str, version: &str, about: &str, specs: &[OptionSpec]) -> String {
+        let mut page = format!(
+            ".TH {} 1 \"\" \"{} {}\" \"User Commands\"\n",
+            roff(&app.to_uppercase()),
+            roff(app),
+            roff(version)
+        );
+        page += &format!(".SH NAME\n{} \\- {}\n", roff(app), roff(about));
+
+        let synopsis = Config::generate_synopsis(app, specs);
+        page += &format!(".SH SYNOPSIS\n{}\n", roff_line(&synopsis));
+
+        let visible: Vec<&OptionSpec> = specs.iter().filter(|spec| !spec.hidden).collect();
+        let (arguments, options): (Vec<&OptionSpec>, Vec<&OptionSpec>) = visible
+            .into_iter()
+            .partition(|spec| spec.positional || spec.trailing);
+
+        let arguments: Vec<&OptionSpec> = arguments
+            .into_iter()
+            .filter(|spec| spec.policy.max() != Some(0))
+            .collect();
+        if !arguments.is_empty() {
+            page += ".SH ARGUMENTS\n";
+            for spec in arguments {
+                let name = match spec.name.as_ref() {
+                    UNNAMED => "args",
+                    name => name,
+                };
+                let name = match spec.trailing {
+                    true => format!("\\-\\- \\fI{}\\fR...", roff(name)),
+                    false => format!("\\fI{}\\fR", roff(name)),
+                };
+                page += &man_entry(&name, spec);
+            }
+        }
+
+        if !options.is_empty() {
+            page += ".SH OPTIONS\n";
+            for spec in options {
+                let mut names: Vec<String> = spec
+                    .abrev
+                    .iter()
+                    .map(|abrev| format!("\\fB\\-{}\\fR", roff(&abrev.to_string())))
+                    .collect();
+                if spec.long {
+                    let name = match spec.negatable && spec.takes_no_values() {
+                        true => format!("[no\\-]{}", roff(&spec.name)),
+                        false => roff(&spec.name),
+                    };
+                    names.push(format!("\\fB\\-\\-{}\\fR", name));
+                    names.extend(
+                        spec.aliases
+                            .iter()
+                            .filter(|(_, visible)| *visible)
+                            .map(|(alias, _)| format!("\\fB\\-\\-{}\\fR", roff(alias))),
+                    );
+                }
+                let mut name = names.join(", ");
+                if !spec.takes_no_values() {
+                    let value = spec.value_name.as_deref().unwrap_or("value");
+                    name += &format!(" \\fI{}\\fR", roff(value));
+                }
+                page += &man_entry(&name, spec);
+            }
+        }
+
+        page
+    }
+
+    /// Generates a Markdown table of the options with the columns option, short, required, default and description<br>
+    /// Positional arguments are listed as `<NAME>`, e.g; for the usage section of a README
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{Config, OptionPolicy, OptionSpec};
+    /// let specs = [
+    ///     OptionSpec::new('\0', "(unnamed)", "Input files", false, OptionPolicy::AtLeast(0)),
+    ///     OptionSpec::new('o', "output", "Output file", false, OptionPolicy::Exact(1))
+    ///         .default_values(&["a.out"]),
+    /// ];
+    ///
+    /// let table = Config::generate_markdown(&specs);
+    /// assert!(table.ends_with("| `--output` | `-o` | no | `a.out` | Output file |\n"));
+    /// ```
+    pub fn generate_markdown(specs: &[OptionSpec]) -> String {
+        let mut table = String::from(
+            "| Option | Short | Required | Default | Description |\n| --- | --- | --- | --- | --- |\n",
+        );
+
+        // A positional spec taking no values only rejects stray arguments
+        for spec in specs
+            .iter()
+            .filter(|spec| !spec.hidden && (!spec.positional || spec.policy.max() != Some(0)))
+        {
+            let name = match spec.name.as_ref() {
+                _ if spec.trailing => format!("`-- <{}>...`", spec.name),
+                UNNAMED => String::from("`<args>...`"),
+                name if spec.positional => format!("`<{}>`", name),
+                name if spec.long => format!("`--{}`", name),
+                _ => String::new(),
+            };
+            let short = spec
+                .abrev
+                .map(|abrev| format!("`-{}`", abrev))
+                .unwrap_or_default();
+            let default = match spec.defaults.is_empty() {
+                true => String::new(),
+                false => format!("`{}`", spec.defaults.join(", ")),
+            };
+            let mut desc = spec.desc.lines().collect::<Vec<_>>().join("<br>");
+            if !spec.choices.is_empty() {
+                desc += &format!(" (possible values: {})", spec.choices.join(", "));
+            }
+
+            table += &format!(
+                "| {} | {} | {} | {} | {} |\n",
+                markdown(&name),
+                markdown(&short),
+                if spec.required { "yes" } else { "no" },
+                markdown(&default),
+                markdown(&desc)
+            );
+        }
+
+        table
+    }
+}
+
+// Escapes the characters special to troff inside a line
+fn roff(string: &str) -> String {
+    string.replace('\\', "\\e").replace('-', "\\-")
+}
+
+// Escapes a whole line of text, a line starting with a control character is kept as text
+fn roff_line(line: &str) -> String {
+    let line = roff(line);
+    match line.starts_with('.') || line.starts_with('\'') {
+        true => format!("\\&{}", line),
+        false => line,
+    }
+}
+
+// A tagged paragraph listing an option or argument with its description and attributes
+fn man_entry(name: &str, spec: &OptionSpec) -> String {
+    let mut entry = format!(".TP\n{}\n", name);
+    for line in spec.desc.lines() {
+        entry += &format!("{}\n", roff_line(line));
+    }
+
+    let mut attributes = Vec::new();
+    if spec.required {
+        attributes.push(String::from("required"));
+    }
+    if !spec.takes_no_values() && !spec.positional {
+        attributes.push(format!("values: {}", spec.policy));
+    }
+    if !spec.choices.is_empty() {
+        attributes.push(format!("possible values: {}", spec.choices.join(", ")));
+    }
+    if !spec.defaults.is_empty() {
+        attributes.push(format!("default: {}", spec.defaults.join(", ")));
+    }
+    if let Some(var) = &spec.env {
+        attributes.push(format!("env: {}", var));
+    }
+    if let Some(note) = &spec.deprecated {
+        attributes.push(format!("deprecated: {}", note));
+    }
+    for attribute in attributes {
+        entry += &format!(".br\n[{}]\n", roff(&attribute));
+    }
+    entry
+}
+
+// Escapes the pipes of a Markdown table cell
+fn markdown(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
@@ -1,32 +1,162 @@
 //! Utilities for reading user input
+//! The functions prompt on stdout and read from stdin, see Input for other streams
+use std::fmt::{self, Display};
+use std::fs::File;
 use std::io;
-use std::io::Read;
-use std::io::Write;
+use std::io::{BufRead, Read, StdinLock, Stdout, Write};
+use std::path::Path;
+use std::str::{FromStr, Utf8Error};
+
+#[cfg(feature = "line_edit")]
+mod editor;
+#[cfg(feature = "terminal")]
+mod terminal;
+
+// An Input with its reader and writer boxed, see ConfigBuilder::prompt_input
+pub(crate) type BoxedInput = Input<Box<dyn BufRead>, Box<dyn Write>>;
+
+// The message of the panics of the functions reading from stdin
+const READ_FAILED: &str = "Failed to read from stdin";
+
+/// Returns true if stdin is a terminal, i.e; a user can answer the prompts
+/// Returns false when input is piped or redirected, e.g; `echo y | myapp`
+pub fn is_interactive() -> bool {
+    use std::io::IsTerminal;
+    io::stdin().is_terminal()
+}
+
+/// What an Input does when it isn't interactive, see Input::non_interactive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonInteractive {
+    /// Prompts as usual, the default
+    Prompt,
+    /// Reads the answers without writing the prompts, lists or error messages
+    Silent,
+    /// Fails with ErrorKind::Other instead of prompting, e.g; to require the value on the command line instead
+    Fail,
+}
 
 /// Prints a message and prompt to the console
 /// Returns one line entered from stdin
 /// Includes the newline character, use .trim() to remove
 /// msg and prompt are separated because you usually want to provide msg from a list and prompt is usually the same, this alleviates the need for a read_line(format!(())
 /// Panics if stdin can't be read
+#[deprecated(note = "panics if stdin can't be read, use input::try_read_line")]
 pub fn read_line(msg: &str, prompt: &str) -> String {
-    self::prompt(msg, prompt);
-    let mut string = String::new();
-    io::stdin()
-        .read_line(&mut string)
-        .expect("Failed to read from stdin");
-    string
+    try_read_line(msg, prompt).expect(READ_FAILED)
+}
+
+/// Same as read_line but returns Err instead of panicking if stdin can't be read, e.g; when it is closed
+/// An empty string is returned at the end of stdin
+/// ## Example
+/// ```no_run
+/// use libcli::input;
+/// match input::try_read_line("Name", ": ") {
+///     Ok(name) => println!("Hello {}", name.trim()),
+///     Err(err) => eprintln!("Failed to read the name: {}", err),
+/// }
+/// ```
+pub fn try_read_line(msg: &str, prompt: &str) -> io::Result<String> {
+    Input::stdio().read_line(msg, prompt)
+}
+
+/// Prints a message and prompt to the console
+/// Returns one line entered from stdin without echoing it, e.g; for a password
+/// The newline character is removed
+/// Echo is only disabled with the terminal feature enabled and stdin a terminal, otherwise the line is read like read_line
+/// Ctrl+C restores the echo before the process is interrupted
+/// Returns Err if stdin can't be read
+pub fn read_password(msg: &str, prompt: &str) -> io::Result<String> {
+    Input::stdio().read_password(msg, prompt)
+}
+
+/// Asks a yes or no question, e.g; `Overwrite the file? [y/N]: `
+/// Accepts y, yes, n and no in any case, an empty answer takes the default if any
+/// The question is asked again on any other answer
+/// Returns the default, or false without one, at the end of stdin
+/// Returns Err if stdin can't be read
+pub fn confirm(msg: &str, default: Option<bool>) -> io::Result<bool> {
+    Input::stdio().confirm(msg, default)
+}
+
+/// Prints a message followed by the items numbered from 1 and reads the number of one
+/// The choice is asked again until it is a number in range
+/// Returns the index of the chosen item, see select_item for the item itself
+/// Returns Err if stdin can't be read, with ErrorKind::UnexpectedEof if it ends before a choice
+/// Panics if items is empty
+pub fn select<T: Display>(msg: &str, items: &[T]) -> io::Result<usize> {
+    Input::stdio().select(msg, items)
+}
+
+/// Same as select but returns the chosen item
+pub fn select_item<'a, T: Display>(msg: &str, items: &'a [T]) -> io::Result<&'a T> {
+    Input::stdio().select_item(msg, items)
+}
+
+/// Prints a message followed by the items numbered from 1 and reads the numbers of any of them
+/// The numbers are separated by commas or spaces and can be ranges, e.g; `1,3-5`
+/// The choice is asked again until every number is in range, an empty choice selects nothing
+/// Returns the sorted indices of the chosen items without duplicates
+/// Returns Err if stdin can't be read, with ErrorKind::UnexpectedEof if it ends before a choice
+pub fn multi_select<T: Display>(msg: &str, items: &[T]) -> io::Result<Vec<usize>> {
+    Input::stdio().multi_select(msg, items)
+}
+
+/// Prints a message and prompt to the console
+/// Reads lines until one parses as T, printing the error of the ones that don't
+/// The surrounding whitespace is removed before parsing
+/// Returns Err if stdin can't be read, with ErrorKind::UnexpectedEof if it ends before a valid value
+pub fn read_parse<T>(msg: &str, prompt: &str) -> io::Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Input::stdio().read_parse(msg, prompt)
+}
+
+/// Same as read_parse but gives up after attempts invalid lines
+/// Returns None when out of attempts or at the end of stdin
+/// Returns Err if stdin can't be read
+pub fn read_parse_limited<T>(msg: &str, prompt: &str, attempts: usize) -> io::Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Input::stdio().read_parse_limited(msg, prompt, attempts)
+}
+
+/// Prints a message and prompt to the console
+/// Reads lines until the validator accepts one, printing the message of the rejections
+/// The validators of the args module work as well, e.g; args::exists_file()
+/// Returns the accepted line without the surrounding whitespace
+/// Returns Err if stdin can't be read, with ErrorKind::UnexpectedEof if it ends before a valid line
+pub fn read_validated<F>(msg: &str, prompt: &str, validator: F) -> io::Result<String>
+where
+    F: Fn(&str) -> Result<(), String>,
+{
+    Input::stdio().read_validated(msg, prompt, validator)
+}
+
+/// Prints a message, the default and the prompt to the console, e.g; `Port [8080]: `
+/// Returns one line entered from stdin without the newline character, or the default if the line is empty
+/// The default is returned at the end of stdin as well
+/// Returns Err if stdin can't be read
+pub fn read_line_default(msg: &str, prompt: &str, default: &str) -> io::Result<String> {
+    Input::stdio().read_line_default(msg, prompt, default)
 }
 
 /// Prints a message and prompt to the console
 /// Returns all characters from stdin
 /// Panics if stdin can't be read
+#[deprecated(note = "panics if stdin can't be read, use input::try_read_all")]
 pub fn read_all(msg: &str, prompt: &str) -> String {
-    self::prompt(msg, prompt);
-    let mut string = String::new();
-    io::stdin()
-        .read_to_string(&mut string)
-        .expect("Failed to read from stdin");
-    string
+    try_read_all(msg, prompt).expect(READ_FAILED)
+}
+
+/// Same as read_all but returns Err instead of panicking if stdin can't be read
+/// Err with ErrorKind::InvalidData is returned if stdin isn't valid utf8
+pub fn try_read_all(msg: &str, prompt: &str) -> io::Result<String> {
+    Input::stdio().read_all(msg, prompt)
 }
 
 /// Prints a message and prompt to the console
@@ -34,19 +164,407 @@ pub fn read_all(msg: &str, prompt: &str) -> String {
 /// Returns Err if read buffer couldn't be converted to valid utf8
 /// Note: num_bytes may not correspond with resulting string length due to multibyte characters
 /// Panics if stdin can't be read
-pub fn read_num(num_bytes: usize, msg: &str, prompt: &str) -> Result<String, std::str::Utf8Error> {
-    self::prompt(msg, prompt);
-    let mut buf = Vec::with_capacity(num_bytes);
-    io::stdin()
-        .read_exact(&mut buf)
-        .expect("Failed to read from stdin");
-    match std::str::from_utf8(&buf) {
-        Ok(v) => Ok(v.to_string()),
-        Err(e) => Err(e),
+#[deprecated(note = "panics if stdin can't be read, use input::try_read_num")]
+pub fn read_num(num_bytes: usize, msg: &str, prompt: &str) -> Result<String, Utf8Error> {
+    match try_read_num(num_bytes, msg, prompt) {
+        Ok(string) => Ok(string),
+        Err(e) => match e.get_ref().and_then(|e| e.downcast_ref::<Utf8Error>()) {
+            Some(e) => Err(*e),
+            None => panic!("{}", READ_FAILED),
+        },
     }
 }
 
-fn prompt(msg: &str, prompt: &str) {
-    print!("{}{}", msg, prompt);
-    std::io::stdout().flush().expect("Failed to flush stdout");
+/// Same as read_num but returns Err instead of panicking if stdin can't be read
+/// Err with ErrorKind::UnexpectedEof is returned if stdin ends before num_bytes
+/// and Err with ErrorKind::InvalidData wrapping the Utf8Error if the bytes aren't valid utf8
+pub fn try_read_num(num_bytes: usize, msg: &str, prompt: &str) -> io::Result<String> {
+    Input::stdio().read_num(num_bytes, msg, prompt)
+}
+
+/// Opens a file for reading, following the convention that a path of `-` refers to stdin<br>
+/// Stdin is locked for as long as the returned reader lives<br>
+/// Returns Err if the file couldn't be opened
+pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn Read>> {
+    let path = path.as_ref();
+    if path == Path::new("-") {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+
+    Ok(Box::new(io::BufReader::new(File::open(path)?)))
+}
+
+/// Prompts on a writer and reads the answers from a reader, the functions of this module use stdout and stdin
+/// Lets the prompts be driven from any stream, e.g; byte buffers in tests or a socket
+/// The methods are the same as the functions of the module, with read_line, read_all and read_num behaving like try_read_line, try_read_all and try_read_num
+/// Err is returned when reading or writing fails, and with ErrorKind::UnexpectedEof when the reader ends before an answer that is needed
+/// ## Example
+/// ```
+/// use libcli::input::Input;
+/// let mut input = Input::new("8080\ny\n".as_bytes(), Vec::new());
+///
+/// assert_eq!(input.read_parse::<u16>("Port", ": ").unwrap(), 8080);
+/// assert!(input.confirm("Start the server?", None).unwrap());
+///
+/// let (_, output) = input.into_inner();
+/// assert_eq!(String::from_utf8(output).unwrap(), "Port: Start the server? [y/n]: ");
+/// ```
+pub struct Input<R, W> {
+    reader: R,
+    writer: W,
+    // The terminal on stdin is controlled for passwords and line editing
+    #[cfg(feature = "terminal")]
+    stdio: bool,
+    interactive: bool,
+    non_interactive: NonInteractive,
+    #[cfg(feature = "line_edit")]
+    line_editing: bool,
+}
+
+impl Input<StdinLock<'static>, Stdout> {
+    /// Creates an Input reading from stdin and prompting on stdout
+    /// Stdin is locked for as long as the Input lives
+    /// The Input is interactive if stdin is a terminal, see input::is_interactive
+    pub fn stdio() -> Self {
+        Self {
+            reader: io::stdin().lock(),
+            writer: io::stdout(),
+            #[cfg(feature = "terminal")]
+            stdio: true,
+            interactive: is_interactive(),
+            non_interactive: NonInteractive::Prompt,
+            #[cfg(feature = "line_edit")]
+            line_editing: true,
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Input<R, W> {
+    /// Creates an Input reading the answers from reader and writing the prompts to writer
+    /// The Input is considered interactive, see Input::interactive
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            #[cfg(feature = "terminal")]
+            stdio: false,
+            interactive: true,
+            non_interactive: NonInteractive::Prompt,
+            #[cfg(feature = "line_edit")]
+            line_editing: false,
+        }
+    }
+
+    /// Sets whether a user answers the prompts, overriding the detection of Input::stdio
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Sets what is done when the Input isn't interactive, prompting as usual by default
+    /// ## Example
+    /// ```no_run
+    /// use libcli::input::{Input, NonInteractive};
+    /// // `echo y | myapp` answers without printing the question
+    /// let mut input = Input::stdio().non_interactive(NonInteractive::Silent);
+    /// let overwrite = input.confirm("Overwrite the file?", Some(false)).unwrap();
+    /// ```
+    pub fn non_interactive(mut self, non_interactive: NonInteractive) -> Self {
+        self.non_interactive = non_interactive;
+        self
+    }
+
+    /// Sets whether the lines read by the prompts can be edited as they are typed, enabled for Input::stdio
+    /// The arrow keys, Home and End move the cursor, Ctrl+Left and Ctrl+Right or Alt+B and Alt+F by words
+    /// Backspace and Delete remove a character, Ctrl+W and Alt+D a word and Ctrl+U and Ctrl+K the rest of the line
+    /// Editing is only done when the Input is interactive, on stdin it needs a unix terminal and falls back to reading whole lines elsewhere
+    /// Ctrl+C in a prompt on stdin sends SIGINT to the process as usual, the prompt returns Err with ErrorKind::Interrupted if the process continues
+    #[cfg(feature = "line_edit")]
+    pub fn line_editing(mut self, line_editing: bool) -> Self {
+        self.line_editing = line_editing;
+        self
+    }
+
+    /// Returns the reader and writer
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+
+    // Returns true if a user answers the prompts, see Input::interactive
+    pub(crate) fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Same as input::read_line, an empty string is returned at the end of the reader
+    pub fn read_line(&mut self, msg: &str, prompt: &str) -> io::Result<String> {
+        self.prompt(msg, prompt)?;
+        #[cfg(feature = "line_edit")]
+        if self.line_editing && self.interactive {
+            // The keys only reach the editor as they are pressed in raw mode
+            let raw = match self.stdio {
+                true => terminal::RawMode::new(),
+                false => None,
+            };
+            if raw.is_some() || !self.stdio {
+                let prompt = format!("{}{}", msg, prompt);
+                let result = editor::read_line(&mut self.reader, &mut self.writer, &prompt);
+                drop(raw);
+                if self.stdio {
+                    terminal::forward_interrupt(&result);
+                }
+                return result;
+            }
+        }
+
+        let mut string = String::new();
+        self.reader.read_line(&mut string)?;
+        Ok(string)
+    }
+
+    /// Same as input::read_password, the echo is only disabled for an Input created with Input::stdio
+    pub fn read_password(&mut self, msg: &str, prompt: &str) -> io::Result<String> {
+        self.prompt(msg, prompt)?;
+        #[cfg(feature = "terminal")]
+        if self.stdio {
+            if let Some(result) = terminal::read_hidden(&mut self.reader) {
+                // The newline entered wasn't echoed
+                self.print(format_args!("\n"))?;
+                terminal::forward_interrupt(&result);
+                return result;
+            }
+        }
+
+        let mut string = String::new();
+        self.reader.read_line(&mut string)?;
+
+        let len = string.trim_end_matches(['\n', '\r']).len();
+        string.truncate(len);
+        Ok(string)
+    }
+
+    /// Same as input::confirm
+    pub fn confirm(&mut self, msg: &str, default: Option<bool>) -> io::Result<bool> {
+        let choices = match default {
+            Some(true) => "[Y/n]",
+            Some(false) => "[y/N]",
+            None => "[y/n]",
+        };
+        let prompt = format!(" {}: ", choices);
+        loop {
+            let line = self.read_line(msg, &prompt)?;
+            if line.is_empty() {
+                self.print(format_args!("\n"))?;
+                return Ok(default.unwrap_or(false));
+            }
+
+            match (line.trim().to_lowercase().as_str(), default) {
+                ("y" | "yes", _) => return Ok(true),
+                ("n" | "no", _) => return Ok(false),
+                ("", Some(default)) => return Ok(default),
+                _ => self.print(format_args!("Please answer yes or no\n"))?,
+            }
+        }
+    }
+
+    /// Same as input::select
+    /// Panics if items is empty
+    pub fn select<T: Display>(&mut self, msg: &str, items: &[T]) -> io::Result<usize> {
+        assert!(!items.is_empty(), "No items to select from");
+        self.print_items(msg, items)?;
+
+        let prompt = format!("Choice [1-{}]: ", items.len());
+        loop {
+            let line = self.read_answer("", &prompt)?;
+            match line.trim().parse::<usize>() {
+                Ok(choice) if (1..=items.len()).contains(&choice) => return Ok(choice - 1),
+                _ => self.print(format_args!(
+                    "Please enter a number from 1 to {}\n",
+                    items.len()
+                ))?,
+            }
+        }
+    }
+
+    /// Same as input::select_item
+    pub fn select_item<'a, T: Display>(&mut self, msg: &str, items: &'a [T]) -> io::Result<&'a T> {
+        Ok(&items[self.select(msg, items)?])
+    }
+
+    /// Same as input::multi_select
+    pub fn multi_select<T: Display>(&mut self, msg: &str, items: &[T]) -> io::Result<Vec<usize>> {
+        self.print_items(msg, items)?;
+
+        let prompt = format!("Choices [1-{}]: ", items.len());
+        loop {
+            let line = self.read_answer("", &prompt)?;
+            match parse_selection(&line, items.len()) {
+                Ok(selection) => return Ok(selection),
+                Err(err) => self.print(format_args!("{}\n", err))?,
+            }
+        }
+    }
+
+    /// Same as input::read_parse
+    pub fn read_parse<T>(&mut self, msg: &str, prompt: &str) -> io::Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.read_parse_limited(msg, prompt, usize::MAX)?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))
+    }
+
+    /// Same as input::read_parse_limited
+    pub fn read_parse_limited<T>(
+        &mut self,
+        msg: &str,
+        prompt: &str,
+        attempts: usize,
+    ) -> io::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        for _ in 0..attempts {
+            let line = self.read_line(msg, prompt)?;
+            if line.is_empty() {
+                self.print(format_args!("\n"))?;
+                return Ok(None);
+            }
+
+            match line.trim().parse() {
+                Ok(value) => return Ok(Some(value)),
+                Err(err) => {
+                    self.print(format_args!("Invalid value '{}': {}\n", line.trim(), err))?
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Same as input::read_validated
+    pub fn read_validated<F>(&mut self, msg: &str, prompt: &str, validator: F) -> io::Result<String>
+    where
+        F: Fn(&str) -> Result<(), String>,
+    {
+        loop {
+            let line = self.read_answer(msg, prompt)?;
+            match validator(line.trim()) {
+                Ok(()) => return Ok(line.trim().to_string()),
+                Err(err) => self.print(format_args!("{}\n", err))?,
+            }
+        }
+    }
+
+    /// Same as input::read_line_default
+    pub fn read_line_default(
+        &mut self,
+        msg: &str,
+        prompt: &str,
+        default: &str,
+    ) -> io::Result<String> {
+        let line = self.read_line(&format!("{} [{}]", msg, default), prompt)?;
+        Ok(match line.trim_end_matches(['\n', '\r']) {
+            "" => default.to_string(),
+            line => line.to_string(),
+        })
+    }
+
+    /// Same as input::read_all
+    pub fn read_all(&mut self, msg: &str, prompt: &str) -> io::Result<String> {
+        self.prompt(msg, prompt)?;
+        let mut string = String::new();
+        self.reader.read_to_string(&mut string)?;
+        Ok(string)
+    }
+
+    /// Same as input::read_num, Err with ErrorKind::InvalidData wraps the Utf8Error if the bytes aren't valid utf8
+    pub fn read_num(&mut self, num_bytes: usize, msg: &str, prompt: &str) -> io::Result<String> {
+        self.prompt(msg, prompt)?;
+        let mut buf = vec![0; num_bytes];
+        self.reader.read_exact(&mut buf)?;
+        match std::str::from_utf8(&buf) {
+            Ok(v) => Ok(v.to_string()),
+            Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+
+    // Reads a line which is needed, failing at the end of the reader
+    fn read_answer(&mut self, msg: &str, prompt: &str) -> io::Result<String> {
+        match self.read_line(msg, prompt)? {
+            line if line.is_empty() => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            line => Ok(line),
+        }
+    }
+
+    // Prints the message and the items numbered from 1
+    fn print_items<T: Display>(&mut self, msg: &str, items: &[T]) -> io::Result<()> {
+        self.print(format_args!("{}\n", msg))?;
+        for (i, item) in items.iter().enumerate() {
+            self.print(format_args!("{:>4}) {}\n", i + 1, item))?;
+        }
+        Ok(())
+    }
+
+    fn prompt(&mut self, msg: &str, prompt: &str) -> io::Result<()> {
+        self.print(format_args!("{}{}", msg, prompt))?;
+        self.writer.flush()
+    }
+
+    // Writes to the writer, unless the Input isn't interactive and mustn't prompt
+    pub(crate) fn print(&mut self, args: fmt::Arguments<'_>) -> io::Result<()> {
+        match (self.interactive, self.non_interactive) {
+            (false, NonInteractive::Silent) => Ok(()),
+            (false, NonInteractive::Fail) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Input is not interactive",
+            )),
+            _ => self.writer.write_fmt(args),
+        }
+    }
+}
+
+impl<R: BufRead + 'static, W: Write + 'static> Input<R, W> {
+    // Boxes the reader and writer, keeping the settings
+    pub(crate) fn boxed(self) -> BoxedInput {
+        Input {
+            reader: Box::new(self.reader),
+            writer: Box::new(self.writer),
+            #[cfg(feature = "terminal")]
+            stdio: self.stdio,
+            interactive: self.interactive,
+            non_interactive: self.non_interactive,
+            #[cfg(feature = "line_edit")]
+            line_editing: self.line_editing,
+        }
+    }
+}
+
+// Parses numbers and ranges of numbers from 1 to len into sorted indices, e.g; `1,3-5`
+fn parse_selection(line: &str, len: usize) -> Result<Vec<usize>, String> {
+    let number = |word: &str| match word.trim().parse::<usize>() {
+        Ok(n) if (1..=len).contains(&n) => Ok(n - 1),
+        _ => Err(format!("Please enter numbers from 1 to {}", len)),
+    };
+
+    let mut selection = Vec::new();
+    for word in line
+        .split(|ch: char| ch == ',' || ch.is_whitespace())
+        .filter(|word| !word.is_empty())
+    {
+        match word.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (number(start)?, number(end)?);
+                if start > end {
+                    return Err(format!("The range '{}' is reversed", word));
+                }
+                selection.extend(start..=end);
+            }
+            None => selection.push(number(word)?),
+        }
+    }
+
+    selection.sort_unstable();
+    selection.dedup();
+    Ok(selection)
 }
@@ -0,0 +1,238 @@
+//! Edits the line of a prompt as the keys are pressed, see Input::line_editing
+
+use super::terminal::read_byte;
+use std::io::{self, Read, Write};
+
+// The keys understood by the editor, decoded from the bytes a terminal sends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+    WordLeft,
+    WordRight,
+    DeleteWordLeft,
+    DeleteWordRight,
+    KillStart,
+    KillEnd,
+    EndOfFile,
+    Interrupt,
+    Other,
+}
+
+// The characters of the line being edited and the position of the cursor among them
+#[derive(Default)]
+struct Line {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl Line {
+    fn apply(&mut self, key: Key) {
+        let len = self.chars.len();
+        match key {
+            Key::Char(ch) => {
+                self.chars.insert(self.cursor, ch);
+                self.cursor += 1;
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+            }
+            Key::Delete | Key::EndOfFile if self.cursor < len => {
+                self.chars.remove(self.cursor);
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(len),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = len,
+            Key::WordLeft => self.cursor = self.word_left(),
+            Key::WordRight => self.cursor = self.word_right(),
+            Key::DeleteWordLeft => {
+                let start = self.word_left();
+                self.chars.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            Key::DeleteWordRight => {
+                let end = self.word_right();
+                self.chars.drain(self.cursor..end);
+            }
+            Key::KillStart => {
+                self.chars.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Key::KillEnd => self.chars.truncate(self.cursor),
+            _ => {}
+        }
+    }
+
+    // The start of the word before the cursor, skipping the separators in between
+    fn word_left(&self) -> usize {
+        let mut i = self.cursor;
+        while i > 0 && !is_word(self.chars[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && is_word(self.chars[i - 1]) {
+            i -= 1;
+        }
+        i
+    }
+
+    // The end of the word after the cursor, skipping the separators in between
+    fn word_right(&self) -> usize {
+        let mut i = self.cursor;
+        while i < self.chars.len() && !is_word(self.chars[i]) {
+            i += 1;
+        }
+        while i < self.chars.len() && is_word(self.chars[i]) {
+            i += 1;
+        }
+        i
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    // Redraws the prompt and the line, then moves the cursor back into place
+    fn draw<W: Write>(&self, writer: &mut W, prompt: &str) -> io::Result<()> {
+        write!(writer, "\r{}{}\x1b[K", prompt, self.text())?;
+        let back = self.chars.len() - self.cursor;
+        if back > 0 {
+            write!(writer, "\x1b[{}D", back)?;
+        }
+        writer.flush()
+    }
+}
+
+fn is_word(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+// Reads a line from reader, editing it with the keys as they are read and redrawing it on writer after the prompt
+// The prompt is expected to be written already, only its last line is redrawn
+// Returns the line with a newline like BufRead::read_line, an empty string at the end of the reader or on Ctrl+D
+// and Err with ErrorKind::Interrupted on Ctrl+C
+pub(super) fn read_line<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    prompt: &str,
+) -> io::Result<String> {
+    let prompt = prompt.rsplit('\n').next().unwrap_or_default();
+    let mut line = Line::default();
+    loop {
+        let key = match read_key(reader)? {
+            Some(key) => key,
+            None => return Ok(line.text()),
+        };
+
+        match key {
+            Key::Enter => {
+                writeln!(writer)?;
+                return Ok(line.text() + "\n");
+            }
+            Key::EndOfFile if line.chars.is_empty() => return Ok(String::new()),
+            Key::Interrupt => {
+                writeln!(writer, "^C")?;
+                return Err(io::Error::from(io::ErrorKind::Interrupted));
+            }
+            key => line.apply(key),
+        }
+        line.draw(writer, prompt)?;
+    }
+}
+
+// Reads the next key, returning None at the end of the reader
+fn read_key<R: Read>(reader: &mut R) -> io::Result<Option<Key>> {
+    let byte = match read_byte(reader)? {
+        Some(byte) => byte,
+        None => return Ok(None),
+    };
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x02 => Key::Left,
+        0x03 => Key::Interrupt,
+        0x04 => Key::EndOfFile,
+        0x05 => Key::End,
+        0x06 => Key::Right,
+        0x0b => Key::KillEnd,
+        0x15 => Key::KillStart,
+        0x17 => Key::DeleteWordLeft,
+        0x1b => read_escape(reader)?,
+        byte if byte < 0x20 => Key::Other,
+        byte => read_char(reader, byte)?,
+    };
+    Ok(Some(key))
+}
+
+// Decodes the rest of an escape sequence, e.g; `ESC [ 1 ; 5 C` for Ctrl+Right
+// Alt and a key sends the key after an escape
+fn read_escape<R: Read>(reader: &mut R) -> io::Result<Key> {
+    let key = match read_byte(reader)? {
+        Some(b'[') => {
+            let mut params = Vec::new();
+            let last = loop {
+                match read_byte(reader)? {
+                    Some(byte @ 0x40..=0x7e) => break byte,
+                    Some(byte) => params.push(byte),
+                    None => return Ok(Key::Other),
+                }
+            };
+
+            match (params.as_slice(), last) {
+                (b"", b'C') => Key::Right,
+                (b"", b'D') => Key::Left,
+                (b"", b'H') | (b"1", b'~') | (b"7", b'~') => Key::Home,
+                (b"", b'F') | (b"4", b'~') | (b"8", b'~') => Key::End,
+                (b"3", b'~') => Key::Delete,
+                (b"1;5", b'C') | (b"1;3", b'C') => Key::WordRight,
+                (b"1;5", b'D') | (b"1;3", b'D') => Key::WordLeft,
+                _ => Key::Other,
+            }
+        }
+        Some(b'O') => match read_byte(reader)? {
+            Some(b'C') => Key::Right,
+            Some(b'D') => Key::Left,
+            Some(b'H') => Key::Home,
+            Some(b'F') => Key::End,
+            _ => Key::Other,
+        },
+        Some(b'b') => Key::WordLeft,
+        Some(b'f') => Key::WordRight,
+        Some(b'd') => Key::DeleteWordRight,
+        Some(0x7f | 0x08) => Key::DeleteWordLeft,
+        _ => Key::Other,
+    };
+    Ok(key)
+}
+
+// Reads the continuation bytes of a utf8 character starting with byte
+fn read_char<R: Read>(reader: &mut R, byte: u8) -> io::Result<Key> {
+    let len = match byte {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+
+    let mut bytes = vec![byte];
+    for _ in 1..len {
+        match read_byte(reader)? {
+            Some(byte) => bytes.push(byte),
+            None => break,
+        }
+    }
+
+    Ok(std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|s| s.chars().next())
+        .map_or(Key::Other, Key::Char))
+}
@@ -0,0 +1,146 @@
+//! Controls the terminal on stdin, hiding passwords and passing the keys on as they are pressed
+
+use std::io::{self, BufRead, Read};
+
+// Reads a byte, returning None at the end of the reader
+#[cfg_attr(not(any(unix, feature = "line_edit")), allow(dead_code))]
+pub(super) fn read_byte<R: Read>(reader: &mut R) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    loop {
+        match reader.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Reads a line from stdin without echoing it, e.g; for a password
+// Returns None if the echo can't be disabled, e.g; when stdin isn't a terminal
+// Returns Err with ErrorKind::Interrupted on Ctrl+C, see forward_interrupt
+#[cfg(unix)]
+pub(super) fn read_hidden<R: BufRead>(reader: &mut R) -> Option<io::Result<String>> {
+    // Ctrl+C is read as a key in raw mode, so the terminal is restored before the process is interrupted
+    let raw = RawMode::new()?;
+    let mut line = Vec::new();
+    let result = loop {
+        match read_byte(reader) {
+            Ok(None) | Ok(Some(b'\r' | b'\n')) => break Ok(()),
+            Ok(Some(0x03)) => break Err(io::Error::from(io::ErrorKind::Interrupted)),
+            Ok(Some(0x04)) if line.is_empty() => break Ok(()),
+            // Removes the last character along with its continuation bytes
+            Ok(Some(0x7f | 0x08)) => while line.pop().is_some_and(|byte| byte & 0xc0 == 0x80) {},
+            Ok(Some(0x15)) => line.clear(),
+            Ok(Some(byte)) => line.push(byte),
+            Err(e) => break Err(e),
+        }
+    };
+    drop(raw);
+
+    Some(result.and_then(|()| {
+        String::from_utf8(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }))
+}
+
+// Reads a line from stdin with the echo of the console disabled
+#[cfg(windows)]
+pub(super) fn read_hidden<R: BufRead>(reader: &mut R) -> Option<io::Result<String>> {
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_ECHO_INPUT, STD_INPUT_HANDLE,
+    };
+
+    // SAFETY: the handle of stdin is only passed to the console functions, which fail for a non console handle
+    let (handle, mode) = unsafe {
+        let handle = GetStdHandle(STD_INPUT_HANDLE);
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0
+            || SetConsoleMode(handle, mode & !ENABLE_ECHO_INPUT) == 0
+        {
+            return None;
+        }
+        (handle, mode)
+    };
+
+    let mut string = String::new();
+    let result = reader.read_line(&mut string);
+    // SAFETY: the handle and mode were returned by GetStdHandle and GetConsoleMode
+    unsafe { SetConsoleMode(handle, mode) };
+
+    Some(result.map(|_| {
+        let len = string.trim_end_matches(['\n', '\r']).len();
+        string.truncate(len);
+        string
+    }))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(super) fn read_hidden<R: BufRead>(_: &mut R) -> Option<io::Result<String>> {
+    None
+}
+
+// Passes the keys of the terminal on stdin on as they are pressed, without echoing them, until dropped
+// Ctrl+C is read as a key rather than sending SIGINT
+#[cfg(unix)]
+pub(super) struct RawMode(libc::termios);
+
+#[cfg(unix)]
+impl RawMode {
+    // Returns None if stdin isn't a terminal
+    pub(super) fn new() -> Option<Self> {
+        // SAFETY: termios is a plain C struct filled in by tcgetattr before it is used
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::isatty(libc::STDIN_FILENO) == 0
+                || libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0
+            {
+                return None;
+            }
+
+            let mut raw = termios;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+            raw.c_iflag &= !(libc::ICRNL | libc::IXON);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            match libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) {
+                0 => Some(Self(termios)),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: the termios was returned by tcgetattr for the same descriptor
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0) };
+    }
+}
+
+// Raw mode is only supported on unix, the prompts fall back to reading whole lines elsewhere
+#[cfg(not(unix))]
+#[cfg_attr(not(feature = "line_edit"), allow(dead_code))]
+pub(super) struct RawMode;
+
+#[cfg(not(unix))]
+#[cfg_attr(not(feature = "line_edit"), allow(dead_code))]
+impl RawMode {
+    pub(super) fn new() -> Option<Self> {
+        None
+    }
+}
+
+// Sends SIGINT to the process if reading was interrupted by Ctrl+C, as it would have been outside of raw mode
+pub(super) fn forward_interrupt<T>(result: &io::Result<T>) {
+    if result
+        .as_ref()
+        .is_err_and(|e| e.kind() == io::ErrorKind::Interrupted)
+    {
+        // SAFETY: raise has no preconditions
+        #[cfg(unix)]
+        unsafe {
+            libc::raise(libc::SIGINT);
+        }
+    }
+}
@@ -1,2 +1,3 @@
 pub mod args;
 pub mod input;
+pub mod repl;
@@ -7,7 +7,7 @@ fn main() {
             "(unnamed)",
             "Input files",
             true,
-            args::OptionPolicy::AtLeast(1), // 1st value is program name
+            args::OptionPolicy::AtLeast(1),
         ),
         args::OptionSpec::new(
             'o',
@@ -39,19 +39,17 @@ fn main() {
 
     println!("{:?}", config.option("help"));
 
-    if let Some(_) = config.option("help") {
+    if config.option("help").is_some() {
         println!(
-            "Myprogram\n{}",
+            "{}\n{}",
+            config.program_name(),
             args::Config::generate_usage(&specs, true, true)
         );
         return;
     }
 
     // Check if verbose was specified, either as --verbose or -v
-    let verbose: bool = match config.option("verbose") {
-        Some(_) => true,
-        None => false,
-    };
+    let verbose: bool = config.option("verbose").is_some();
 
     // Should always return Some since option was required, new_env should have failed if not included
     let files = match config.option("(unnamed)") {
@@ -0,0 +1,104 @@
+//! An interactive shell running the subcommands of an App
+use crate::args::{self, App};
+use crate::input;
+
+/// Reads lines from stdin in a loop and runs each like the arguments of a command line, e.g; `build --release`<br>
+/// The lines are split like a shell would, see args::split_args, and run with App::run so the subcommands dispatch to their handlers<br>
+/// Errors are printed to stderr and the loop continues, it ends at the end of stdin or on an exit command
+/// ## Example
+/// ```no_run
+/// use libcli::args::{App, Command};
+/// use libcli::repl::Repl;
+/// let app = App::new("myapp").subcommand(Command::new("hello", "Greets", Vec::new()), |_| {
+///     println!("Hello");
+///     Ok::<(), String>(())
+/// });
+///
+/// Repl::new(&app).prompt("myapp> ").run();
+/// ```
+pub struct Repl<'a> {
+    app: &'a App,
+    prompt: String,
+    exit_commands: Vec<String>,
+}
+
+impl<'a> Repl<'a> {
+    /// Creates a shell for the subcommands of app, left with `exit` or `quit`
+    pub fn new(app: &'a App) -> Self {
+        Self {
+            app,
+            prompt: format!("{}> ", app.name()),
+            exit_commands: vec![String::from("exit"), String::from("quit")],
+        }
+    }
+
+    /// Sets the prompt printed before every line, `<name>> ` by default
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.prompt = prompt.to_string();
+        self
+    }
+
+    /// Sets the commands which end the loop, replacing `exit` and `quit`
+    pub fn exit_commands(mut self, commands: &[&str]) -> Self {
+        self.exit_commands = commands.iter().map(|command| command.to_string()).collect();
+        self
+    }
+
+    /// Runs lines until the end of stdin or an exit command<br>
+    /// Returns the exit status of the last line that was run, 0 if none was, or 1 if stdin can't be read
+    pub fn run(&self) -> i32 {
+        let mut status = 0;
+        loop {
+            let line = match input::try_read_line("", &self.prompt) {
+                Ok(line) => line,
+                Err(err) => {
+                    eprintln!("Failed to read from stdin: {}", err);
+                    return 1;
+                }
+            };
+            // Nothing, not even a newline, is read at the end of stdin
+            if line.is_empty() {
+                println!();
+                return status;
+            }
+
+            match self.run_line(&line) {
+                Some(line_status) => status = line_status,
+                None => return status,
+            }
+        }
+    }
+
+    /// Runs a single line, returning its exit status, see App::run<br>
+    /// Returns None for an exit command, blank lines are skipped with a status of 0
+    /// ## Example
+    /// ```
+    /// # use libcli::args::{App, Command};
+    /// # use libcli::repl::Repl;
+    /// let app = App::new("myapp").subcommand(Command::new("fail", "Fails", Vec::new()), |_| Err("failed"));
+    /// let repl = Repl::new(&app);
+    ///
+    /// assert_eq!(repl.run_line("fail"), Some(1));
+    /// assert_eq!(repl.run_line("exit"), None);
+    /// ```
+    pub fn run_line(&self, line: &str) -> Option<i32> {
+        let words = match args::split_args(line) {
+            Ok(words) => words,
+            Err(err) => {
+                eprintln!("{}", err);
+                return Some(2);
+            }
+        };
+
+        match words.first() {
+            None => Some(0),
+            Some(first) if self.exit_commands.contains(first) && words.len() == 1 => None,
+            Some(_) => {
+                let args: Vec<&str> = std::iter::once(self.app.name())
+                    .chain(words.iter().map(String::as_str))
+                    .collect();
+                Some(self.app.run(&args))
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
     use libcli::args;
+    use libcli::input::Input;
 
     #[test]
     fn parse_0() {
@@ -57,7 +58,7 @@ mod tests {
             args::Config::generate_usage(&specs, false, true)
         );
 
-        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!(err));
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
 
         assert_eq!(
             *config
@@ -88,7 +89,7 @@ mod tests {
 
         let args = ["./test"];
 
-        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!(err));
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
     }
 
     #[test]
@@ -103,7 +104,7 @@ mod tests {
         )];
 
         let args = ["./test", "file1", "file2"];
-        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!(err));
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
     }
 
     #[test]
@@ -127,7 +128,7 @@ mod tests {
         ];
 
         let args = ["./test", "-o", "file1", "-o", "file2"];
-        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!(err));
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
     }
 
     #[test]
@@ -157,6 +158,4043 @@ mod tests {
         ];
 
         let args = ["./test", "foo", "-vo", "file1", "-v"];
-        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!(err));
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    #[test]
+    fn parse_subcommand() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let commands = [args::Command::new(
+            "build",
+            "Builds the project",
+            vec![
+                args::OptionSpec::new(
+                    '\0',
+                    "(unnamed)",
+                    "Targets",
+                    true,
+                    args::OptionPolicy::AtLeast(1),
+                ),
+                args::OptionSpec::new(
+                    'r',
+                    "release",
+                    "Build with optimizations",
+                    false,
+                    args::OptionPolicy::Exact(0),
+                ),
+            ],
+        )];
+
+        let args = ["./test", "-v", "build", "lib", "-r"];
+        let config = args::Config::new_with_commands(&args[..], &specs, &commands)
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert!(config.option("verbose").is_some());
+        let (name, sub) = config.subcommand().expect("Didn't parse subcommand");
+        assert_eq!(name, "build");
+        assert_eq!(*sub.option("(unnamed)").unwrap(), ["lib".to_string()]);
+        assert!(sub.option("release").is_some());
+    }
+
+    #[test]
+    fn parse_nested_subcommand() {
+        let unnamed = || {
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            )
+        };
+
+        let commands = [
+            args::Command::new("remote", "Manage remotes", vec![unnamed()])
+                .subcommand(args::Command::new("add", "Adds a remote", vec![unnamed()])),
+        ];
+
+        let args = ["./test", "remote", "add", "origin"];
+        let config = args::Config::new_with_commands(&args[..], &[unnamed()], &commands)
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(config.command_path(), ["remote", "add"]);
+        let (_, remote) = config.subcommand().unwrap();
+        let (_, add) = remote.subcommand().unwrap();
+        assert_eq!(*add.option("(unnamed)").unwrap(), ["origin".to_string()]);
+    }
+
+    #[test]
+    fn parse_attached_long_value() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'd',
+                "define",
+                "Defines",
+                false,
+                args::OptionPolicy::AtLeast(1),
+            ),
+        ];
+
+        let args = ["./test", "--output=out=1.txt", "--define=a", "b"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option("output").unwrap(), ["out=1.txt".to_string()]);
+        assert_eq!(
+            *config.option("define").unwrap(),
+            ["a".to_string(), "b".to_string()]
+        );
+
+        let args = ["./test", "--output=a", "b"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_attached_short_value() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new(
+                'n',
+                "number",
+                "The number of iterations to perform",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+
+        let args = ["./test", "-vn3", "-ooutput.txt"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        assert!(config.option("verbose").is_some());
+        assert_eq!(*config.option("number").unwrap(), ["3".to_string()]);
+        assert_eq!(
+            *config.option("output").unwrap(),
+            ["output.txt".to_string()]
+        );
+
+        // Switches can't take attached values
+        let args = ["./test", "-vx", "-o", "out"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_typed() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'n',
+                "number",
+                "The number of iterations to perform",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+
+        let args = ["./test", "1", "2", "-n", "x3"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(config.get_many::<u8>("(unnamed)"), Ok(vec![1, 2]));
+        assert_eq!(config.get::<f32>("missing"), Ok(None));
+
+        let err = config.get::<u32>("number").unwrap_err().to_string();
+        assert!(err.contains("'number'") && err.contains("'x3'"));
+    }
+
+    #[test]
+    fn parse_validator() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'p',
+                "port",
+                "Port to listen on",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .with_validator(|v| match v.parse::<u16>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err("expected a port number".to_string()),
+            }),
+        ];
+
+        let args = ["./test", "--port", "8080"];
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        let args = ["./test", "--port", "99999"];
+        let err = args::Config::new(&args[..], &specs)
+            .err()
+            .expect("Validator didn't reject value")
+            .to_string();
+        assert!(err.contains("'port'") && err.contains("expected a port number"));
+    }
+
+    #[test]
+    fn parse_choices() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'f',
+                "format",
+                "Output format",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .choices(&["json", "yaml", "text"]),
+        ];
+
+        let args = ["./test", "-f", "yaml"];
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        let args = ["./test", "-f", "xml"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+
+        assert!(args::Config::generate_usage(&specs, true, true)
+            .contains("[possible values: json, yaml, text]"));
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "When to use colors",
+                true,
+                args::OptionPolicy::Exact(1),
+            )
+            .default_values(&["auto"]),
+        ];
+
+        let args = ["./test"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("color").unwrap(), ["auto".to_string()]);
+
+        let args = ["./test", "-c", "never"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("color").unwrap(), ["never".to_string()]);
+
+        assert!(args::Config::generate_usage(&specs, true, true).contains("[default: auto]"));
+    }
+
+    #[test]
+    fn parse_env() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            )
+            .env("LIBCLI_TEST_PARSE_ENV_OUTPUT")
+            .default_values(&["default.txt"]),
+        ];
+
+        std::env::set_var("LIBCLI_TEST_PARSE_ENV_OUTPUT", "env.txt");
+
+        let args = ["./test"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("output").unwrap(), ["env.txt".to_string()]);
+
+        let args = ["./test", "-o", "cli.txt"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("output").unwrap(), ["cli.txt".to_string()]);
+
+        std::env::remove_var("LIBCLI_TEST_PARSE_ENV_OUTPUT");
+
+        let args = ["./test"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(
+            *config.option("output").unwrap(),
+            ["default.txt".to_string()]
+        );
+
+        assert!(args::Config::generate_usage(&specs, true, true)
+            .contains("[env: LIBCLI_TEST_PARSE_ENV_OUTPUT]"));
+    }
+
+    #[test]
+    fn parse_env_prefix() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output-file",
+                "Specifies the output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'd',
+                "dry-run",
+                "Don't write anything",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        std::env::set_var("LIBCLI_TEST_PREFIX_OUTPUT_FILE", "env.txt");
+        std::env::set_var("LIBCLI_TEST_PREFIX_DRY_RUN", "1");
+
+        let args = ["./test"];
+        let config = args::Config::new_with_prefix(&args[..], "LIBCLI_TEST_PREFIX", &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(
+            *config.option("output-file").unwrap(),
+            ["env.txt".to_string()]
+        );
+        assert!(config.option("dry-run").is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn parse_file() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new(
+                'n',
+                "number",
+                "The number of iterations to perform",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+
+        let path = std::env::temp_dir().join("libcli_test_parse_file.toml");
+        std::fs::write(&path, "output = \"file.txt\"\nverbose = true\nnumber = 3\n").unwrap();
+
+        let args = ["./test", "-o", "cli.txt"];
+        let config = args::Config::new_with_file(&args[..], &path, &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option("output").unwrap(), ["cli.txt".to_string()]);
+        assert_eq!(*config.option("number").unwrap(), ["3".to_string()]);
+        assert!(config.option("verbose").is_some());
+        assert_eq!(config.source("output"), Some(&args::Source::Cli));
+        assert_eq!(
+            config.source("number"),
+            Some(&args::Source::File(path.clone()))
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parse_builder() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .default_values(&["default.txt"]),
+            args::OptionSpec::new(
+                'n',
+                "number",
+                "The number of iterations to perform",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .default_values(&["1"]),
+        ];
+
+        std::env::set_var("LIBCLI_TEST_BUILDER_NUMBER", "2");
+
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "input"])
+            .env_prefix("LIBCLI_TEST_BUILDER")
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option("(unnamed)").unwrap(), ["input".to_string()]);
+        assert_eq!(
+            *config.option("output").unwrap(),
+            ["default.txt".to_string()]
+        );
+        assert_eq!(*config.option("number").unwrap(), ["2".to_string()]);
+    }
+
+    #[test]
+    fn parse_range() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::Range(1, 3),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let args = ["./test", "a", "b", "c", "-v"];
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        let args = ["./test", "-v"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+
+        let args = ["./test", "a", "b", "c", "d"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+
+        assert!(args::Config::generate_usage(&specs, true, true).contains("[values: 1 to 3]"));
+    }
+
+    #[test]
+    fn parse_duplicate_policy() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .duplicate_policy(args::DuplicatePolicy::LastWins),
+            args::OptionSpec::new(
+                'i',
+                "include",
+                "Include directories",
+                false,
+                args::OptionPolicy::AtLeast(1),
+            )
+            .duplicate_policy(args::DuplicatePolicy::Append),
+        ];
+
+        let args = [
+            "./test",
+            "-o",
+            "a",
+            "-i",
+            "x",
+            "y",
+            "-o",
+            "b",
+            "--include",
+            "z",
+        ];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option("output").unwrap(), ["b".to_string()]);
+        assert_eq!(
+            *config.option("include").unwrap(),
+            ["x".to_string(), "y".to_string(), "z".to_string()]
+        );
+    }
+
+    #[test]
+    fn parse_requires() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'z',
+                "compress",
+                "Compress the output",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .requires("output"),
+        ];
+
+        let args = ["./test", "-z", "-o", "out.gz"];
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        let args = ["./test", "-z"];
+        let err = args::Config::new(&args[..], &specs)
+            .err()
+            .expect("Missing dependency wasn't rejected");
+        assert_eq!(
+            err,
+            args::ParseError::MissingDependency {
+                name: "compress".to_string(),
+                requires: "output".to_string(),
+            }
+        );
+
+        assert!(args::Config::generate_usage(&specs, true, true).contains("[requires: --output]"));
+    }
+
+    #[test]
+    fn parse_conditionally_required() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'c',
+                "config",
+                "Config file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .required_unless("config"),
+            args::OptionSpec::new(
+                'r',
+                "remote",
+                "Use a remote",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new(
+                'u',
+                "url",
+                "Url of the remote",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .required_if("remote"),
+        ];
+
+        let args = ["./test", "-c", "app.toml"];
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        let args = ["./test", "-o", "out"];
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+
+        let args = ["./test"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+
+        let args = ["./test", "-o", "out", "-r"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+
+        let args = ["./test", "-o", "out", "-r", "-u", "host"];
+        args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    #[test]
+    fn parse_alias() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "When to use colors",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .visible_alias("colour")
+            .alias("colr"),
+        ];
+
+        let args = ["./test", "--colour", "never"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("color").unwrap(), ["never".to_string()]);
+
+        let usage = args::Config::generate_usage(&specs, true, true);
+        assert!(usage.contains("--color, --colour"));
+        assert!(!usage.contains("--colr"));
+    }
+
+    #[test]
+    fn parse_long_and_short_only() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::long(
+                "dry-run",
+                "Don't write anything",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::short(
+                'x',
+                "extract",
+                "Extract the archive",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let args = ["./test", "--dry-run", "-x"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.option("dry-run").is_some());
+        assert!(config.option("extract").is_some());
+
+        let args = ["./test", "--extract"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+
+        let usage = args::Config::generate_usage(&specs, true, true);
+        assert!(usage.contains("        --dry-run"));
+        assert!(usage.contains("    -x \n"));
+        assert!(!usage.contains('\0'));
+    }
+
+    #[test]
+    fn parse_positionals() {
+        let specs = [
+            args::OptionSpec::positional(
+                "SRC",
+                "Files to copy",
+                true,
+                args::OptionPolicy::AtLeast(1),
+            ),
+            args::OptionSpec::positional("DEST", "Destination", true, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let args = ["./test", "a", "b", "c", "-v"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(
+            *config.option("SRC").unwrap(),
+            ["a".to_string(), "b".to_string()]
+        );
+        assert_eq!(*config.option("DEST").unwrap(), ["c".to_string()]);
+
+        let args = ["./test", "a"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_trailing() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::trailing("COMMAND", "Command to run", 1),
+        ];
+
+        let args = ["./test", "-v", "--", "grep", "-r", "--", "."];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.option("verbose").is_some());
+        assert_eq!(
+            *config.option("COMMAND").unwrap(),
+            ["grep", "-r", "--", "."].map(String::from)
+        );
+
+        let args = ["./test", "-v"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_interleaved() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                true,
+                args::OptionPolicy::AtLeast(1),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "file1", "-o", "out.txt", "file2", "-v", "file3"])
+            .interleaved(true)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option("output").unwrap(), ["out.txt".to_string()]);
+        assert_eq!(
+            *config.option("(unnamed)").unwrap(),
+            ["file1", "file2", "file3"].map(String::from)
+        );
+
+        let args = ["./test", "file1", "-o", "out.txt", "file2"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_posix() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                true,
+                args::OptionPolicy::AtLeast(1),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "-o", "out.txt", "file", "--verbose", "-o"])
+            .posix(true)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option("output").unwrap(), ["out.txt".to_string()]);
+        assert!(config.option("verbose").is_none());
+        assert_eq!(
+            *config.option("(unnamed)").unwrap(),
+            ["file", "--verbose", "-o"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn parse_allow_unknown() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "--foo=bar", "-o", "out", "-vx", "-yz", "--baz"])
+            .allow_unknown(true)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option("output").unwrap(), ["out".to_string()]);
+        assert!(config.option("verbose").is_some());
+        assert_eq!(config.unknown(), ["--foo=bar", "-x", "-yz", "--baz"]);
+
+        let args = ["./test", "--foo"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_suggestion() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "When to use colors",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .alias("colour"),
+        ];
+
+        let args = ["./test", "--ouput", "out"];
+        let err = args::Config::new(&args[..], &specs)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("did you mean '--output'?"));
+
+        let args = ["./test", "--colouur"];
+        let err = args::Config::new(&args[..], &specs)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("did you mean '--colour'?"));
+
+        let args = ["./test", "--recursive"];
+        let err = args::Config::new(&args[..], &specs)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(!err.contains("did you mean"));
+    }
+
+    #[test]
+    fn parse_prefix_matching() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new(
+                'V',
+                "version",
+                "Show the version",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let parse = |args: &[&str]| {
+            args::ConfigBuilder::new(&specs)
+                .args(args)
+                .case_insensitive(true)
+                .prefix_matching(true)
+                .build()
+        };
+
+        let config = parse(&["./test", "--Verb"]).unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.option("verbose").is_some());
+
+        let err = parse(&["./test", "--ver"]).err().unwrap().to_string();
+        assert!(err.contains("'--verbose', '--version'"));
+
+        let args = ["./test", "--verb"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_response_file() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+
+        let dir = std::env::temp_dir();
+        let outer = dir.join("libcli_test_response_outer.txt");
+        let inner = dir.join("libcli_test_response_inner.txt");
+        std::fs::write(&outer, format!("file1\n@{}\n", inner.display())).unwrap();
+        std::fs::write(&inner, "file2 file3\n-o out.txt").unwrap();
+
+        let outer_arg = format!("@{}", outer.display());
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "file0", &outer_arg])
+            .response_files(true)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(
+            *config.option("(unnamed)").unwrap(),
+            ["file0", "file1", "file2", "file3"].map(String::from)
+        );
+        assert_eq!(*config.option("output").unwrap(), ["out.txt".to_string()]);
+
+        std::fs::remove_file(&outer).unwrap();
+        std::fs::remove_file(&inner).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn parse_os() {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+
+        let file = OsString::from_vec(b"file\xff.txt".to_vec());
+        let output = OsString::from_vec(b"out\xfe.txt\xc3".to_vec());
+        let config = args::Config::new_os(
+            [
+                OsString::from("./test"),
+                file.clone(),
+                OsString::from_vec([b"-o".to_vec(), output.clone().into_vec()].concat()),
+            ],
+            &specs,
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option_os("(unnamed)").unwrap(), [file]);
+        assert_eq!(*config.option_os("output").unwrap(), [output]);
+        assert_eq!(
+            *config.option("(unnamed)").unwrap(),
+            ["file\u{FFFD}.txt".to_string()]
+        );
+
+        // Valid characters in the range used to escape invalid bytes come back unchanged
+        let reserved = OsString::from("\u{10FF41}\u{10FFFF}");
+        let config = args::Config::new_os(
+            [
+                OsString::from("./test"),
+                reserved.clone(),
+                OsString::from_vec(
+                    [b"-o".to_vec(), b"\xff".to_vec(), "\u{10FF41}".into()].concat(),
+                ),
+            ],
+            &specs,
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option_os("(unnamed)").unwrap(), [reserved]);
+        assert_eq!(
+            *config.option_os("output").unwrap(),
+            [OsString::from_vec(
+                [b"\xff".to_vec(), "\u{10FF41}".into()].concat()
+            )]
+        );
+        assert_eq!(
+            *config.option("(unnamed)").unwrap(),
+            ["\u{10FF41}\u{10FFFF}".to_string()]
+        );
+        assert_eq!(
+            *config.option("output").unwrap(),
+            ["\u{FFFD}\u{10FF41}".to_string()]
+        );
+    }
+
+    #[test]
+    fn parse_negatable() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "Colorize the output",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .env("LIBCLI_TEST_NEGATABLE_COLOR")
+            .negatable(),
+        ];
+
+        std::env::set_var("LIBCLI_TEST_NEGATABLE_COLOR", "1");
+
+        let config = args::Config::new(&["./test", "--no-color", "file"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.option("color").is_none());
+        assert_eq!(*config.option("(unnamed)").unwrap(), ["file".to_string()]);
+
+        let config = args::Config::new(&["./test", "--color", "--no-color"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.option("color").is_none());
+
+        let config = args::Config::new(&["./test", "--no-color", "-c"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.option("color").is_some());
+
+        assert!(args::Config::generate_usage(&specs, true, true).contains("-c, --[no-]color"));
+
+        std::env::remove_var("LIBCLI_TEST_NEGATABLE_COLOR");
+    }
+
+    #[test]
+    fn usage_hidden() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                '\0',
+                "dump-tokens",
+                "Prints the lexed tokens",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .hidden(),
+        ];
+
+        let usage = args::Config::generate_usage(&specs, true, true);
+        assert!(!usage.contains("dump-tokens"));
+
+        let config = args::Config::new(&["./test", "--dump-tokens"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.option("dump-tokens").is_some());
+    }
+
+    #[test]
+    fn parse_deprecated() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                '\0',
+                "out",
+                "Specifies the output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .deprecated("use --output instead")
+            .default_values(&["out.txt"]),
+        ];
+
+        let config = args::Config::new(&["./test"], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.warnings().is_empty());
+
+        let config = args::Config::new(&["./test", "--out", "a.txt"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("out").unwrap(), ["a.txt".to_string()]);
+        assert_eq!(
+            config.warnings(),
+            [args::ParseWarning::Deprecated {
+                name: "out".to_string(),
+                note: "use --output instead".to_string()
+            }]
+        );
+        assert_eq!(
+            config.warnings()[0].to_string(),
+            "Option 'out' is deprecated, use --output instead"
+        );
+    }
+
+    #[test]
+    fn parse_global() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Show verbose output",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .global(),
+            args::OptionSpec::new(
+                'c',
+                "config",
+                "Specifies the config file",
+                true,
+                args::OptionPolicy::Exact(1),
+            )
+            .global(),
+        ];
+
+        let commands = [args::Command::new(
+            "build",
+            "Builds the project",
+            vec![args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Targets",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            )],
+        )];
+
+        // Required at the top level but given after the subcommand
+        let args = ["./test", "-v", "build", "lib", "-c", "a.toml"];
+        let config = args::Config::new_with_commands(&args[..], &specs, &commands)
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        assert_eq!(*config.option("config").unwrap(), ["a.toml".to_string()]);
+        let (_, sub) = config.subcommand().expect("Didn't parse subcommand");
+        assert!(sub.option("verbose").is_some());
+        assert_eq!(*sub.option("(unnamed)").unwrap(), ["lib".to_string()]);
+
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "-c", "a.toml", "build", "--config", "b.toml"])
+            .commands(&commands)
+            .interleaved(true)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("config").unwrap(), ["b.toml".to_string()]);
+    }
+
+    #[test]
+    fn parse_delimiter() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'F',
+                "features",
+                "Features to enable",
+                false,
+                args::OptionPolicy::Range(1, 3),
+            )
+            .value_delimiter(','),
+        ];
+
+        let config = args::Config::new(&["./test", "--features", "a,b", "c"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(
+            *config.option("features").unwrap(),
+            ["a", "b", "c"].map(String::from)
+        );
+
+        assert!(args::Config::new(&["./test", "-F", "a,b,c,d"], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_key_value() {
+        let define = |duplicates| {
+            args::OptionSpec::new(
+                'D',
+                "define",
+                "Defines a variable",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .duplicate_policy(args::DuplicatePolicy::Append)
+            .key_value(duplicates)
+        };
+        let unnamed = || {
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            )
+        };
+
+        let args = [
+            "./test",
+            "-D",
+            "mode=debug",
+            "-D",
+            "mode=release",
+            "-Djobs=4",
+        ];
+        let specs = [unnamed(), define(args::DuplicateKeyPolicy::LastWins)];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        let defines = config.map("define").unwrap();
+        assert_eq!(defines.len(), 2);
+        assert_eq!(defines["mode"], "release");
+        assert_eq!(defines["jobs"], "4");
+
+        let specs = [unnamed(), define(args::DuplicateKeyPolicy::FirstWins)];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.map("define").unwrap()["mode"], "debug");
+
+        let specs = [unnamed(), define(args::DuplicateKeyPolicy::Error)];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+        assert!(args::Config::new(&["./test", "-D", "mode"], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_occurrences() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'p',
+                "point",
+                "Adds a point",
+                false,
+                args::OptionPolicy::Exact(2),
+            )
+            .duplicate_policy(args::DuplicatePolicy::Append),
+        ];
+
+        let args = ["./test", "--point", "1", "2", "-p", "3", "4"];
+        let config = args::Config::new(&args[..], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(
+            *config.occurrences("point").unwrap(),
+            [["1", "2"].map(String::from), ["3", "4"].map(String::from)]
+        );
+        assert_eq!(config.option("point").unwrap().len(), 4);
+
+        let args = ["./test", "--point", "1", "2", "-p", "3"];
+        assert!(args::Config::new(&args[..], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_default_missing() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "When to use colors",
+                false,
+                args::OptionPolicy::AtMost(1),
+            )
+            .default_missing_values(&["auto"])
+            .default_values(&["never"]),
+        ];
+
+        let config = args::Config::new(&["./test"], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("color").unwrap(), ["never".to_string()]);
+
+        let config = args::Config::new(&["./test", "--color"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("color").unwrap(), ["auto".to_string()]);
+
+        let config = args::Config::new(&["./test", "--color", "always"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*config.option("color").unwrap(), ["always".to_string()]);
+    }
+
+    #[test]
+    fn parse_version() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+
+        for flag in ["-V", "--version"] {
+            let config = args::ConfigBuilder::new(&specs)
+                .args(&["./test", flag])
+                .version("test", "1.0.0")
+                .build()
+                .unwrap_or_else(|err| panic!("{}", err));
+            assert!(config.option("version").is_some());
+        }
+
+        assert!(args::ConfigBuilder::new(&specs)
+            .args(&["./test", "--version"])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn parse_auto_help() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Specifies the output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+
+        let commands = [args::Command::new(
+            "build",
+            "Builds the project",
+            vec![
+                args::OptionSpec::new(
+                    '\0',
+                    "(unnamed)",
+                    "Targets",
+                    false,
+                    args::OptionPolicy::AtLeast(0),
+                ),
+                args::OptionSpec::new(
+                    'r',
+                    "release",
+                    "Build with optimizations",
+                    false,
+                    args::OptionPolicy::Exact(0),
+                ),
+            ],
+        )];
+
+        let parse = |args: &[&str]| {
+            args::ConfigBuilder::new(&specs)
+                .args(args)
+                .commands(&commands)
+                .auto_help(true)
+                .parse()
+                .unwrap_or_else(|err| panic!("{}", err))
+        };
+
+        match parse(&["./test", "-h"]) {
+            args::ParseOutcome::HelpRequested(usage) => {
+                assert!(usage.contains("--output"));
+                assert!(usage.contains("--help"));
+            }
+            _ => panic!("Help wasn't requested"),
+        }
+
+        match parse(&["./test", "build", "--help"]) {
+            args::ParseOutcome::HelpRequested(usage) => {
+                assert!(usage.contains("--release"));
+                assert!(!usage.contains("--output"));
+            }
+            _ => panic!("Help wasn't requested"),
+        }
+
+        match parse(&["./test", "-o", "out.txt"]) {
+            args::ParseOutcome::Parsed(config) => assert!(config.option("help").is_none()),
+            _ => panic!("Arguments weren't parsed"),
+        }
+    }
+
+    #[test]
+    fn parse_dynamic_names() {
+        let plugins = ["lint", "format"];
+        let mut specs = vec![args::OptionSpec::new(
+            '\0',
+            "(unnamed)",
+            "Unnamed",
+            false,
+            args::OptionPolicy::AtLeast(0),
+        )];
+        specs.extend(plugins.iter().map(|plugin| {
+            args::OptionSpec::long(
+                format!("with-{}", plugin),
+                format!("Enables the {} plugin", plugin),
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+        }));
+
+        let config = args::Config::new(&["./test", "--with-format"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.option("with-format").is_some());
+        assert!(config.option("with-lint").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn parse_derive() {
+        use args::Options;
+
+        #[derive(Options)]
+        struct Opts {
+            /// Input files
+            #[option(positional, policy = AtLeast(1))]
+            files: Vec<String>,
+            /// Specifies the output file
+            #[option(abrev = 'o')]
+            output: Option<String>,
+            /// Number of jobs
+            #[option(abrev = 'j', default = "1")]
+            jobs: u32,
+            /// Show verbose output
+            #[option(abrev = 'v')]
+            verbose: bool,
+            /// Disables the cache
+            no_cache: bool,
+        }
+
+        let opts = Opts::parse(&["./test", "a.txt", "b.txt", "-vj", "4", "--no-cache"])
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(opts.files, ["a.txt", "b.txt"]);
+        assert_eq!(opts.output, None);
+        assert_eq!(opts.jobs, 4);
+        assert!(opts.verbose);
+        assert!(opts.no_cache);
+
+        let opts = Opts::parse(&["./test", "a.txt", "-o", "out.txt"])
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(opts.output.as_deref(), Some("out.txt"));
+        assert_eq!(opts.jobs, 1);
+
+        assert!(Opts::parse(&["./test"]).is_err());
+        assert!(Opts::parse(&["./test", "a.txt", "-j", "four"]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn parse_deserialize() {
+        #[derive(serde::Deserialize)]
+        struct Opts {
+            verbose: bool,
+            dry_run: bool,
+            jobs: u32,
+            output: Option<String>,
+            files: Vec<String>,
+            mode: Mode,
+        }
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Mode {
+            Debug,
+            Release,
+        }
+
+        let specs = [
+            args::OptionSpec::new('\0', "(unnamed)", "", false, args::OptionPolicy::Exact(0)),
+            args::OptionSpec::new('v', "verbose", "", false, args::OptionPolicy::Exact(0)),
+            args::OptionSpec::new('n', "dry-run", "", false, args::OptionPolicy::Exact(0)),
+            args::OptionSpec::new('j', "jobs", "", false, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new('o', "output", "", false, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new('f', "files", "", false, args::OptionPolicy::AtLeast(0)),
+            args::OptionSpec::new('m', "mode", "", false, args::OptionPolicy::Exact(1))
+                .default_values(&["debug"]),
+        ];
+
+        let config = args::Config::new(
+            &[
+                "./test",
+                "-v",
+                "-j",
+                "4",
+                "--dry-run",
+                "-f",
+                "a.txt",
+                "b.txt",
+            ],
+            &specs,
+        )
+        .unwrap();
+        let opts: Opts = config.deserialize().unwrap_or_else(|err| panic!("{}", err));
+        assert!(opts.verbose);
+        assert!(opts.dry_run);
+        assert_eq!(opts.jobs, 4);
+        assert_eq!(opts.output, None);
+        assert_eq!(opts.files, ["a.txt", "b.txt"]);
+        assert_eq!(opts.mode, Mode::Debug);
+
+        let config = args::Config::new(&["./test", "-j", "four"], &specs).unwrap();
+        assert!(config.deserialize::<Opts>().is_err());
+
+        let config = args::Config::new(&["./test", "-o", "out", "-m", "release"], &specs).unwrap();
+        assert!(config.deserialize::<Opts>().is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let specs = [
+            args::OptionSpec::new('\0', "(unnamed)", "", false, args::OptionPolicy::Exact(0)),
+            args::OptionSpec::new('o', "output", "", true, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new('j', "jobs", "", false, args::OptionPolicy::Exact(1))
+                .with_validator(|v| v.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())),
+        ];
+
+        let err = |args: &[&str]| args::Config::new(args, &specs).err().unwrap();
+
+        assert_eq!(
+            err(&["./test"]),
+            args::ParseError::MissingRequired {
+                name: "output".to_string()
+            }
+        );
+        assert_eq!(
+            err(&["./test", "-o", "out", "--ouput"]),
+            args::ParseError::UnknownOption {
+                token: "--ouput".to_string(),
+                suggestion: Some("output".to_string()),
+                span: Some(args::ArgSpan {
+                    index: 3,
+                    token: "--ouput".to_string()
+                }),
+            }
+        );
+        assert_eq!(
+            err(&["./test", "-o", "a", "b"]),
+            args::ParseError::WrongValueCount {
+                name: "output".to_string(),
+                count: 2,
+                expected: args::OptionPolicy::Exact(1),
+                span: Some(args::ArgSpan {
+                    index: 1,
+                    token: "-o".to_string()
+                }),
+            }
+        );
+        assert!(matches!(
+            err(&["./test", "-o", "out", "-j", "x"]),
+            args::ParseError::InvalidValue { name, value, .. } if name == "jobs" && value == "x"
+        ));
+        assert_eq!(
+            err(&["./test", "-o", "out", "-x"]).to_string(),
+            "Invalid option -x"
+        );
+    }
+
+    #[test]
+    fn parse_error_span() {
+        let specs = [
+            args::OptionSpec::new('\0', "(unnamed)", "", false, args::OptionPolicy::AtLeast(0)),
+            args::OptionSpec::new('v', "verbose", "", false, args::OptionPolicy::Exact(0)),
+            args::OptionSpec::new('j', "jobs", "", false, args::OptionPolicy::Exact(1))
+                .choices(&["1", "2", "4"]),
+            args::OptionSpec::new('r', "required", "", true, args::OptionPolicy::Exact(0)),
+        ];
+        let commands = [args::Command::new(
+            "build",
+            "",
+            vec![
+                args::OptionSpec::new('\0', "(unnamed)", "", false, args::OptionPolicy::AtLeast(0)),
+                args::OptionSpec::new('j', "jobs", "", false, args::OptionPolicy::Exact(1)),
+            ],
+        )];
+
+        let span = |args: &[&str]| {
+            let err = args::Config::new_with_commands(args, &specs, &commands)
+                .err()
+                .expect("Arguments weren't rejected");
+            err.span().map(|span| (span.index, span.token.clone()))
+        };
+
+        assert_eq!(span(&["./test", "-r", "-vx"]), Some((2, "-vx".to_string())));
+        assert_eq!(
+            span(&["./test", "-r", "-j", "3", "-v"]),
+            Some((3, "3".to_string()))
+        );
+        assert_eq!(
+            span(&["./test", "-r", "--jobs=3"]),
+            Some((2, "--jobs=3".to_string()))
+        );
+        assert_eq!(
+            span(&["./test", "-r", "build", "-j", "1", "--what"]),
+            Some((5, "--what".to_string()))
+        );
+        assert_eq!(span(&["./test"]), None);
+    }
+
+    #[test]
+    fn usage_sections() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'q',
+                "quiet",
+                "No output",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .section("Logging"),
+            args::OptionSpec::new(
+                'i',
+                "input",
+                "Input file",
+                true,
+                args::OptionPolicy::Exact(1),
+            )
+            .section("Input"),
+            args::OptionSpec::new(
+                'h',
+                "help",
+                "Shows help",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "More output",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .section("Logging"),
+        ];
+
+        let usage = args::Config::generate_usage(&specs, true, true);
+        let position = |text: &str| usage.find(text).unwrap();
+        assert!(position("--help") < position("Logging:\n"));
+        assert!(position("Logging:\n") < position("--quiet"));
+        assert!(position("--quiet") < position("--verbose"));
+        assert!(position("--verbose") < position("Input:\n"));
+        assert!(position("Input:\n") < position("--input"));
+
+        let usage = args::Config::generate_usage(&specs, true, false);
+        assert!(usage.contains("Input:") && !usage.contains("Logging:"));
+    }
+
+    #[test]
+    fn parse_stdin_value() {
+        use std::io::Read;
+
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'i',
+                "input",
+                "Input file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+
+        let config = args::Config::new(&["./test", "-", "-i", "-"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.option("input"), Some(&["-".to_string()][..]));
+        assert_eq!(config.option("(unnamed)"), Some(&["-".to_string()][..]));
+
+        let path = std::env::temp_dir().join("libcli_reader_for.txt");
+        std::fs::write(&path, "contents").unwrap();
+        let config = args::Config::new(&["./test", "-i", path.to_str().unwrap()], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let mut contents = String::new();
+        config
+            .reader_for("input")
+            .unwrap()
+            .expect("Input wasn't given")
+            .read_to_string(&mut contents)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents, "contents");
+
+        assert!(config.reader_for("(unnamed)").unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_value_range() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('p', "port", "Port", false, args::OptionPolicy::Exact(1))
+                .range(1..=65535),
+            args::OptionSpec::new('l', "level", "Level", false, args::OptionPolicy::Exact(1))
+                .range(..10),
+        ];
+
+        let config = args::Config::new(&["./test", "-p", "8080", "--level=-3"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.get::<u16>("port"), Ok(Some(8080)));
+        assert_eq!(config.get::<i8>("level"), Ok(Some(-3)));
+
+        let err = args::Config::new(&["./test", "-p", "0"], &specs)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("1..=65535"));
+        assert!(args::Config::new(&["./test", "-l", "10"], &specs).is_err());
+        assert!(args::Config::new(&["./test", "-p", "http"], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_path_validators() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('c', "config", "Config", false, args::OptionPolicy::Exact(1))
+                .with_validator(args::exists_file()),
+            args::OptionSpec::new('d', "dir", "Directory", false, args::OptionPolicy::Exact(1))
+                .with_validator(args::exists_dir()),
+            args::OptionSpec::new('o', "output", "Output", false, args::OptionPolicy::Exact(1))
+                .with_validator(args::creatable_path()),
+        ];
+
+        let dir = std::env::temp_dir();
+        let file = dir.join("libcli_path_validators.toml");
+        std::fs::write(&file, "").unwrap();
+        let (dir, file) = (dir.to_str().unwrap(), file.to_str().unwrap());
+        let missing = std::env::temp_dir().join("libcli_missing").join("out.txt");
+        let missing = missing.to_str().unwrap();
+
+        let parse = |args: &[&str]| args::Config::new(args, &specs);
+        assert!(parse(&["./test", "-c", file, "-d", dir, "-o", "out.txt"]).is_ok());
+
+        let err = parse(&["./test", "-c", "missing.toml"]).err().unwrap();
+        assert!(err.to_string().contains("missing.toml"));
+        assert!(parse(&["./test", "-c", dir]).is_err());
+        assert!(parse(&["./test", "-d", file]).is_err());
+        assert!(parse(&["./test", "-o", dir]).is_err());
+        assert!(parse(&["./test", "-o", missing]).is_err());
+
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn parse_matches() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('d', "date", "Date", false, args::OptionPolicy::Exact(1))
+                .matches(r"^\d{4}-\d{2}-\d{2}$"),
+        ];
+
+        assert!(args::Config::new(&["./test", "-d", "2024-02-29"], &specs).is_ok());
+
+        let err = args::Config::new(&["./test", "-d", "29/02/2024"], &specs)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains(r"^\d{4}-\d{2}-\d{2}$"));
+    }
+
+    #[test]
+    fn spec_value_hint() {
+        let spec =
+            args::OptionSpec::new('o', "output", "Output", false, args::OptionPolicy::Exact(1));
+        assert_eq!(spec.get_value_hint(), args::ValueHint::Unknown);
+
+        let spec = spec.value_hint(args::ValueHint::DirPath);
+        assert_eq!(spec.get_value_hint(), args::ValueHint::DirPath);
+    }
+
+    #[test]
+    fn parse_on_parse() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let (verbose_seen, output_seen) = (seen.clone(), seen.clone());
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .on_parse(move |_| verbose_seen.borrow_mut().push("verbose".to_string())),
+            args::OptionSpec::new('o', "output", "Output", false, args::OptionPolicy::Exact(1))
+                .on_parse(move |values| output_seen.borrow_mut().push(values.join(","))),
+            args::OptionSpec::new('j', "jobs", "Jobs", false, args::OptionPolicy::Exact(1))
+                .default_values(&["1"])
+                .on_parse(|_| panic!("Called for a default value")),
+        ];
+
+        args::Config::new(&["./test", "-vv", "-o", "out.txt"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(*seen.borrow(), ["verbose", "verbose", "out.txt"]);
+
+        seen.borrow_mut().clear();
+        assert!(args::Config::new(&["./test", "-v", "--invalid"], &specs).is_err());
+        assert_eq!(*seen.borrow(), ["verbose"]);
+    }
+
+    #[test]
+    fn config_iter() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new('o', "output", "Output", false, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new('j', "jobs", "Jobs", false, args::OptionPolicy::Exact(1))
+                .default_values(&["1"]),
+            args::OptionSpec::new('q', "quiet", "Quiet", false, args::OptionPolicy::Exact(0)),
+        ];
+
+        let config = args::Config::new(&["./test", "in.txt", "-v", "-o", "out.txt"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        let names: Vec<_> = config.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["(unnamed)", "jobs", "output", "verbose"]);
+        assert_eq!(config.iter().nth(2).unwrap().1, ["out.txt"]);
+        assert_eq!(config.len(), 4);
+        assert!(config.contains("jobs") && !config.contains("quiet"));
+    }
+
+    #[test]
+    fn config_flag_single() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new('q', "quiet", "Quiet", false, args::OptionPolicy::Exact(0)),
+            args::OptionSpec::new('o', "output", "Output", false, args::OptionPolicy::Exact(1)),
+        ];
+
+        let config = args::Config::new(&["./test", "a", "b", "-v", "-o", "out.txt"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.flag("verbose") && !config.flag("quiet"));
+        assert_eq!(config.single("output"), Some("out.txt"));
+        assert_eq!(config.single("verbose"), None);
+
+        let result = std::panic::catch_unwind(|| config.single("(unnamed)"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn config_with_specs() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .alias("out"),
+        ];
+
+        let config = args::SpecConfig::new(&["./test", "--out", "a.txt"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.single("output"), Some("a.txt"));
+        assert_eq!(config.spec("out").map(|spec| spec.name()), Some("output"));
+        assert!(config.spec("input").is_none());
+        assert_eq!(
+            config.usage(),
+            args::Config::generate_usage(&specs, true, true)
+        );
+        assert!(config.into_inner().flag("output"));
+    }
+
+    #[test]
+    fn parse_exclusive() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            )
+            .default_values(&["a.out"]),
+            args::OptionSpec::new(
+                'i',
+                "init",
+                "Creates a config",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .exclusive(),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                true,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        // Required options may be missing and default values don't conflict
+        let config = args::Config::new(&["./test", "--init"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.flag("init"));
+        assert_eq!(config.single("output"), Some("a.out"));
+
+        // The other option is the first given in the order of the specs
+        for (args, other) in [
+            (&["./test", "-i", "-v"][..], "verbose"),
+            (&["./test", "file", "-i"], "(unnamed)"),
+            (&["./test", "-o", "b", "--init"], "output"),
+            (&["./test", "file", "-v", "-i"], "(unnamed)"),
+            (&["./test", "-v", "-o", "b", "-i"], "output"),
+        ] {
+            assert_eq!(
+                args::Config::new(args, &specs).err(),
+                Some(args::ParseError::ExclusiveOption {
+                    name: String::from("init"),
+                    other: String::from(other),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn parse_hyphen_values() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "offset",
+                "Offset",
+                false,
+                args::OptionPolicy::Range(1, 2),
+            )
+            .allow_hyphen_values(),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let config = args::Config::new(&["./test", "-o", "-x", "--verbose", "-v"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.option("offset").unwrap(), ["-x", "--verbose"]);
+        assert!(config.flag("verbose"));
+
+        // Without the setting the value is taken as options
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('o', "offset", "Offset", false, args::OptionPolicy::Exact(1)),
+        ];
+        assert!(args::Config::new(&["./test", "--offset", "-x"], &specs).is_err());
+    }
+
+    #[test]
+    fn parse_negative_numbers() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('n', "lines", "Lines", false, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new('1', "one", "One", false, args::OptionPolicy::Exact(0)),
+        ];
+
+        let config = args::Config::new(&["./test", "-3", "-2.5", "file", "-n", "-20"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.option("(unnamed)").unwrap(), ["-3", "-2.5", "file"]);
+        assert_eq!(config.single("lines"), Some("-20"));
+
+        // A registered digit abbreviation is still an option
+        let config =
+            args::Config::new(&["./test", "-1"], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.flag("one"));
+        assert_eq!(config.option("(unnamed)"), Some(&[][..]));
+    }
+
+    #[test]
+    fn unnamed_error_messages() {
+        let specs = [args::OptionSpec::new(
+            '\0',
+            args::UNNAMED,
+            "Unnamed",
+            true,
+            args::OptionPolicy::Exact(1),
+        )];
+        let err = args::Config::new(&["./test", "a", "b"], &specs)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "2 values supplied for arguments, expected exactly 1"
+        );
+
+        // A positional spec chooses the name shown to users
+        let specs = [args::OptionSpec::positional(
+            "FILES",
+            "Files",
+            true,
+            args::OptionPolicy::AtLeast(1),
+        )];
+        let err = args::Config::new(&["./test"], &specs).err().unwrap();
+        assert!(err.to_string().contains("'FILES'"));
+        let config =
+            args::Config::new(&["./test", "a"], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.option("FILES").unwrap(), ["a"]);
+    }
+
+    #[test]
+    fn parse_without_positionals() {
+        let specs = [args::OptionSpec::new(
+            'o',
+            "output",
+            "Output file",
+            false,
+            args::OptionPolicy::Exact(1),
+        )];
+
+        let config = args::Config::new(&["./test", "-o", "a.txt"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.single("output"), Some("a.txt"));
+        assert!(!config.contains(args::UNNAMED));
+
+        let err = args::Config::new(&["./test", "stray"], &specs)
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Unexpected argument 'stray'");
+        assert_eq!(err.span().map(|span| span.index), Some(1));
+    }
+
+    #[test]
+    fn config_program_name() {
+        let specs = [args::OptionSpec::new(
+            '\0',
+            "(unnamed)",
+            "Unnamed",
+            true,
+            args::OptionPolicy::Exact(1),
+        )];
+
+        let config = args::Config::new(&["/usr/local/bin/tool", "file"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.program_name(), "tool");
+        assert_eq!(config.command(), "/usr/local/bin/tool");
+        assert_eq!(config.option("(unnamed)").unwrap(), ["file"]);
+    }
+
+    #[test]
+    fn parse_invalid_specs() {
+        let unnamed = || {
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            )
+        };
+        let option = |abrev, name| {
+            args::OptionSpec::new(abrev, name, "", false, args::OptionPolicy::Exact(1))
+        };
+
+        let invalid = [
+            (
+                vec![unnamed(), option('a', "all"), option('b', "all")],
+                args::SpecError::DuplicateName { name: "all".into() },
+            ),
+            (
+                vec![
+                    unnamed(),
+                    option('a', "all"),
+                    option('b', "both").alias("all"),
+                ],
+                args::SpecError::DuplicateName { name: "all".into() },
+            ),
+            (
+                vec![unnamed(), option('a', "all"), option('a', "any")],
+                args::SpecError::DuplicateAbrev { abrev: 'a' },
+            ),
+            (
+                vec![unnamed(), option('a', "all").default_values(&["1", "2"])],
+                args::SpecError::InvalidDefaults {
+                    name: "all".into(),
+                    count: 2,
+                    policy: args::OptionPolicy::Exact(1),
+                },
+            ),
+        ];
+
+        for (specs, expected) in invalid {
+            assert_eq!(args::validate_specs(&specs), Err(expected.clone()));
+            assert_eq!(
+                args::Config::new(&["./test"], &specs).err(),
+                Some(args::ParseError::InvalidSpec(expected))
+            );
+        }
+
+        let specs = [
+            unnamed(),
+            args::OptionSpec::new('r', "range", "", false, args::OptionPolicy::Range(3, 1)),
+        ];
+        assert!(matches!(
+            args::validate_specs(&specs),
+            Err(args::SpecError::InvalidPolicy { .. })
+        ));
+    }
+
+    #[test]
+    fn config_sources() {
+        std::env::set_var("LIBCLI_TEST_SOURCE_LEVEL", "3");
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('l', "level", "Level", false, args::OptionPolicy::Exact(1))
+                .env("LIBCLI_TEST_SOURCE_LEVEL"),
+            args::OptionSpec::new('j', "jobs", "Jobs", false, args::OptionPolicy::Exact(1))
+                .default_values(&["1"]),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let config =
+            args::Config::new(&["./test", "-v"], &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.source("verbose"), Some(&args::Source::Cli));
+        assert_eq!(
+            config.source("level"),
+            Some(&args::Source::Env("LIBCLI_TEST_SOURCE_LEVEL".into()))
+        );
+        assert_eq!(config.source("jobs"), Some(&args::Source::Default));
+        assert_eq!(config.source("(unnamed)"), Some(&args::Source::Cli));
+
+        let config = args::Config::new(&["./test", "-l", "5", "-j", "2"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.source("level"), Some(&args::Source::Cli));
+        assert_eq!(config.source("jobs"), Some(&args::Source::Cli));
+    }
+
+    #[test]
+    fn parse_warnings() {
+        std::env::set_var("LIBCLI_WARN_LEVEL", "1");
+        std::env::set_var("LIBCLI_WARN_LEVLE", "1");
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('l', "level", "Level", false, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "--verb"])
+            .env_prefix("LIBCLI_WARN")
+            .prefix_matching(true)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(config.flag("verbose"));
+        assert_eq!(config.single("level"), Some("1"));
+        assert_eq!(
+            config.warnings(),
+            [
+                args::ParseWarning::Corrected {
+                    given: "verb".to_string(),
+                    name: "verbose".to_string()
+                },
+                args::ParseWarning::UnknownEnv {
+                    var: "LIBCLI_WARN_LEVLE".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_from_str() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('o', "output", "Output", false, args::OptionPolicy::Exact(1)),
+        ];
+
+        let config = args::Config::from_str(r#"a\ b 'c d' "" e -o "my file.txt""#, &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.single("output"), Some("my file.txt"));
+        assert_eq!(config.option("(unnamed)").unwrap(), ["a b", "c d", "", "e"]);
+        assert_eq!(config.command(), "");
+
+        assert!(args::Config::from_str("-o 'open", &specs).is_err());
+        assert!(args::split_args("trailing\\").is_err());
+        assert_eq!(args::split_args("  ").unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn config_to_args() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('o', "offset", "Offset", false, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new('p', "point", "Point", false, args::OptionPolicy::Exact(2))
+                .duplicate_policy(args::DuplicatePolicy::Append),
+            args::OptionSpec::new(
+                'F',
+                "features",
+                "Features",
+                false,
+                args::OptionPolicy::AtLeast(1),
+            )
+            .value_delimiter(','),
+            args::OptionSpec::new('j', "jobs", "Jobs", false, args::OptionPolicy::Exact(1))
+                .default_values(&["4"]),
+            args::OptionSpec::trailing("COMMAND", "Command", 0),
+        ];
+
+        let config = args::Config::new(
+            &[
+                "./test",
+                "a",
+                "b",
+                "--offset=-x",
+                "-p",
+                "1",
+                "2",
+                "-p",
+                "3",
+                "4",
+                "-F",
+                "x,y",
+                "--",
+                "ls",
+                "-l",
+            ],
+            &specs,
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+        let args = config.to_args(&specs);
+        assert_eq!(
+            args,
+            [
+                "./test",
+                "a",
+                "b",
+                "--offset=-x",
+                "--point",
+                "1",
+                "2",
+                "--point",
+                "3",
+                "4",
+                "--features=x,y",
+                "--jobs=4",
+                "--",
+                "ls",
+                "-l"
+            ]
+        );
+
+        // The reconstructed arguments parse to the same values
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+        let reparsed = args::Config::new(&args, &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(
+            reparsed.iter().collect::<Vec<_>>(),
+            config.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn zsh_completion() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .value_hint(args::ValueHint::FilePath),
+            args::OptionSpec::new(
+                '\0',
+                "color",
+                "Colorize [default: auto]",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .choices(&["auto", "never"]),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .global(),
+            args::OptionSpec::new(
+                '\0',
+                "secret",
+                "Secret",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .hidden(),
+        ];
+        let commands = [args::Command::new(
+            "build",
+            "Builds the project",
+            vec![args::OptionSpec::new(
+                'r',
+                "release",
+                "Release mode",
+                false,
+                args::OptionPolicy::Exact(0),
+            )],
+        )];
+
+        let script = args::Config::generate_zsh_completion("my-app", &specs, &commands);
+        assert!(script.starts_with("#compdef my-app\n"));
+        assert!(script.contains("'(-o --output)--output=[Output file]:output:_files'"));
+        assert!(script
+            .contains("'(--color)--color=[Colorize \\[default\\: auto\\]]:color:(auto never)'"));
+        assert!(script.contains("'build:Builds the project'"));
+        assert!(script.contains("\n_my_app_build() {"));
+        assert!(script.contains("'(-r --release)-r[Release mode]'"));
+        // The global option is completed after the subcommand as well
+        assert_eq!(
+            script.matches("'(-v --verbose)--verbose[Verbose]'").count(),
+            2
+        );
+        assert!(!script.contains("secret"));
+        assert!(script.ends_with("\n_my_app \"$@\"\n"));
+    }
+
+    #[test]
+    fn fish_completion() {
+        let specs = [
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .global(),
+            args::OptionSpec::new(
+                '\0',
+                "color",
+                "Colorize",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .choices(&["auto", "never"]),
+        ];
+        let commands = [args::Command::new(
+            "build",
+            "Builds the project",
+            vec![
+                args::OptionSpec::positional(
+                    "DIR",
+                    "Directory",
+                    false,
+                    args::OptionPolicy::Exact(1),
+                ),
+                args::OptionSpec::new(
+                    'o',
+                    "out-dir",
+                    "Output",
+                    false,
+                    args::OptionPolicy::Exact(1),
+                )
+                .value_hint(args::ValueHint::DirPath),
+            ],
+        )];
+
+        let script = args::Config::generate_fish_completion("myapp", &specs, &commands);
+        let lines: Vec<&str> = script.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "complete -c myapp -s v -l verbose -d 'Verbose'",
+                "complete -c myapp -n 'not __fish_seen_subcommand_from build' -l color -d 'Colorize' -x -a 'auto never'",
+                "complete -c myapp -n 'not __fish_seen_subcommand_from build' -f -a 'build' -d 'Builds the project'",
+                "complete -c myapp -n '__fish_seen_subcommand_from build' -s o -l out-dir -d 'Output' -x -a '(__fish_complete_directories)'",
+            ]
+        );
+    }
+
+    #[test]
+    fn powershell_completion() {
+        let specs = [
+            args::OptionSpec::new('v', "verbose", "", false, args::OptionPolicy::Exact(0)).global(),
+            args::OptionSpec::new(
+                '\0',
+                "user's",
+                "Quoted",
+                false,
+                args::OptionPolicy::Exact(1),
+            ),
+        ];
+        let commands = [args::Command::new(
+            "build",
+            "Builds the project",
+            vec![args::OptionSpec::new(
+                'r',
+                "release",
+                "Release mode",
+                false,
+                args::OptionPolicy::Exact(0),
+            )],
+        )];
+
+        let script = args::Config::generate_powershell_completion("myapp", &specs, &commands);
+        assert!(script
+            .contains("Register-ArgumentCompleter -Native -CommandName 'myapp' -ScriptBlock {"));
+        assert!(script.contains("        'myapp' {\n            [CompletionResult]::new('-v', 'v', [CompletionResultType]::ParameterName, '-v')\n"));
+        assert!(script.contains("[CompletionResult]::new('--user''s', 'user''s', [CompletionResultType]::ParameterName, 'Quoted')"));
+        assert!(script.contains("[CompletionResult]::new('build', 'build', [CompletionResultType]::ParameterValue, 'Builds the project')"));
+        assert!(script.contains("        'myapp;build' {\n"));
+        assert!(script.contains("[CompletionResult]::new('--release', 'release', [CompletionResultType]::ParameterName, 'Release mode')"));
+        // The global option is completed after the subcommand as well
+        assert_eq!(script.matches("new('--verbose'").count(), 2);
+    }
+
+    #[test]
+    fn dynamic_completion() {
+        let dir = std::env::temp_dir().join("libcli_test_completion");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("file.txt"), "").unwrap();
+        let dir = dir.to_str().unwrap();
+
+        let specs = [
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .global(),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "Colorize",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .choices(&["auto", "never"]),
+            args::OptionSpec::new(
+                'o',
+                "out-dir",
+                "Output",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .value_hint(args::ValueHint::DirPath),
+        ];
+        let commands = [args::Command::new(
+            "build",
+            "Builds the project",
+            vec![
+                args::OptionSpec::positional("FILE", "File", false, args::OptionPolicy::Exact(1))
+                    .value_hint(args::ValueHint::FilePath),
+                args::OptionSpec::new(
+                    'r',
+                    "release",
+                    "Release",
+                    false,
+                    args::OptionPolicy::Exact(0),
+                ),
+            ],
+        )];
+        let complete = |words: &[&str]| args::Config::complete(words, &specs, &commands);
+
+        assert_eq!(complete(&[""]), ["build"]);
+        assert_eq!(complete(&["--"]), ["--verbose", "--color", "--out-dir"]);
+        assert_eq!(complete(&["-c", ""]), ["auto", "never"]);
+        assert_eq!(complete(&["--color=n"]), ["--color=never"]);
+        assert_eq!(
+            complete(&["-o", &format!("{}/", dir)]),
+            [format!("{}/sub/", dir)]
+        );
+        assert_eq!(
+            complete(&["build", "-"]),
+            ["-v", "--verbose", "-r", "--release"]
+        );
+        assert_eq!(
+            complete(&["-v", "build", &format!("{}/f", dir)]),
+            [format!("{}/file.txt", dir)]
+        );
+    }
+
+    #[test]
+    fn man_page() {
+        let specs = [
+            args::OptionSpec::positional(
+                "FILES",
+                "Input files",
+                true,
+                args::OptionPolicy::AtLeast(1),
+            ),
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .default_values(&["a.out"]),
+            args::OptionSpec::new(
+                '\0',
+                "dry-run",
+                ".Prints what would be done",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new(
+                '\0',
+                "secret",
+                "Secret",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .hidden(),
+        ];
+
+        let page = args::Config::generate_man("my-app", "1.0", "Processes files", &specs);
+        assert!(page.starts_with(".TH MY\\-APP 1 \"\" \"my\\-app 1.0\" \"User Commands\"\n"));
+        assert!(page.contains(".SH NAME\nmy\\-app \\- Processes files\n"));
+        assert!(page.contains(".SH ARGUMENTS\n.TP\n\\fIFILES\\fR\nInput files\n.br\n[required]\n"));
+        assert!(page.contains(
+            ".TP\n\\fB\\-o\\fR, \\fB\\-\\-output\\fR \\fIvalue\\fR\nOutput file\n.br\n[values: exactly 1]\n.br\n[default: a.out]\n"
+        ));
+        assert!(page.contains("\\fB\\-\\-dry\\-run\\fR\n\\&.Prints what would be done\n"));
+        assert!(!page.contains("secret"));
+    }
+
+    #[test]
+    fn markdown_table() {
+        let specs = [
+            args::OptionSpec::positional(
+                "FILES",
+                "Input files",
+                true,
+                args::OptionPolicy::AtLeast(1),
+            ),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "Colorize\nthe output",
+                false,
+                args::OptionPolicy::Exact(1),
+            )
+            .choices(&["auto", "never"]),
+            args::OptionSpec::new('\0', "pipe", "a | b", false, args::OptionPolicy::Exact(0)),
+            args::OptionSpec::new(
+                '\0',
+                "secret",
+                "Secret",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .hidden(),
+        ];
+
+        assert_eq!(
+            args::Config::generate_markdown(&specs),
+            "| Option | Short | Required | Default | Description |\n\
+             | --- | --- | --- | --- | --- |\n\
+             | `<FILES>` |  | yes |  | Input files |\n\
+             | `--color` | `-c` | no |  | Colorize<br>the output (possible values: auto, never) |\n\
+             | `--pipe` |  | no |  | a \\| b |\n"
+        );
+    }
+
+    #[test]
+    fn usage_wrapped() {
+        let specs = [
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Where the result is written to",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                '\0',
+                "a-rather-long-option-name",
+                "Long",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        assert_eq!(
+            args::Config::generate_usage_wrapped(&specs, true, true, Some(40)),
+            "    -o, --output <VALUE>\n                    Where the result is\n                    written to\n                    [required]\n                    [values: exactly 1]\n        --a-rather-long-option-name\n                    Long\n"
+        );
+
+        let outcome = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "--help"])
+            .auto_help(true)
+            .help_width(40)
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err));
+        match outcome {
+            args::ParseOutcome::HelpRequested(usage) => {
+                assert!(usage.contains(
+                    "    -h, --help      Prints help\n                    information\n"
+                ));
+            }
+            _ => panic!("Help wasn't requested"),
+        }
+    }
+
+    #[test]
+    fn usage_colored() {
+        let specs = [
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new(
+                'q',
+                "quiet",
+                "Prints less",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .section("Logging"),
+        ];
+
+        let usage =
+            args::Config::generate_usage_colored(&specs, true, true, args::ColorChoice::Always);
+        assert!(usage.contains("\x1b[1m-o, --output <VALUE>\x1b[0m \x1b[31m[required]\x1b[0m"));
+        assert!(usage.contains("\x1b[1;4mLogging\x1b[0m:\n"));
+        assert_eq!(
+            args::Config::generate_usage_colored(&specs, true, true, args::ColorChoice::Never),
+            args::Config::generate_usage(&specs, true, true)
+        );
+
+        let outcome = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "-h"])
+            .auto_help(true)
+            .help_width(80)
+            .color(args::ColorChoice::Always)
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err));
+        match outcome {
+            args::ParseOutcome::HelpRequested(usage) => {
+                assert!(usage
+                    .contains("    \x1b[1m-h, --help\x1b[0m            Prints help information\n"));
+            }
+            _ => panic!("Help wasn't requested"),
+        }
+    }
+
+    #[test]
+    fn help_template() {
+        let specs = [args::OptionSpec::new(
+            'o',
+            "output",
+            "Output file",
+            true,
+            args::OptionPolicy::Exact(1),
+        )];
+
+        let help = args::Help::new("myapp", &specs)
+            .about("Processes files")
+            .examples("myapp -o out.txt")
+            .width(80)
+            .render();
+        assert_eq!(
+            help,
+            "Processes files\n\n\
+             Usage: myapp --output <VALUE>\n\n\
+             Options:\n    \
+             -o, --output <VALUE>  Output file [required] [values: exactly 1]\n\n\
+             Examples:\n    \
+             myapp -o out.txt\n"
+        );
+
+        let outcome = args::ConfigBuilder::new(&specs)
+            .args(&["./myapp", "--help"])
+            .auto_help(true)
+            .help_width(80)
+            .help_template("{usage}\n{after-help}")
+            .after_help("Report bugs to <bugs@example.com>")
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err));
+        match outcome {
+            args::ParseOutcome::HelpRequested(help) => assert_eq!(
+                help,
+                "Usage: myapp --output <VALUE>\nReport bugs to <bugs@example.com>\n"
+            ),
+            _ => panic!("Help wasn't requested"),
+        }
+    }
+
+    #[test]
+    fn usage_synopsis() {
+        let specs = [
+            args::OptionSpec::positional("SRC", "Source", true, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::positional(
+                "DEST",
+                "Destinations",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new('o', "output", "Output", true, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::short(
+                'd',
+                "define",
+                "Defines",
+                true,
+                args::OptionPolicy::AtLeast(1),
+            ),
+            args::OptionSpec::new('\0', "level", "Level", true, args::OptionPolicy::AtMost(1)),
+            args::OptionSpec::new('\0', "size", "Size", true, args::OptionPolicy::Exact(2)),
+            args::OptionSpec::trailing("REST", "Rest", 0),
+        ];
+
+        assert_eq!(
+            args::Config::generate_synopsis("myapp", &specs),
+            "myapp --output <VALUE> -d <VALUE>... --level [VALUE] --size <VALUE> <VALUE> <SRC> [DEST]... -- [REST]..."
+        );
+
+        let specs = [args::OptionSpec::new(
+            '\0',
+            "(unnamed)",
+            "Args",
+            true,
+            args::OptionPolicy::AtLeast(0),
+        )];
+        assert_eq!(
+            args::Config::generate_synopsis("myapp", &specs),
+            "myapp <ARGS>..."
+        );
+    }
+
+    #[test]
+    fn usage_value_name() {
+        let specs = [
+            args::OptionSpec::new('o', "output", "Output", true, args::OptionPolicy::Exact(1))
+                .value_name("FILE"),
+            args::OptionSpec::new(
+                'i',
+                "include",
+                "Includes",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            )
+            .value_name("DIR"),
+            args::OptionSpec::new('\0', "jobs", "Jobs", false, args::OptionPolicy::Range(1, 3))
+                .value_name("N"),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        let usage = args::Config::generate_usage(&specs, true, true);
+        assert!(usage.contains("    -o, --output <FILE> [required]\n"));
+        assert!(usage.contains("    -i, --include [DIR]... \n"));
+        assert!(usage.contains("        --jobs <N>... \n"));
+        assert!(usage.contains("    -v, --verbose \n"));
+        assert_eq!(
+            args::Config::generate_synopsis("myapp", &specs),
+            "myapp [OPTIONS] --output <FILE>"
+        );
+    }
+
+    #[test]
+    fn usage_attributes() {
+        let specs = [args::OptionSpec::new(
+            'c',
+            "color",
+            "When to use colors",
+            false,
+            args::OptionPolicy::Exact(1),
+        )
+        .choices(&["auto", "always", "never"])
+        .default_values(&["auto"])];
+
+        assert!(args::Config::generate_usage(&specs, true, true).contains(
+            "\n        [values: exactly 1] [default: auto] [possible values: auto, always, never]\n"
+        ));
+
+        let outcome = args::ConfigBuilder::new(&specs)
+            .args(&["./myapp", "--help"])
+            .env_prefix("MYAPP")
+            .auto_help(true)
+            .help_width(100)
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err));
+        match outcome {
+            args::ParseOutcome::HelpRequested(help) => assert!(help.contains(
+                "    -c, --color <VALUE>  When to use colors [values: exactly 1] [default: auto] [env: MYAPP_COLOR]\n                         [possible values: auto, always, never]\n"
+            )),
+            _ => panic!("Help wasn't requested"),
+        }
+    }
+
+    #[test]
+    fn long_help() {
+        let specs = [args::OptionSpec::new(
+            'j',
+            "jobs",
+            "Number of jobs",
+            false,
+            args::OptionPolicy::Exact(1),
+        )
+        .long_desc("Number of jobs to run in parallel\nDefaults to the number of CPUs")];
+
+        let help = |arg: &str| match args::ConfigBuilder::new(&specs)
+            .args(&["./myapp", arg])
+            .auto_help(true)
+            .help_width(100)
+            .about("Runs jobs")
+            .long_about("Runs jobs in parallel")
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err))
+        {
+            args::ParseOutcome::HelpRequested(help) => help,
+            _ => panic!("Help wasn't requested"),
+        };
+
+        let short = help("-h");
+        assert!(short.starts_with("Runs jobs\n"));
+        assert!(short.contains(
+            "    -j, --jobs <VALUE>  Number of jobs [values: exactly 1]\n    -h, --help"
+        ));
+
+        let long = help("--help");
+        assert!(long.starts_with("Runs jobs in parallel\n"));
+        assert!(long.contains(
+            "    -j, --jobs <VALUE>  Number of jobs to run in parallel\n                        \
+             Defaults to the number of CPUs [values: exactly 1]\n\n    -h, --help"
+        ));
+    }
+
+    #[test]
+    fn usage_order() {
+        let specs = [
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+            args::OptionSpec::new('o', "output", "Output", true, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new('a', "all", "All", false, args::OptionPolicy::Exact(0)),
+            args::OptionSpec::new('q', "quiet", "Quiet", false, args::OptionPolicy::Exact(0))
+                .display_order(0),
+        ];
+
+        let order = |order: args::UsageOrder| {
+            let help = args::Help::new("myapp", &specs)
+                .template("{options}")
+                .width(80)
+                .order(order)
+                .render();
+            let mut names = vec!["verbose", "output", "all", "quiet"];
+            names.sort_by_key(|name| help.find(&format!("--{}", name)).unwrap());
+            names
+        };
+
+        assert_eq!(
+            order(args::UsageOrder::Required),
+            ["quiet", "output", "verbose", "all"]
+        );
+        assert_eq!(
+            order(args::UsageOrder::Declaration),
+            ["quiet", "verbose", "output", "all"]
+        );
+        assert_eq!(
+            order(args::UsageOrder::Alphabetical),
+            ["quiet", "all", "output", "verbose"]
+        );
+    }
+
+    #[test]
+    fn help_before_after() {
+        let specs = [args::OptionSpec::new(
+            'v',
+            "verbose",
+            "Verbose",
+            false,
+            args::OptionPolicy::Exact(0),
+        )];
+
+        let outcome = args::ConfigBuilder::new(&specs)
+            .args(&["./myapp", "-h"])
+            .auto_help(true)
+            .help_width(80)
+            .before_help("Licensed under MIT")
+            .after_help("Docs: <https://example.com/docs>")
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err));
+        match outcome {
+            args::ParseOutcome::HelpRequested(help) => {
+                assert!(
+                    help.starts_with("Licensed under MIT\n\nUsage: myapp [OPTIONS]\n\nOptions:\n")
+                );
+                assert!(help.ends_with("information\n\nDocs: <https://example.com/docs>\n"));
+            }
+            _ => panic!("Help wasn't requested"),
+        }
+    }
+
+    #[test]
+    fn write_usage() {
+        let specs = [
+            args::OptionSpec::new('o', "output", "Output", true, args::OptionPolicy::Exact(1)),
+            args::OptionSpec::new('q', "quiet", "Quiet", false, args::OptionPolicy::Exact(0))
+                .section("Logging"),
+        ];
+
+        let mut usage = Vec::new();
+        args::Config::write_usage(&mut usage, &specs, true, true).unwrap();
+        assert_eq!(
+            String::from_utf8(usage).unwrap(),
+            args::Config::generate_usage(&specs, true, true)
+        );
+
+        // A writer which fails once full
+        struct Full(usize);
+        impl std::io::Write for Full {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                match self.0.checked_sub(buf.len()) {
+                    Some(left) => {
+                        self.0 = left;
+                        Ok(buf.len())
+                    }
+                    None => Err(std::io::ErrorKind::WriteZero.into()),
+                }
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        assert!(args::Config::write_usage(&mut Full(10), &specs, true, true).is_err());
+    }
+
+    #[test]
+    fn localized_messages() {
+        struct German;
+        impl args::Messages for German {
+            fn error(&self, err: &args::ParseError) -> String {
+                match err {
+                    args::ParseError::MissingRequired { name } => {
+                        format!("Erforderliche Option '{}' fehlt", name)
+                    }
+                    _ => err.to_string(),
+                }
+            }
+
+            fn help(&self, text: args::HelpText) -> String {
+                match text {
+                    args::HelpText::Usage => String::from("Aufruf"),
+                    args::HelpText::Options => String::from("Optionen"),
+                    args::HelpText::Required => String::from("erforderlich"),
+                    args::HelpText::Values => String::from("Werte"),
+                    _ => text.to_string(),
+                }
+            }
+
+            fn policy(&self, policy: &args::OptionPolicy) -> String {
+                match policy {
+                    args::OptionPolicy::Exact(n) => format!("genau {}", n),
+                    _ => policy.to_string(),
+                }
+            }
+        }
+
+        let specs = [args::OptionSpec::new(
+            'o',
+            "output",
+            "Ausgabedatei",
+            true,
+            args::OptionPolicy::Exact(1),
+        )];
+
+        let err = args::Config::new(&["./myapp"], &specs).err().unwrap();
+        assert_eq!(
+            args::Messages::error(&German, &err),
+            "Erforderliche Option 'output' fehlt"
+        );
+
+        let outcome = args::ConfigBuilder::new(&specs)
+            .args(&["./myapp", "-h"])
+            .auto_help(true)
+            .help_width(100)
+            .messages(&German)
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err));
+        match outcome {
+            args::ParseOutcome::HelpRequested(help) => {
+                assert!(help.starts_with("Aufruf: myapp --output <VALUE>\n\nOptionen:\n"));
+                assert!(help.contains("Ausgabedatei [erforderlich] [Werte: genau 1]\n"));
+            }
+            _ => panic!("Help wasn't requested"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn describe_specs() {
+        let specs = [
+            args::OptionSpec::positional(
+                "FILES",
+                "Input files",
+                true,
+                args::OptionPolicy::AtLeast(1),
+            )
+            .value_hint(args::ValueHint::FilePath),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "When to use colors",
+                false,
+                args::OptionPolicy::AtMost(1),
+            )
+            .choices(&["auto", "never"])
+            .visible_alias("colour")
+            .env("MYAPP_COLOR"),
+            args::OptionSpec::long("help", "Help", false, args::OptionPolicy::FinalizeIgnore())
+                .hidden(),
+        ];
+
+        let description = args::Config::describe(&specs);
+        let files = &description[0];
+        assert_eq!(files["positional"], true);
+        assert_eq!(files["required"], true);
+        assert_eq!(files["short"], serde_json::Value::Null);
+        assert_eq!(
+            files["policy"],
+            serde_json::json!({ "min": 1, "max": null })
+        );
+        assert_eq!(files["value_hint"], "file_path");
+
+        let color = &description[1];
+        assert_eq!(color["short"], "c");
+        assert_eq!(color["description"], "When to use colors");
+        assert_eq!(color["policy"], serde_json::json!({ "min": 0, "max": 1 }));
+        assert_eq!(color["choices"], serde_json::json!(["auto", "never"]));
+        assert_eq!(color["visible_aliases"], serde_json::json!(["colour"]));
+        assert_eq!(color["env"], "MYAPP_COLOR");
+
+        assert_eq!(description[2]["policy"], "finalize_ignore");
+        assert_eq!(description[2]["hidden"], true);
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn load_specs() {
+        let specs = args::Config::specs_from_toml(
+            r#"
+            [[options]]
+            name = "FILES"
+            positional = true
+            description = "Input files"
+            required = true
+            policy = { min = 1 }
+            value_hint = "file_path"
+
+            [[options]]
+            name = "verbose"
+            short = "v"
+            description = "Prints more"
+            duplicates = "append"
+
+            [[options]]
+            name = "color"
+            description = "When to use colors"
+            policy = { min = 0, max = 1 }
+            choices = ["auto", "never"]
+            default_missing = "auto"
+            "#,
+        )
+        .unwrap();
+
+        let config = args::Config::new(&["./test", "a", "b", "-vv", "--color"], &specs).unwrap();
+        assert_eq!(
+            config.option("FILES"),
+            Some(&[String::from("a"), String::from("b")][..])
+        );
+        assert_eq!(config.occurrences("verbose").map(<[_]>::len), Some(2));
+        assert_eq!(config.option("color"), Some(&[String::from("auto")][..]));
+
+        let err = args::Config::specs_from_toml("[[options]]\nname = \"x\"\nshrot = \"x\"")
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "option 'x' has an unknown key 'shrot'");
+    }
+
+    #[test]
+    fn app_metadata() {
+        let app = args::App::new("myapp")
+            .version("1.2.0")
+            .author("Tim Roberts")
+            .about("Processes files")
+            .spec(args::OptionSpec::new(
+                'o',
+                "output",
+                "Output file",
+                false,
+                args::OptionPolicy::Exact(1),
+            ));
+
+        assert_eq!(app.version_string(), "myapp 1.2.0");
+        assert!(app.usage().starts_with(
+            "myapp 1.2.0\nTim Roberts\n\nProcesses files\n\nUsage: myapp [OPTIONS]\n"
+        ));
+
+        match app.parse(&["./myapp", "-h"]).unwrap() {
+            args::ParseOutcome::HelpRequested(text) => assert_eq!(text, app.usage()),
+            _ => panic!("expected help"),
+        }
+        match app.parse(&["./myapp", "-o", "out.txt"]).unwrap() {
+            args::ParseOutcome::Parsed(config) => {
+                assert_eq!(config.single("output"), Some("out.txt"))
+            }
+            _ => panic!("expected the config"),
+        }
+    }
+
+    #[test]
+    fn app_handlers() {
+        let ran = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let (build_ran, main_ran) = (ran.clone(), ran.clone());
+        let build = args::Command::new(
+            "build",
+            "Builds the project",
+            vec![args::OptionSpec::new(
+                'r',
+                "release",
+                "Build with optimizations",
+                false,
+                args::OptionPolicy::Exact(0),
+            )],
+        );
+
+        let app = args::App::new("myapp")
+            .subcommand(build, move |config| {
+                build_ran.borrow_mut().push("build");
+                match config.flag("release") {
+                    true => Ok(()),
+                    false => Err(String::from("debug builds are unsupported")),
+                }
+            })
+            .command(args::Command::new(
+                "clean",
+                "Removes the output",
+                Vec::new(),
+            ))
+            .handler(move |config| {
+                main_ran.borrow_mut().push("main");
+                match config.subcommand() {
+                    Some(("clean", _)) | None => Ok::<(), std::io::Error>(()),
+                    Some(_) => unreachable!(),
+                }
+            });
+
+        assert_eq!(app.run(&["./myapp", "build", "--release"]), 0);
+        assert_eq!(app.run(&["./myapp", "build"]), 1);
+        assert_eq!(app.run(&["./myapp", "clean"]), 0);
+        assert_eq!(app.run(&["./myapp"]), 0);
+        assert_eq!(app.run(&["./myapp", "--unknown"]), 2);
+        assert_eq!(*ran.borrow(), ["build", "build", "main", "main"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn external_subcommand() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join("libcli_external_subcommand");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("libcli-test-plugin");
+        std::fs::write(
+            &path,
+            "#!/bin/sh\n[ \"$1\" = \"--flag\" ] && exit 3\nexit 4\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let paths = std::env::var_os("PATH").unwrap_or_default();
+        let paths = std::iter::once(dir.clone()).chain(std::env::split_paths(&paths));
+        std::env::set_var("PATH", std::env::join_paths(paths).unwrap());
+
+        let specs = [args::OptionSpec::new(
+            'v',
+            "verbose",
+            "Shows verbose output",
+            false,
+            args::OptionPolicy::Exact(0),
+        )];
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./libcli", "-v", "test-plugin", "--flag", "-v"])
+            .external_subcommands("libcli")
+            .build()
+            .unwrap();
+        let external = config.external_subcommand().unwrap();
+        assert!(config.flag("verbose"));
+        assert_eq!(external.name(), "test-plugin");
+        assert_eq!(external.path(), path);
+        assert_eq!(external.args(), ["--flag", "-v"]);
+
+        let app = args::App::new("libcli").external_subcommands(true);
+        assert_eq!(app.run(&["./libcli", "test-plugin", "--flag"]), 3);
+        assert_eq!(app.run(&["./libcli", "test-plugin"]), 4);
+        assert_eq!(app.run(&["./libcli", "missing-plugin"]), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn subcommand_aliases() {
+        let specs = [args::OptionSpec::new(
+            'v',
+            "verbose",
+            "Shows verbose output",
+            false,
+            args::OptionPolicy::Exact(0),
+        )];
+        let commands = [
+            args::Command::new(
+                "build",
+                "Builds the project",
+                vec![args::OptionSpec::new(
+                    'j',
+                    "jobs",
+                    "Number of jobs",
+                    false,
+                    args::OptionPolicy::Exact(1),
+                )
+                .default_values(&["4"])],
+            )
+            .visible_alias("b")
+            .default_subcommand(),
+            args::Command::new("clean", "Removes the output", Vec::new()).alias("c"),
+        ];
+
+        let config =
+            args::Config::new_with_commands(&["./test", "b", "-j", "2"], &specs, &commands)
+                .unwrap();
+        let (name, build) = config.subcommand().unwrap();
+        assert_eq!((name, build.single("jobs")), ("build", Some("2")));
+
+        let config = args::Config::new_with_commands(&["./test", "c"], &specs, &commands).unwrap();
+        assert_eq!(config.command_path(), ["clean"]);
+
+        let config = args::Config::new_with_commands(&["./test", "-v"], &specs, &commands).unwrap();
+        let (name, build) = config.subcommand().unwrap();
+        assert_eq!((name, build.single("jobs")), ("build", Some("4")));
+
+        let help = args::Help::new("test", &specs)
+            .commands(&commands)
+            .width(80)
+            .render();
+        assert_eq!(
+            help,
+            "Usage: test [OPTIONS]\n\nCommands:\n    build, b  Builds the project [default]\n    clean     Removes the output\n\nOptions:\n    -v, --verbose  Shows verbose output\n"
+        );
+    }
+
+    #[test]
+    fn repl_lines() {
+        let greeted = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let names = greeted.clone();
+        let app = args::App::new("myapp").subcommand(
+            args::Command::new(
+                "greet",
+                "Greets someone",
+                vec![args::OptionSpec::positional(
+                    "NAME",
+                    "Who to greet",
+                    true,
+                    args::OptionPolicy::Exact(1),
+                )],
+            ),
+            move |config| {
+                names
+                    .borrow_mut()
+                    .push(config.single("NAME").unwrap_or_default().to_string());
+                Ok::<(), String>(())
+            },
+        );
+        let repl = libcli::repl::Repl::new(&app).exit_commands(&["bye"]);
+
+        assert_eq!(repl.run_line(r#"greet "Tim Roberts""#), Some(0));
+        assert_eq!(repl.run_line("   "), Some(0));
+        assert_eq!(repl.run_line("greet"), Some(2));
+        assert_eq!(repl.run_line("greet 'unterminated"), Some(2));
+        assert_eq!(repl.run_line("exit"), Some(2));
+        assert_eq!(repl.run_line("bye"), None);
+        assert_eq!(*greeted.borrow(), ["Tim Roberts"]);
+    }
+
+    #[test]
+    fn prompt_missing() {
+        let specs = [
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Output file",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new('y', "yes", "Confirms", true, args::OptionPolicy::Exact(0)),
+        ];
+
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "-o", "out.txt", "-y"])
+            .prompt_missing(true)
+            .build()
+            .unwrap();
+        assert_eq!(config.source("output"), Some(&args::Source::Cli));
+
+        // A switch has no values to enter
+        let err = args::ConfigBuilder::new(&specs)
+            .args(&["./test", "-o", "out.txt"])
+            .prompt_missing(true)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            args::ParseError::MissingRequired {
+                name: String::from("yes")
+            }
+        );
+    }
+
+    #[test]
+    fn empty_unnamed_required() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Files",
+                true,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'v',
+                "verbose",
+                "Verbose",
+                false,
+                args::OptionPolicy::Exact(0),
+            ),
+        ];
+
+        // A required unnamed spec accepting no values is satisfied without any
+        let config = args::Config::new(&["./test", "-v"], &specs).unwrap();
+        assert_eq!(config.option("(unnamed)"), Some(&[][..]));
+        let config = args::Config::new(&["./test"], &specs).unwrap();
+        assert_eq!(config.option("(unnamed)"), Some(&[][..]));
+    }
+
+    #[test]
+    fn zsh_completion_large_max() {
+        let specs = [
+            args::OptionSpec::positional("SRC", "Sources", true, args::OptionPolicy::Exact(2)),
+            args::OptionSpec::positional(
+                "DEST",
+                "Destinations",
+                true,
+                args::OptionPolicy::Range(1, usize::MAX),
+            ),
+        ];
+
+        // A small maximum gets a line per argument, a large one a single line taking the rest
+        let script = args::Config::generate_zsh_completion("my-app", &specs, &[]);
+        assert_eq!(script.matches("':SRC: '").count(), 2);
+        assert_eq!(script.matches("'*:DEST: '").count(), 1);
+        assert!(!script.contains("':DEST:"));
+
+        let specs = [args::OptionSpec::new(
+            '\0',
+            "(unnamed)",
+            "Unnamed",
+            false,
+            args::OptionPolicy::AtMost(100_000),
+        )];
+        let script = args::Config::generate_zsh_completion("my-app", &specs, &[]);
+        assert!(script.lines().count() < 100);
+        assert!(script.contains("'*:args: '"));
+    }
+
+    #[test]
+    fn localized_report() {
+        struct German;
+        impl args::Messages for German {
+            fn error(&self, err: &args::ParseError) -> String {
+                match err {
+                    args::ParseError::MissingRequired { name } => {
+                        format!("Erforderliche Option '{}' fehlt", name)
+                    }
+                    _ => err.to_string(),
+                }
+            }
+
+            fn help(&self, text: args::HelpText) -> String {
+                match text {
+                    args::HelpText::Usage => String::from("Aufruf"),
+                    _ => text.to_string(),
+                }
+            }
+
+            fn hint(&self) -> String {
+                String::from("Mehr Informationen mit '--help'")
+            }
+        }
+
+        let specs = [args::OptionSpec::new(
+            'o',
+            "output",
+            "Ausgabedatei",
+            true,
+            args::OptionPolicy::Exact(1),
+        )];
+
+        let err = args::Config::new(&["./myapp"], &specs).err().unwrap();
+        let synopsis = args::Config::generate_synopsis("myapp", &specs);
+        assert_eq!(
+            args::Messages::report(&German, &err, &synopsis),
+            format!(
+                "Erforderliche Option 'output' fehlt\nAufruf: {}\nMehr Informationen mit '--help'\n",
+                synopsis
+            )
+        );
+        assert_eq!(
+            args::Messages::report(&args::English, &err, &synopsis),
+            format!(
+                "Missing required option 'output'\nUsage: {}\nTry '--help' for more information\n",
+                synopsis
+            )
+        );
+
+        // The help of an App is translated as well
+        let app = args::App::new("myapp").specs(specs).messages(German);
+        assert!(app.usage().contains("Aufruf"));
+    }
+
+    #[test]
+    fn unknown_env_custom_var() {
+        std::env::set_var("LIBCLI_CUSTOM_OUT", "out.txt");
+        std::env::set_var("LIBCLI_CUSTOM_FILES", "a");
+        std::env::set_var("LIBCLI_CUSTOM_OTHER", "1");
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            )
+            .env("LIBCLI_CUSTOM_FILES"),
+            args::OptionSpec::new('o', "output", "Output", false, args::OptionPolicy::Exact(1))
+                .env("LIBCLI_CUSTOM_OUT"),
+        ];
+
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test"])
+            .env_prefix("LIBCLI_CUSTOM")
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.single("output"), Some("out.txt"));
+        assert_eq!(
+            config.warnings(),
+            [args::ParseWarning::UnknownEnv {
+                var: "LIBCLI_CUSTOM_OTHER".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn config_to_args_negated() {
+        let specs = [
+            args::OptionSpec::new(
+                '\0',
+                "(unnamed)",
+                "Unnamed",
+                false,
+                args::OptionPolicy::AtLeast(0),
+            ),
+            args::OptionSpec::new(
+                'c',
+                "color",
+                "Colorize the output",
+                false,
+                args::OptionPolicy::Exact(0),
+            )
+            .env("LIBCLI_TEST_TO_ARGS_COLOR")
+            .negatable(),
+        ];
+
+        std::env::set_var("LIBCLI_TEST_TO_ARGS_COLOR", "1");
+
+        let config = args::Config::new(&["./test", "--no-color", "a"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let args = config.to_args(&specs);
+        assert_eq!(args, ["./test", "a", "--no-color"]);
+
+        // The switch stays cleared even though the variable is set
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+        let reparsed = args::Config::new(&args, &specs).unwrap_or_else(|err| panic!("{}", err));
+        assert!(reparsed.option("color").is_none());
+        assert_eq!(reparsed.to_args(&specs), args);
+
+        let config = args::Config::new(&["./test", "--no-color", "-c"], &specs)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.to_args(&specs), ["./test", "--color"]);
+
+        std::env::remove_var("LIBCLI_TEST_TO_ARGS_COLOR");
+    }
+
+    #[test]
+    fn help_template_literal_placeholders() {
+        let specs = [args::OptionSpec::new(
+            'f',
+            "format",
+            "Output format, e.g; '{about}' or '{options}'",
+            false,
+            args::OptionPolicy::Exact(1),
+        )];
+
+        let help = args::Help::new("myapp", &specs)
+            .about("Formats {usage} strings")
+            .after_help("Placeholders: {after-help}")
+            .template("{about}\n{options}\n{after-help} {unknown}")
+            .width(100)
+            .render();
+        assert_eq!(
+            help,
+            "Formats {usage} strings\n    \
+             -f, --format <VALUE>  Output format, e.g; '{about}' or '{options}' [values: exactly 1]\n\n\
+             Placeholders: {after-help} {unknown}\n"
+        );
+    }
+
+    // A writer whose output can be read after it was moved into an Input
+    #[derive(Clone, Default)]
+    struct SharedOutput(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl std::io::Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedOutput {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn prompt_missing_input() {
+        let specs = [args::OptionSpec::new(
+            'o',
+            "output",
+            "Output file",
+            true,
+            args::OptionPolicy::Exact(1),
+        )];
+
+        // An invalid entry is reported and prompted for again
+        let output = SharedOutput::default();
+        let config = args::ConfigBuilder::new(&specs)
+            .args(&["./test"])
+            .prompt_missing(true)
+            .prompt_input(Input::new(
+                "a b\n'my file.txt'\n".as_bytes(),
+                output.clone(),
+            ))
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(config.single("output"), Some("my file.txt"));
+        assert_eq!(config.source("output"), Some(&args::Source::Prompt));
+        assert_eq!(
+            output.text(),
+            "Output file (--output): \
+             2 values supplied for option 'output', expected exactly 1\n\
+             Output file (--output): "
+        );
+
+        // The end of the input fails like a missing option
+        let output = SharedOutput::default();
+        let err = args::ConfigBuilder::new(&specs)
+            .args(&["./test"])
+            .prompt_missing(true)
+            .prompt_input(Input::new("".as_bytes(), output.clone()))
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            args::ParseError::MissingRequired {
+                name: String::from("output")
+            }
+        );
+        assert_eq!(output.text(), "Output file (--output): \n");
+
+        // Nothing is prompted for when the Input isn't interactive
+        let output = SharedOutput::default();
+        let err = args::ConfigBuilder::new(&specs)
+            .args(&["./test"])
+            .prompt_missing(true)
+            .prompt_input(Input::new("out.txt\n".as_bytes(), output.clone()).interactive(false))
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            args::ParseError::MissingRequired {
+                name: String::from("output")
+            }
+        );
+        assert_eq!(output.text(), "");
+    }
+
+    #[test]
+    fn build_records_requests() {
+        let specs = [args::OptionSpec::new(
+            'o',
+            "output",
+            "Output file",
+            false,
+            args::OptionPolicy::Exact(1),
+        )];
+        let commands = [args::Command::new(
+            "build",
+            "Builds the project",
+            vec![args::OptionSpec::new(
+                'r',
+                "release",
+                "Build with optimizations",
+                false,
+                args::OptionPolicy::Exact(0),
+            )],
+        )];
+
+        let build = |args: &[&str]| {
+            args::ConfigBuilder::new(&specs)
+                .args(args)
+                .commands(&commands)
+                .auto_help(true)
+                .version("myapp", "1.2.0")
+                .build()
+                .unwrap_or_else(|err| panic!("{}", err))
+        };
+
+        let config = build(&["./myapp", "--version"]);
+        assert!(config.version_requested());
+        assert!(!config.help_requested());
+
+        let config = build(&["./myapp", "build", "-h"]);
+        assert!(config.help_requested());
+        assert!(!config.version_requested());
+
+        let config = build(&["./myapp", "build", "-r"]);
+        assert!(!config.help_requested());
+        assert!(!config.version_requested());
+    }
+
+    #[test]
+    fn localized_usage() {
+        struct German;
+        impl args::Messages for German {
+            fn help(&self, text: args::HelpText) -> String {
+                match text {
+                    args::HelpText::Required => String::from("erforderlich"),
+                    args::HelpText::Values => String::from("Werte"),
+                    args::HelpText::Default => String::from("Standard"),
+                    _ => text.to_string(),
+                }
+            }
+
+            fn policy(&self, policy: &args::OptionPolicy) -> String {
+                match policy {
+                    args::OptionPolicy::Exact(n) => format!("genau {}", n),
+                    _ => policy.to_string(),
+                }
+            }
+        }
+
+        let specs = [
+            args::OptionSpec::new(
+                'o',
+                "output",
+                "Ausgabedatei",
+                true,
+                args::OptionPolicy::Exact(1),
+            ),
+            args::OptionSpec::new('j', "jobs", "Jobs", false, args::OptionPolicy::Exact(1))
+                .default_values(&["4"]),
+        ];
+
+        assert_eq!(
+            args::Config::generate_usage_with(&specs, true, true, &German),
+            "    -o, --output <VALUE> [erforderlich]\n        \
+             Ausgabedatei\n        \
+             [Werte: genau 1]\n\n    \
+             -j, --jobs <VALUE> \n        \
+             Jobs\n        \
+             [Werte: genau 1] [Standard: 4]\n\n"
+        );
+        assert_eq!(
+            specs[0].usage_with(&German),
+            args::Config::generate_usage_with(&specs, true, false, &German)
+        );
+        assert_eq!(
+            args::Config::generate_usage_wrapped_with(&specs, true, false, Some(80), &German),
+            "    -o, --output <VALUE>  Ausgabedatei [erforderlich] [Werte: genau 1]\n"
+        );
+        assert_eq!(
+            args::Config::generate_usage_colored_with(
+                &specs,
+                true,
+                false,
+                args::ColorChoice::Never,
+                &German
+            ),
+            args::Config::generate_usage_with(&specs, true, false, &German)
+        );
+    }
+
+    #[test]
+    fn help_template_blank_lines() {
+        let specs = [args::OptionSpec::new(
+            'o',
+            "output",
+            "Output file",
+            false,
+            args::OptionPolicy::Exact(1),
+        )];
+        let help = |template: &str| {
+            args::Help::new("myapp", &specs)
+                .about("Processes files\n\n\nQuickly")
+                .template(template)
+                .width(80)
+                .render()
+        };
+
+        // The blank lines written in the template and texts are kept
+        assert_eq!(
+            help("{about}\n\n\n{options}"),
+            "Processes files\n\n\nQuickly\n\n\n    -o, --output <VALUE>  Output file [values: exactly 1]\n"
+        );
+
+        // The ones around empty placeholders are collapsed
+        assert_eq!(
+            help("{before-help}\n\n{about}\n\n{commands}\n\n{options}\n{examples}\n\n{after-help}"),
+            "Processes files\n\n\nQuickly\n\n    -o, --output <VALUE>  Output file [values: exactly 1]\n"
+        );
+        assert_eq!(
+            help("{about}\n\n\n{commands}\n\n{options}"),
+            "Processes files\n\n\nQuickly\n\n\n    -o, --output <VALUE>  Output file [values: exactly 1]\n"
+        );
     }
 }
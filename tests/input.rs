@@ -0,0 +1,277 @@
+#[cfg(test)]
+mod tests {
+    use libcli::input::{Input, NonInteractive};
+    use std::io::{self, BufReader, ErrorKind, Read};
+
+    #[test]
+    fn input_prompts() {
+        let answers = "\nmaybe\ny\n4\n2\nabc\n3000\n\n";
+        let mut input = Input::new(answers.as_bytes(), Vec::new());
+
+        assert!(!input.confirm("Continue?", Some(false)).unwrap());
+        assert!(input.confirm("Continue?", None).unwrap());
+        assert_eq!(input.select("Pick", &["red", "blue"]).unwrap(), 1);
+        assert_eq!(input.read_parse::<u16>("Port", ": ").unwrap(), 3000);
+        assert_eq!(
+            input.read_line_default("Host", ": ", "localhost").unwrap(),
+            "localhost"
+        );
+
+        // The reader has ended
+        assert_eq!(input.read_line_default("Host", ": ", "::1").unwrap(), "::1");
+        assert_eq!(
+            input.read_parse::<u16>("Port", ": ").unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+
+        let (_, output) = input.into_inner();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Continue? [y/N]: Continue? [y/n]: Please answer yes or no\n\
+             Continue? [y/n]: Pick\n   1) red\n   2) blue\n\
+             Choice [1-2]: Please enter a number from 1 to 2\nChoice [1-2]: \
+             Port: Invalid value 'abc': invalid digit found in string\nPort: \
+             Host [localhost]: Host [::1]: Port: \n"
+        );
+    }
+
+    #[test]
+    fn input_non_interactive() {
+        let mut input = Input::new("x\n2\ny\n".as_bytes(), Vec::new())
+            .interactive(false)
+            .non_interactive(NonInteractive::Silent);
+
+        assert_eq!(input.select("Pick", &["red", "blue"]).unwrap(), 1);
+        assert!(input.confirm("Continue?", None).unwrap());
+        assert!(input.into_inner().1.is_empty());
+
+        let mut input = Input::new("y\n".as_bytes(), Vec::new())
+            .interactive(false)
+            .non_interactive(NonInteractive::Fail);
+        assert!(input.confirm("Continue?", None).is_err());
+
+        // An interactive Input prompts regardless
+        let mut input =
+            Input::new("y\n".as_bytes(), Vec::new()).non_interactive(NonInteractive::Fail);
+        assert!(input.confirm("Continue?", None).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "line_edit")]
+    fn input_line_editing() {
+        // Left twice, insert, Ctrl+E, a word, Ctrl+W, another word
+        let answers = "helo\x1b[D\x1b[Dl\x05 world\x17there\r\
+                       abc\x01\x1b[3~x\x1b[F!\x1b[1;5Dy\r\
+                       one two\x1bb\x0b\x7f\x7fe\r\x04";
+        let mut input = Input::new(answers.as_bytes(), Vec::new()).line_editing(true);
+
+        assert_eq!(input.read_line("", "> ").unwrap(), "hello there\n");
+        assert_eq!(input.read_line("", "> ").unwrap(), "yxbc!\n");
+        assert_eq!(input.read_line("", "> ").unwrap(), "one\n");
+        assert_eq!(input.read_line("", "> ").unwrap(), "");
+
+        let (_, output) = input.into_inner();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with("\r> one\x1b[K\n> "));
+    }
+
+    // A reader failing like a closed stdin
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(ErrorKind::BrokenPipe, "closed"))
+        }
+    }
+
+    #[test]
+    fn input_read_errors() {
+        let mut input = Input::new(&b"line\nrest\xff"[..], Vec::new());
+        assert_eq!(input.read_line("", "").unwrap(), "line\n");
+        assert_eq!(input.read_num(4, "", "").unwrap(), "rest");
+        assert_eq!(
+            input.read_num(1, "", "").unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        assert_eq!(
+            input.read_num(1, "", "").unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+        // The end of the reader is an empty line
+        assert_eq!(input.read_line("", "").unwrap(), "");
+        assert_eq!(input.read_all("", "").unwrap(), "");
+
+        let mut input = Input::new("a\nb".as_bytes(), Vec::new());
+        assert_eq!(input.read_all("", "").unwrap(), "a\nb");
+
+        let mut input = Input::new(BufReader::new(Broken), Vec::new());
+        assert_eq!(
+            input.read_line("", "").unwrap_err().kind(),
+            ErrorKind::BrokenPipe
+        );
+        assert_eq!(
+            input.read_all("", "").unwrap_err().kind(),
+            ErrorKind::BrokenPipe
+        );
+        assert_eq!(
+            input.read_num(2, "", "").unwrap_err().kind(),
+            ErrorKind::BrokenPipe
+        );
+        assert_eq!(
+            input.confirm("Continue?", Some(true)).unwrap_err().kind(),
+            ErrorKind::BrokenPipe
+        );
+    }
+
+    #[test]
+    fn input_multi_select() {
+        let items = ["a", "b", "c", "d", "e"];
+        let answers = "1-3\n3-1\n0\nx\n4,2 2 1-2\n\n";
+        let mut input = Input::new(answers.as_bytes(), Vec::new());
+
+        assert_eq!(input.multi_select("Pick", &items).unwrap(), [0, 1, 2]);
+        // Asked again until the choice is valid, duplicates are removed
+        assert_eq!(input.multi_select("Pick", &items).unwrap(), [0, 1, 3]);
+        assert!(input.multi_select("Pick", &items).unwrap().is_empty());
+        assert_eq!(
+            input.multi_select("Pick", &items).unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+
+        let (_, output) = input.into_inner();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "Choices [1-5]: The range '3-1' is reversed\n\
+             Choices [1-5]: Please enter numbers from 1 to 5\n\
+             Choices [1-5]: Please enter numbers from 1 to 5\n\
+             Choices [1-5]: Pick\n"
+        ));
+    }
+
+    #[test]
+    fn input_read_validated() {
+        let validator = |line: &str| match line.contains('@') {
+            true => Ok(()),
+            false => Err(format!("'{}' is not an email address", line)),
+        };
+        let mut input = Input::new("tim\n  tim@example.com \n".as_bytes(), Vec::new());
+
+        assert_eq!(
+            input.read_validated("Email", ": ", validator).unwrap(),
+            "tim@example.com"
+        );
+        assert_eq!(
+            input
+                .read_validated("Email", ": ", validator)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::UnexpectedEof
+        );
+
+        let (_, output) = input.into_inner();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Email: 'tim' is not an email address\nEmail: Email: "
+        );
+    }
+
+    #[test]
+    fn input_confirm() {
+        let mut input = Input::new("\n\n\nYES\nNo\n".as_bytes(), Vec::new());
+
+        // An empty answer takes the default, without one it is asked again
+        assert!(input.confirm("Continue?", Some(true)).unwrap());
+        assert!(!input.confirm("Continue?", Some(false)).unwrap());
+        assert!(input.confirm("Continue?", None).unwrap());
+        assert!(!input.confirm("Continue?", Some(true)).unwrap());
+        // The end of the reader takes the default, or false
+        assert!(input.confirm("Continue?", Some(true)).unwrap());
+        assert!(!input.confirm("Continue?", None).unwrap());
+
+        let (_, output) = input.into_inner();
+        assert!(String::from_utf8(output).unwrap().starts_with(
+            "Continue? [Y/n]: Continue? [y/N]: Continue? [y/n]: Please answer yes or no\n"
+        ));
+    }
+
+    #[test]
+    fn input_select() {
+        let items = ["red", "green", "blue"];
+        let mut input = Input::new("0\nblue\n 3 \n1\n".as_bytes(), Vec::new());
+
+        // Asked again until the number is in range
+        assert_eq!(input.select("Pick", &items).unwrap(), 2);
+        assert_eq!(input.select_item("Pick", &items).unwrap(), &"red");
+        assert_eq!(
+            input.select("Pick", &items).unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+
+        let (_, output) = input.into_inner();
+        assert!(String::from_utf8(output).unwrap().starts_with(
+            "Pick\n   1) red\n   2) green\n   3) blue\n\
+             Choice [1-3]: Please enter a number from 1 to 3\n\
+             Choice [1-3]: Please enter a number from 1 to 3\n\
+             Choice [1-3]: Pick\n"
+        ));
+    }
+
+    #[test]
+    fn input_read_parse_limited() {
+        let mut input = Input::new("a\n-1\nb\nc\n7\n".as_bytes(), Vec::new());
+
+        // Gives up after the attempts, the next line is read by the next prompt
+        assert_eq!(input.read_parse_limited::<u8>("N", ": ", 2).unwrap(), None);
+        assert_eq!(
+            input.read_parse_limited::<u8>("N", ": ", 3).unwrap(),
+            Some(7)
+        );
+        assert_eq!(input.read_parse_limited::<u8>("N", ": ", 3).unwrap(), None);
+
+        let (_, output) = input.into_inner();
+        assert!(String::from_utf8(output).unwrap().starts_with(
+            "N: Invalid value 'a': invalid digit found in string\n\
+             N: Invalid value '-1': invalid digit found in string\n\
+             N: Invalid value 'b'"
+        ));
+    }
+
+    #[test]
+    fn input_read_line_default() {
+        let mut input = Input::new("\r\n9000\r\n 80 \n".as_bytes(), Vec::new());
+
+        assert_eq!(
+            input.read_line_default("Port", ": ", "8080").unwrap(),
+            "8080"
+        );
+        assert_eq!(
+            input.read_line_default("Port", ": ", "8080").unwrap(),
+            "9000"
+        );
+        // Only the newline is removed
+        assert_eq!(
+            input.read_line_default("Port", ": ", "8080").unwrap(),
+            " 80 "
+        );
+        assert_eq!(
+            input.read_line_default("Port", ": ", "8080").unwrap(),
+            "8080"
+        );
+
+        let (_, output) = input.into_inner();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with("Port [8080]: "));
+    }
+
+    #[test]
+    fn input_read_password() {
+        // Without a terminal the line is read as is, only without the newline
+        let mut input = Input::new("s3cret\r\n".as_bytes(), Vec::new());
+        assert_eq!(input.read_password("Password", ": ").unwrap(), "s3cret");
+        assert_eq!(input.read_password("Password", ": ").unwrap(), "");
+
+        let (_, output) = input.into_inner();
+        assert_eq!(String::from_utf8(output).unwrap(), "Password: Password: ");
+    }
+}